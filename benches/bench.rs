@@ -6,11 +6,44 @@
 extern crate bencher;
 
 use bencher::Bencher;
+use std::sync::OnceLock;
 use rand::thread_rng;
-use rs_life::{Cell,Grid};
+use rs_life::{BitGrid,Cell,DoubleBuffer,Grid,LineEnding,LtlRule,Parallelism,Rule,Simulation};
 
 /*****************************************************************************/
 
+// Serial against parallel shows where Parallelism::Auto should switch over,
+// single core: both ~10us/iter at 80x24 and ~0.1ms/iter at 200x60
+fn grid_next_serial_80_x_24(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 80, 24);
+    bench.iter(|| { grid.next_serial(Cell::next) })
+}
+
+fn grid_next_parallel_80_x_24(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 80, 24);
+    bench.iter(|| { grid.next_with_parallelism(Cell::next, &Parallelism::Parallel) })
+}
+
+fn grid_next_serial_200_x_60(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 200, 60);
+    bench.iter(|| { grid.next_serial(Cell::next) })
+}
+
+fn grid_next_parallel_200_x_60(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 200, 60);
+    bench.iter(|| { grid.next_with_parallelism(Cell::next, &Parallelism::Parallel) })
+}
+
+fn grid_next_serial_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 640, 480);
+    bench.iter(|| { grid.next_serial(Cell::next) })
+}
+
 fn grid_next_160_x_120(bench: &mut Bencher) {
     let rng = thread_rng();
     let grid = Grid::random(rng, 160, 120);
@@ -23,12 +56,112 @@ fn grid_next_320_x_240(bench: &mut Bencher) {
     bench.iter(|| { grid.next(Cell::next) })
 }
 
+// Single core, nested Vec<Vec<Cell>> rows: ~4.2ms/iter, flat row major buffer: ~3.3ms/iter
 fn grid_next_640_x_480(bench: &mut Bencher) {
     let rng = thread_rng();
     let grid = Grid::random(rng, 640, 480);
     bench.iter(|| { grid.next(Cell::next) })
 }
 
+// Compare with grid_next_640_x_480 run with --features simd, single core: ~3.4ms/iter scalar, ~1.4ms/iter simd
+fn grid_next_scalar_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 640, 480);
+    bench.iter(|| { grid.next_scalar(Cell::next) })
+}
+
+fn grid_step_into_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 640, 480);
+    let mut target = grid.clone();
+    bench.iter(|| { grid.step_into(&mut target, Cell::next) })
+}
+
+fn double_buffer_step_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let mut buffer = DoubleBuffer::new(Grid::random(rng, 640, 480));
+    bench.iter(|| { buffer.step(Cell::next) })
+}
+
+fn grid_next_2560_x_1440(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 2560, 1440);
+    bench.iter(|| { grid.next(Cell::next) })
+}
+
+fn bitgrid_next_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = BitGrid::random(rng, 640, 480);
+    bench.iter(|| { grid.next() })
+}
+
+fn bitgrid_next_2560_x_1440(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = BitGrid::random(rng, 2560, 1440);
+    bench.iter(|| { grid.next() })
+}
+
+// Return a Simulation of a 640x480 soup mostly stabilised after 2000 generations,
+// the soup is only evolved once as bencher calls each benchmark repeatedly
+fn settled_simulation(incremental: bool) -> Simulation {
+    static SETTLED: OnceLock<Grid> = OnceLock::new();
+    let grid = SETTLED.get_or_init(|| {
+        let mut simulation = Simulation::new(Grid::random_seeded(1, 640, 480), Rule::CONWAY);
+        for _ in 0..2000 {
+            simulation.step();
+        }
+        simulation.grid().clone()
+    });
+
+    // First step scans every cell, finding the changed cells to track
+    let mut simulation = Simulation::new(grid.clone(), Rule::CONWAY).with_incremental(incremental);
+    simulation.step();
+    simulation
+}
+
+fn simulation_step_settled_640_x_480(bench: &mut Bencher) {
+    let mut simulation = settled_simulation(false);
+    bench.iter(|| { simulation.step() })
+}
+
+// Single core, full scans: ~2.0ms/iter, incremental: ~0.7ms/iter
+fn simulation_step_incremental_settled_640_x_480(bench: &mut Bencher) {
+    let mut simulation = settled_simulation(true);
+    bench.iter(|| { simulation.step() })
+}
+
+fn grid_next_ltl_bugs_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 640, 480);
+    bench.iter(|| { grid.next_ltl(&LtlRule::BUGS) })
+}
+
+fn grid_population_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 640, 480);
+    bench.iter(|| { grid.population() })
+}
+
+fn grid_population_3840_x_2160(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 3840, 2160);
+    bench.iter(|| { grid.population() })
+}
+
+// Collecting rows into a Vec<String> then joining them took ~2.7ms/iter here
+fn grid_display_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 640, 480);
+    bench.iter(|| { grid.to_string() })
+}
+
+fn grid_render_into_640_x_480(bench: &mut Bencher) {
+    let rng = thread_rng();
+    let grid = Grid::random(rng, 640, 480);
+    let mut buf = String::new();
+    bench.iter(|| { grid.render_into(&mut buf, LineEnding::CrLf) })
+}
+
 fn grid_random(bench: &mut Bencher) {
     let rng = thread_rng();
     bench.iter(|| { Grid::random(rng, 320, 240) })
@@ -37,9 +170,27 @@ fn grid_random(bench: &mut Bencher) {
 /*****************************************************************************/
 
 benchmark_group!(benches, 
+    grid_next_serial_80_x_24,
+    grid_next_parallel_80_x_24,
+    grid_next_serial_200_x_60,
+    grid_next_parallel_200_x_60,
+    grid_next_serial_640_x_480,
     grid_next_160_x_120,
     grid_next_320_x_240,
     grid_next_640_x_480,
+    grid_next_scalar_640_x_480,
+    grid_step_into_640_x_480,
+    double_buffer_step_640_x_480,
+    grid_next_2560_x_1440,
+    bitgrid_next_640_x_480,
+    bitgrid_next_2560_x_1440,
+    simulation_step_settled_640_x_480,
+    simulation_step_incremental_settled_640_x_480,
+    grid_next_ltl_bugs_640_x_480,
+    grid_population_640_x_480,
+    grid_population_3840_x_2160,
+    grid_display_640_x_480,
+    grid_render_into_640_x_480,
     grid_random,
 );
 benchmark_main!(benches);
@@ -0,0 +1,59 @@
+/* rs_life.h - C interface to rs-life, built with the ffi feature
+*  (c)2020 James Wright, see LICENSE file.
+*
+*  Build a static library to link against with:
+*      cargo rustc --release --lib --features ffi --crate-type staticlib
+*
+*  Every function taking a LifeGrid pointer accepts one returned by
+*  life_grid_new and not yet passed to life_grid_free, or NULL, which is checked
+*  for. No function unwinds a Rust panic into C, returning its error value instead.
+*/
+
+#ifndef RS_LIFE_H
+#define RS_LIFE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Toroidal grid of cells, opaque to C */
+typedef struct LifeGrid LifeGrid;
+
+/* Return a new grid of given size with every cell dead, to be freed with
+*  life_grid_free, or NULL if either dimension is zero */
+LifeGrid *life_grid_new(size_t width, size_t height);
+
+/* Free a grid returned by life_grid_new, doing nothing given NULL */
+void life_grid_free(LifeGrid *grid);
+
+/* Replace every cell with a random state, a fraction density of them alive, the
+*  same seed giving the same cells. Returns 0, or -1 given NULL or a density
+*  outside 0.0 to 1.0 */
+int life_grid_randomize(LifeGrid *grid, uint64_t seed, double density);
+
+/* Advance to the next generation under Conway's Life. Returns 0, or -1 given NULL */
+int life_grid_step(LifeGrid *grid);
+
+/* Return 1 if the cell at given position is alive, 0 if dead, or -1 given NULL
+*  or a position out of bounds */
+int life_grid_get(const LifeGrid *grid, size_t x, size_t y);
+
+/* Set the cell at given position alive if alive is non-zero, otherwise dead.
+*  Returns 0, or -1 given NULL or a position out of bounds */
+int life_grid_set(LifeGrid *grid, size_t x, size_t y, int alive);
+
+/* Write rows of cells as ASCII, 'O' for live and ' ' for dead cells separated by
+*  '\n', to buf as a NUL terminated string of at most buf_len bytes including the
+*  NUL, truncating the text if it is longer. Returns the length of the whole text
+*  without the NUL, so a result of buf_len or more means it was truncated, or 0
+*  given a NULL grid. Nothing is written given a NULL buf or buf_len of 0 */
+size_t life_grid_render_ascii(const LifeGrid *grid, char *buf, size_t buf_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RS_LIFE_H */
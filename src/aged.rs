@@ -0,0 +1,181 @@
+/* aged.rs - Grid tracking the number of generations each cell has been alive
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Cell, Grid, MaybeSync, Rule};
+
+/*****************************************************************************/
+
+// Grid with the age of each live cell, 0 on birth and one more for each survival
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgedGrid {
+    grid:   Grid,
+    ages:   Vec<u32>
+}
+
+impl AgedGrid {
+    // Return an AgedGrid of given Grid, every live cell being newborn
+    pub fn new(grid: Grid) -> AgedGrid {
+        let ages = vec![0; grid.cells.len()];
+        AgedGrid { grid, ages }
+    }
+
+    // Return the Grid of live cells
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    // Return number of cells in a row
+    pub fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    // Return number of rows
+    pub fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    // Return age of cell at given position, or None if dead or out of bounds
+    pub fn age(&self, x: usize, y: usize) -> Option<u32> {
+        match self.grid.get(x, y) {
+            Some(cell) if cell.alive => Some(self.ages[y * self.grid.width + x]),
+            _ => None
+        }
+    }
+
+    // Return next AgedGrid state using given cell function, survivors ageing by one
+    pub fn next<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, cell_func: F) -> AgedGrid {
+        let grid = self.grid.next(cell_func);
+        let ages = self.grid.cells.iter().zip(&grid.cells).zip(&self.ages)
+            .map(|((was, is), &age)| match (was.alive, is.alive) {
+                (true, true) => age.saturating_add(1),
+                _ => 0
+            })
+            .collect();
+        AgedGrid { grid, ages }
+    }
+
+    // Return next AgedGrid state using given Rule
+    pub fn next_with_rule(&self, rule: &Rule) -> AgedGrid {
+        self.next(|cell, neighbours| rule.apply(cell, neighbours))
+    }
+
+    // Take grid as the next state, as stepped elsewhere, survivors ageing by one.
+    // Every live cell is newborn given a Grid of another size
+    pub(crate) fn record(&mut self, grid: &Grid) {
+        if (grid.width, grid.height) != (self.grid.width, self.grid.height) {
+            *self = AgedGrid::new(grid.clone());
+            return;
+        }
+        for ((age, was), is) in self.ages.iter_mut().zip(&self.grid.cells).zip(&grid.cells) {
+            *age = if was.alive && is.alive { age.saturating_add(1) } else { 0 };
+        }
+        self.grid.cells.clone_from(&grid.cells);
+    }
+
+    // Render each cell as the char cell_func returns given the cell and its age
+    pub fn to_string_with<F: Fn(&Cell, Option<u32>) -> char>(&self, cell_func: F) -> String {
+        let width = self.grid.width;
+        self.grid.cells.chunks(width.max(1)).enumerate()
+            .map(|(y, row)| row.iter().enumerate()
+                .map(|(x, cell)| cell_func(cell, self.age(x, y)))
+                .collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl From<Grid> for AgedGrid {
+    fn from(grid: Grid) -> AgedGrid {
+        AgedGrid::new(grid)
+    }
+}
+
+impl fmt::Display for AgedGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.grid.fmt(f)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_should_age_block_cells_by_one_each_step() {
+        // given
+        let mut aged = AgedGrid::new(Grid::from_coords(4, 4, vec![(1, 1), (2, 1), (1, 2), (2, 2)]).unwrap());
+
+        for steps in 1..=10 {
+            // when
+            aged = aged.next_with_rule(&Rule::CONWAY);
+
+            // then
+            for &(x, y) in &[(1, 1), (2, 1), (1, 2), (2, 2)] {
+                assert_eq!(aged.age(x, y), Some(steps));
+            }
+            assert_eq!(aged.age(0, 0), None);
+        }
+    }
+
+    #[test]
+    fn next_should_reset_age_of_blinker_ends_on_birth() {
+        // given
+        let mut aged = AgedGrid::new(Grid::from_coords(5, 5, vec![(1, 2), (2, 2), (3, 2)]).unwrap());
+
+        for steps in 1..=6 {
+            // when
+            aged = aged.next(Cell::next);
+
+            // then
+            assert_eq!(aged.age(2, 2), Some(steps));
+            let (born, died) = if steps % 2 == 1 { ([(2, 1), (2, 3)], [(1, 2), (3, 2)]) } else { ([(1, 2), (3, 2)], [(2, 1), (2, 3)]) };
+            for &(x, y) in &born {
+                assert_eq!(aged.age(x, y), Some(0));
+            }
+            for &(x, y) in &died {
+                assert_eq!(aged.age(x, y), None);
+            }
+        }
+    }
+
+    #[test]
+    fn record_should_age_survivors_of_grid_stepped_elsewhere() {
+        // given
+        let blinker = Grid::from_coords(5, 5, vec![(1, 2), (2, 2), (3, 2)]).unwrap();
+        let mut aged = AgedGrid::new(blinker.clone());
+
+        // when
+        aged.record(&blinker.next_with_rule(&Rule::CONWAY));
+
+        // then
+        assert_eq!(aged, AgedGrid::new(blinker.clone()).next_with_rule(&Rule::CONWAY));
+        aged.record(&Grid::from_coords(3, 3, vec![(1, 1)]).unwrap());
+        assert_eq!((aged.width(), aged.age(1, 1)), (3, Some(0)));
+    }
+
+    #[test]
+    fn to_string_with_should_pass_ages_and_display_should_match_grid() {
+        // given
+        let aged = AgedGrid::new(Grid::from_coords(5, 3, vec![(1, 1), (2, 1), (3, 1)]).unwrap()).next(Cell::next);
+
+        // when
+        let text = aged.to_string_with(|_, age| match age {
+            Some(0) => 'n',
+            Some(_) => 'O',
+            None    => ' '
+        });
+
+        // then
+        assert_eq!(text, "  n  \n  O  \n  n  ");
+        assert_eq!(aged.to_string(), aged.grid().to_string());
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,180 @@
+/* animation.rs - Recording Simulation runs as animated GIFs
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+use ::gif::{Encoder, EncodingError, Frame, Repeat};
+
+use crate::{Grid, LifeError, Simulation};
+
+/*****************************************************************************/
+
+// Palette indices of dead and live cells
+const DEAD_INDEX: u8 = 0;
+const LIVE_INDEX: u8 = 1;
+
+// Options for Simulation::record_gif
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GifOptions {
+    // Width and height in pixels of each cell
+    pub cell_px:        u16,
+    // Time each frame is shown in hundredths of a second
+    pub delay_centis:   u16,
+    // RGB colours of live and dead cells
+    pub live:           [u8; 3],
+    pub dead:           [u8; 3],
+    // Number of generations per frame, 1 recording every generation
+    pub frame_every:    usize
+}
+
+impl Default for GifOptions {
+    fn default() -> GifOptions {
+        GifOptions { cell_px: 4, delay_centis: 10, live: [0, 255, 0], dead: [0, 0, 0], frame_every: 1 }
+    }
+}
+
+/*****************************************************************************/
+
+impl Simulation {
+    // Advance given number of generations, writing the grid as a frame of a looping
+    // GIF to writer before each generation that is a multiple of frame_every. Each
+    // frame is encoded as it is drawn so memory stays bounded by one frame. Returns
+    // an error if the scaled grid is too large for a GIF or writing fails
+    pub fn record_gif<W: Write>(&mut self, writer: W, generations: usize, options: GifOptions) -> Result<(), LifeError> {
+        let frame_every = options.frame_every.max(1);
+        let width = gif_dimension(self.grid().width(), options.cell_px)?;
+        let height = gif_dimension(self.grid().height(), options.cell_px)?;
+        let palette = [options.dead, options.live].concat();
+
+        let mut encoder = Encoder::new(writer, width, height, &palette).map_err(encoding_error)?;
+        encoder.set_repeat(Repeat::Infinite).map_err(encoding_error)?;
+
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for generation in 0..generations {
+            if generation % frame_every == 0 {
+                draw_pixels(self.grid(), options.cell_px as usize, &mut pixels);
+                let frame = Frame {
+                    width, height, delay: options.delay_centis, buffer: Cow::Borrowed(&pixels), ..Frame::default()
+                };
+                encoder.write_frame(&frame).map_err(encoding_error)?;
+            }
+            self.step();
+        }
+
+        encoder.into_inner().map_err(encoding_error)?;
+        Ok(())
+    }
+}
+
+// Return given number of cells scaled by cell_px as a GIF dimension
+fn gif_dimension(cells: usize, cell_px: u16) -> Result<u16, LifeError> {
+    cells.checked_mul(cell_px as usize)
+        .and_then(|pixels| u16::try_from(pixels).ok())
+        .filter(|&pixels| pixels > 0)
+        .ok_or_else(|| LifeError::Io {
+            kind: io::ErrorKind::InvalidInput,
+            msg: format!("{} cells of {} pixels do not fit a GIF dimension", cells, cell_px)
+        })
+}
+
+// Replace pixels with palette indices of the cells of grid, each scaled to a
+// square cell_px pixels across
+fn draw_pixels(grid: &Grid, cell_px: usize, pixels: &mut Vec<u8>) {
+    pixels.clear();
+    for row in grid.rows() {
+        let start = pixels.len();
+        for cell in row {
+            let index = if cell.alive { LIVE_INDEX } else { DEAD_INDEX };
+            pixels.extend(std::iter::repeat_n(index, cell_px));
+        }
+        for _ in 1..cell_px {
+            pixels.extend_from_within(start..start + grid.width() * cell_px);
+        }
+    }
+}
+
+// Convert a GIF encoding error to a LifeError
+fn encoding_error(err: EncodingError) -> LifeError {
+    match err {
+        EncodingError::Io(err) => LifeError::from(err),
+        err => LifeError::Io { kind: io::ErrorKind::InvalidData, msg: err.to_string() }
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rule;
+
+    fn blinker() -> Simulation {
+        Simulation::new(Grid::from_text(".....\n..O..\n..O..\n..O..\n.....", 'O').unwrap(), Rule::CONWAY)
+    }
+
+    fn record(simulation: &mut Simulation, generations: usize, options: GifOptions) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        simulation.record_gif(&mut bytes, generations, options).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn record_gif_should_write_a_frame_per_generation() {
+        // given
+        let mut simulation = blinker();
+
+        // when
+        let bytes = record(&mut simulation, 10, GifOptions::default());
+
+        // then
+        assert_eq!(&bytes[..6], b"GIF89a");
+        assert_eq!(simulation.generation(), 10);
+
+        let mut decoder = ::gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (20, 20));
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            frames.push(frame.buffer.to_vec());
+        }
+        assert_eq!(frames.len(), 10);
+        // Vertical then horizontal blinker rows through the centre cell
+        assert_eq!(&frames[0][10 * 20..11 * 20], &[0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&frames[1][10 * 20..11 * 20], &[0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(frames[2], frames[0]);
+    }
+
+    #[test]
+    fn record_gif_should_be_deterministic_and_skip_frames() {
+        // given
+        let options = GifOptions { cell_px: 2, frame_every: 3, ..GifOptions::default() };
+
+        // when
+        let bytes = record(&mut blinker(), 10, options);
+
+        // then
+        assert_eq!(bytes, record(&mut blinker(), 10, options));
+        let mut decoder = ::gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+    }
+
+    #[test]
+    fn record_gif_should_return_error_given_grid_too_large() {
+        // given
+        let mut simulation = Simulation::new(Grid::new(20_000, 2).unwrap(), Rule::CONWAY);
+
+        // then
+        assert!(matches!(simulation.record_gif(Vec::new(), 1, GifOptions::default()),
+            Err(LifeError::Io { kind: io::ErrorKind::InvalidInput, .. })));
+        assert_eq!(simulation.generation(), 0);
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,233 @@
+/* apgcode.rs - Catagolue apgcodes for still lifes, oscillators and spaceships
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Boundary, Grid, InsertMode, LifeError, Overflow, Rule};
+
+/*****************************************************************************/
+
+const CHARS:        &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const STRIP_HEIGHT: usize = 5;
+// Longest run of dead columns written with a single 'y'
+const MAX_RUN:      usize = 4 + 35;
+// Generations evolved looking for the pattern to repeat
+const MAX_PERIOD:   usize = 256;
+
+// Return extended Wechsler text of the Grid as it stands. Each strip of 5 rows
+// is written as its columns, one character per column with the top row in the
+// least significant bit, runs of empty columns shortened to '0', 'w', 'x' or
+// 'y' and a count, and strips separated by 'z'
+fn wechsler(grid: &Grid) -> String {
+    let mut text = String::new();
+    for v in 0..grid.height().div_ceil(STRIP_HEIGHT) {
+        if v > 0 {
+            text.push('z');
+        }
+        let mut zeroes = 0;
+        for x in 0..grid.width() {
+            let column = (0..STRIP_HEIGHT)
+                .filter(|w| grid.get(x, STRIP_HEIGHT * v + w).is_some_and(|cell| cell.is_alive()))
+                .fold(0, |column, w| column | 1 << w);
+            if column == 0 {
+                zeroes += 1;
+                continue;
+            }
+            while zeroes > 0 {
+                let run = zeroes.min(MAX_RUN);
+                match run {
+                    1 => text.push('0'),
+                    2 => text.push('w'),
+                    3 => text.push('x'),
+                    _ => { text.push('y'); text.push(CHARS[run - 4] as char) }
+                }
+                zeroes -= run;
+            }
+            text.push(CHARS[column] as char);
+        }
+    }
+    text
+}
+
+// Return the Grid cropped to the bounding box of its live cells and the
+// position of that box, or None if no cells are alive
+fn crop(grid: &Grid) -> Option<(Grid, usize, usize)> {
+    let (min_x, min_y, max_x, max_y) = grid.bounding_box()?;
+    let cropped = grid.extract(min_x, min_y, max_x - min_x + 1, max_y - min_y + 1).ok()?;
+    Some((cropped, min_x, min_y))
+}
+
+// Return the next generation of a cropped pattern on an unbounded plane,
+// cropped again, and how far its bounding box moved, or None if it died
+fn step(pattern: &Grid, rule: &Rule) -> Option<(Grid, isize, isize)> {
+    let mut padded = Grid::new(pattern.width() + 2, pattern.height() + 2).ok()?;
+    padded.insert(pattern, 1, 1, InsertMode::Replace, Overflow::Clip);
+    let next = padded.next_with(|cell, neighbours| rule.apply(cell, neighbours), rule.neighbourhood(), Boundary::Dead);
+    let (cropped, x, y) = crop(&next)?;
+    Some((cropped, x as isize - 1, y as isize - 1))
+}
+
+// Return the shortest and lexicographically least extended Wechsler text of the
+// cropped phases in any orientation, or None if there are no phases
+pub(crate) fn least_wechsler(phases: &[Grid]) -> Option<String> {
+    phases.iter()
+        .flat_map(Grid::orientations)
+        .map(|orientation| wechsler(&orientation))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return the apgcode of the live cells under Conway's Life, prefixed xs and
+    // the population for a still life, xp and the period for an oscillator or
+    // xq and the period for a spaceship, then the shortest and lexicographically
+    // least extended Wechsler text over all phases and orientations. An empty
+    // Grid is xs0_0, None if the pattern dies or does not repeat within 256
+    // generations
+    pub fn to_apgcode(&self) -> Option<String> {
+        let start = match crop(self) {
+            Some((start, _, _)) => start,
+            None => return Some("xs0_0".to_string())
+        };
+
+        let mut phases = vec![start.clone()];
+        let (mut dx, mut dy) = (0, 0);
+        for period in 1..=MAX_PERIOD {
+            let (next, x, y) = step(phases.last()?, &Rule::CONWAY)?;
+            dx += x;
+            dy += y;
+            if next != start {
+                phases.push(next);
+                continue;
+            }
+
+            let prefix = match (period, (dx, dy)) {
+                (1, (0, 0)) => format!("xs{}", start.population()),
+                (_, (0, 0)) => format!("xp{}", period),
+                _ => format!("xq{}", period)
+            };
+            return Some(format!("{}_{}", prefix, least_wechsler(&phases)?));
+        }
+        None
+    }
+
+    // Return a Grid sized to the bounding box of the cells of an apgcode
+    pub fn from_apgcode(code: &str) -> Result<Grid, LifeError> {
+        let error = |msg: String| LifeError::ParseError { line: 1, msg };
+
+        let body = match code.split_once('_') {
+            Some((prefix, body)) if ["xs", "xp", "xq"].contains(&prefix.get(..2).unwrap_or(""))
+                && prefix.len() > 2 && prefix[2..].bytes().all(|b| b.is_ascii_digit()) => body,
+            _ => return Err(error(format!("'{}' should be xs, xp or xq, a number, '_' then cells", code)))
+        };
+
+        let mut coords = Vec::new();
+        for (v, strip) in body.split('z').enumerate() {
+            let mut x = 0;
+            let mut chars = strip.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    'w' => x += 2,
+                    'x' => x += 3,
+                    'y' => match chars.next().and_then(|count| CHARS.iter().position(|&b| b as char == count)) {
+                        Some(count) => x += 4 + count,
+                        None => return Err(error(format!("'{}' has 'y' without a following count", code)))
+                    },
+                    '0'..='9' | 'a'..='v' => {
+                        let column = CHARS.iter().position(|&b| b as char == c).unwrap_or(0);
+                        coords.extend((0..STRIP_HEIGHT).filter(|w| column & 1 << w != 0).map(|w| (x, STRIP_HEIGHT * v + w)));
+                        x += 1;
+                    },
+                    _ => return Err(error(format!("'{}' contains unexpected '{}'", code, c)))
+                }
+            }
+        }
+
+        if coords.is_empty() {
+            return Err(LifeError::EmptyPattern);
+        }
+        let width  = coords.iter().map(|&(x, _)| x).max().unwrap_or(0) + 1;
+        let height = coords.iter().map(|&(_, y)| y).max().unwrap_or(0) + 1;
+        Grid::from_coords(width, height, coords)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_apgcode_should_match_published_codes() {
+        for &(text, code) in &[
+            ("OO\nOO",                      "xs4_33"),
+            (".OO.\nO..O\n.OO.",            "xs6_696"),
+            ("OOO",                         "xp2_7"),
+            (".O.\n..O\nOOO",               "xq4_153"),
+            (".O..O\nO....\nO...O\nOOOO.",  "xq4_6frc")] {
+            // given
+            let grid = Grid::from_text(text, 'O').unwrap();
+
+            // then
+            assert_eq!(grid.to_apgcode(), Some(code.to_string()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn to_apgcode_should_ignore_orientation_phase_and_position() {
+        // given
+        let glider = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+        let mut grid = Grid::new(12, 10).unwrap();
+        grid.insert(&glider.rotate_cw().flip_vertical(), 6, 5, InsertMode::Replace, Overflow::Clip);
+
+        // then
+        for _ in 0..4 {
+            assert_eq!(grid.to_apgcode(), Some("xq4_153".to_string()));
+            grid = grid.next_with_rule(&Rule::CONWAY);
+        }
+    }
+
+    #[test]
+    fn to_apgcode_should_return_none_given_unstable_pattern() {
+        assert_eq!(Grid::from_text("OO", 'O').unwrap().to_apgcode(), None);
+        assert_eq!(Grid::new(4, 4).unwrap().to_apgcode(), Some("xs0_0".to_string()));
+    }
+
+    #[test]
+    fn from_apgcode_should_round_trip_published_codes() {
+        for code in &["xs4_33", "xs6_696", "xp2_7", "xq4_153", "xq4_6frc", "xp15_4r4z4r4", "xs5_253", "xs7_2596"] {
+            // when
+            let grid = Grid::from_apgcode(code).unwrap();
+
+            // then
+            assert_eq!(grid.to_apgcode(), Some(code.to_string()));
+        }
+    }
+
+    #[test]
+    fn from_apgcode_should_expand_runs_of_empty_columns() {
+        // when
+        let grid = Grid::from_apgcode("xs0_101w1x1y01y11").unwrap();
+
+        // then
+        assert_eq!(grid.width(), 21);
+        assert_eq!(grid.live_cells().map(|(x, _)| x).collect::<Vec<usize>>(), vec![0, 2, 5, 9, 14, 20]);
+    }
+
+    #[test]
+    fn from_apgcode_should_return_error_given_invalid_code() {
+        assert_eq!(Grid::from_apgcode("153").unwrap_err().to_string(),
+            "line 1: '153' should be xs, xp or xq, a number, '_' then cells");
+        assert_eq!(Grid::from_apgcode("xq4_15!").unwrap_err().to_string(),
+            "line 1: 'xq4_15!' contains unexpected '!'");
+        assert_eq!(Grid::from_apgcode("xs4_3y").unwrap_err().to_string(),
+            "line 1: 'xs4_3y' has 'y' without a following count");
+        assert_eq!(Grid::from_apgcode("xs0_0"), Err(LifeError::EmptyPattern));
+    }
+}
+
+/*****************************************************************************/
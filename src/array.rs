@@ -0,0 +1,127 @@
+/* array.rs - Converting Grids to and from ndarray arrays
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+// Arrays have a row per y and a column per x, so the cell at (x, y) is at index
+// [y, x] and an array's dim() is (height, width)
+
+use ndarray::Array2;
+
+use crate::{Cell, Grid, LifeError};
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return the cells as an array of height rows of width elements, 1 for live
+    // cells and 0 for dead cells
+    pub fn to_array2(&self) -> Array2<u8> {
+        self.array_of(self.cells.iter().map(|cell| cell.alive as u8).collect())
+    }
+
+    // Return a Grid of the elements of an array of rows, any nonzero element a live
+    // cell. Returns an error given an array with no rows or columns
+    pub fn from_array2(array: &Array2<u8>) -> Result<Grid, LifeError> {
+        let (height, width) = array.dim();
+        let mut grid = Grid::new(width, height)?;
+
+        // Arrays in row major order are read as one slice, others a row at a time
+        match array.as_slice() {
+            Some(elements) => {
+                for (cell, &element) in grid.cells.iter_mut().zip(elements) {
+                    *cell = Cell::from(element != 0);
+                }
+            }
+            None => {
+                for (cells, row) in grid.cells.chunks_mut(width).zip(array.rows()) {
+                    for (cell, &element) in cells.iter_mut().zip(row) {
+                        *cell = Cell::from(element != 0);
+                    }
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    // Return an array of the number of live neighbours of each cell, wrapping
+    // around the edges as next does
+    pub fn neighbour_counts_array2(&self) -> Array2<u8> {
+        self.array_of(self.neighbour_counts().concat())
+    }
+
+    // Return an array of an element per cell in row major order
+    fn array_of(&self, elements: Vec<u8>) -> Array2<u8> {
+        Array2::from_shape_vec((self.height, self.width), elements)
+            .unwrap_or_else(|_| unreachable!("there is an element per cell"))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn to_array2_should_index_cells_by_row_then_column() {
+        // given
+        let grid = Grid::from_text("O..\n..O", 'O').unwrap();
+
+        // when
+        let array = grid.to_array2();
+
+        // then
+        assert_eq!(array.dim(), (2, 3));
+        assert_eq!(array[[1, 2]], 1);
+        assert_eq!(array[[0, 2]], 0);
+        assert_eq!(array, array![[1, 0, 0], [0, 0, 1]]);
+    }
+
+    #[test]
+    fn from_array2_should_treat_nonzero_elements_as_alive() {
+        // given
+        let array = array![[0, 7, 0], [255, 0, 1]];
+
+        // when
+        let grid = Grid::from_array2(&array).unwrap();
+
+        // then
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid, Grid::from_text(".O.\nO.O", 'O').unwrap());
+        assert_eq!(grid.to_array2(), array.mapv(|element| (element != 0) as u8));
+    }
+
+    #[test]
+    fn from_array2_should_read_arrays_not_in_row_major_order() {
+        // given
+        let array = array![[1, 0], [0, 0], [1, 1]].reversed_axes();
+
+        // then
+        assert!(array.as_slice().is_none());
+        assert_eq!(Grid::from_array2(&array).unwrap(), Grid::from_text("O.O\n..O", 'O').unwrap());
+    }
+
+    #[test]
+    fn from_array2_should_return_error_given_empty_array() {
+        assert_eq!(Grid::from_array2(&Array2::zeros((0, 4))), Err(LifeError::ZeroDimension { width: 4, height: 0 }));
+    }
+
+    #[test]
+    fn neighbour_counts_array2_should_count_live_neighbours_wrapping_around_edges() {
+        // given
+        let grid = Grid::from_text("OO...\nOO...\n.....\n.....", 'O').unwrap();
+
+        // when
+        let counts = grid.neighbour_counts_array2();
+
+        // then
+        assert_eq!(counts, array![
+            [3, 3, 2, 0, 2],
+            [3, 3, 2, 0, 2],
+            [2, 2, 1, 0, 1],
+            [2, 2, 1, 0, 1]
+        ]);
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,291 @@
+/* bitgrid.rs - Bit packed Grid storage
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use rand::Rng;
+use alloc::vec::Vec;
+
+use crate::par::*;
+use crate::{Cell, Grid, LifeError, Rule};
+
+/*****************************************************************************/
+
+const WORD_BITS: usize = 64;
+
+// Grid storing each row as words of 64 cells, bit i of word w being the cell
+// at x = 64 * w + i. Bits beyond the width are always clear
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BitGrid {
+    rows:   Vec<Vec<u64>>,
+    width:  usize,
+    height: usize
+}
+
+impl BitGrid {
+    // Return a BitGrid of dead cells, dimensions must be non-zero
+    pub fn new(width: usize, height: usize) -> Result<BitGrid, LifeError> {
+        if width == 0 || height == 0 {
+            return Err(LifeError::ZeroDimension { width, height });
+        }
+        Ok(BitGrid::dead(width, height))
+    }
+
+    // Return a BitGrid of randomised cell states, drawing from rng in the same
+    // order as Grid::random so that both give the same cells
+    pub fn random<R: Rng>(mut rng: R, width: usize, height: usize) -> BitGrid {
+        let mut grid = BitGrid::dead(width, height);
+        for row in &mut grid.rows {
+            for x in 0..width {
+                if rng.gen_bool(0.5) {
+                    row[x / WORD_BITS] |= 1 << (x % WORD_BITS);
+                }
+            }
+        }
+        grid
+    }
+
+    // Return a BitGrid of dead cells of any size
+    fn dead(width: usize, height: usize) -> BitGrid {
+        BitGrid { rows: vec![vec![0; width.div_ceil(WORD_BITS)]; height], width, height }
+    }
+
+    // Return number of cells in a row
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // Return number of rows
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Return number of live cells
+    pub fn population(&self) -> usize {
+        self.rows.par_iter()
+            .map(|row| row.iter().map(|word| word.count_ones() as usize).sum::<usize>())
+            .sum()
+    }
+
+    // Return cell at given position, or None if out of bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(Cell::new(self.rows[y][x / WORD_BITS] & (1 << (x % WORD_BITS)) != 0))
+    }
+
+    // Set state of cell at given position
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) -> Result<(), LifeError> {
+        if x >= self.width || y >= self.height {
+            return Err(LifeError::OutOfBounds { x, y });
+        }
+        let word = &mut self.rows[y][x / WORD_BITS];
+        if alive { *word |= 1 << (x % WORD_BITS) } else { *word &= !(1 << (x % WORD_BITS)) }
+        Ok(())
+    }
+
+    // Return next BitGrid state under Conway's Life, wrapping around the edges
+    pub fn next(&self) -> BitGrid {
+        self.next_with_rule(&Rule::CONWAY)
+    }
+
+    // Return next BitGrid state using given birth/survival rule, counting
+    // neighbours in its Neighbourhood and wrapping around the edges. Neighbour
+    // counts of 64 cells at a time are summed with bitwise adders into four
+    // bit planes, then compared against each count the rule allows
+    pub fn next_with_rule(&self, rule: &Rule) -> BitGrid {
+        let (width, height) = (self.width, self.height);
+        if width == 0 || height == 0 {
+            return self.clone();
+        }
+
+        let offsets = rule.neighbourhood().offsets();
+        let size = offsets.len() as u8;
+        let (births, survivals): (Vec<u8>, Vec<u8>) = (
+            (0..=size).filter(|&n| rule.birth(n)).collect(),
+            (0..=size).filter(|&n| rule.survival(n)).collect());
+        let last_mask = match width % WORD_BITS {
+            0 => !0,
+            bits => (1 << bits) - 1
+        };
+
+        // Each row shifted so that bit x holds the cell at x - 1, x and x + 1, indexed by dx + 1
+        let shifted: Vec<[Vec<u64>; 3]> = self.rows.par_iter()
+            .map(|row| [west(row, width, last_mask), row.clone(), east(row, width)])
+            .collect();
+
+        let rows = (0..height).into_par_iter()
+            .map(|y| {
+                let neighbours: Vec<&[u64]> = offsets.iter()
+                    .map(|&(dx, dy)| {
+                        let ny = (y as isize + dy).rem_euclid(height as isize) as usize;
+                        shifted[ny][(dx + 1) as usize].as_slice()
+                    })
+                    .collect();
+
+                (0..self.rows[y].len())
+                    .map(|w| {
+                        let mut counts = [0u64; 4];
+                        for neighbour in &neighbours {
+                            let mut carry = neighbour[w];
+                            for plane in counts.iter_mut() {
+                                let next_carry = *plane & carry;
+                                *plane ^= carry;
+                                carry = next_carry;
+                            }
+                        }
+                        let equals = |n: u8| counts.iter().enumerate()
+                            .fold(!0u64, |bits, (i, plane)| bits & if n & (1 << i) != 0 { *plane } else { !*plane });
+                        let centre = self.rows[y][w];
+                        let born: u64 = births.iter().fold(0, |bits, &n| bits | equals(n));
+                        let survived: u64 = survivals.iter().fold(0, |bits, &n| bits | equals(n));
+                        let next = (born & !centre) | (survived & centre);
+                        if w + 1 == self.rows[y].len() { next & last_mask } else { next }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        BitGrid { rows, width, height }
+    }
+}
+
+// Return words of a row with each bit holding the cell to its west, wrapping around
+fn west(row: &[u64], width: usize, last_mask: u64) -> Vec<u64> {
+    let wrapped = (row[(width - 1) / WORD_BITS] >> ((width - 1) % WORD_BITS)) & 1;
+    let mut words: Vec<u64> = row.iter().enumerate()
+        .map(|(w, word)| (word << 1) | if w == 0 { wrapped } else { row[w - 1] >> (WORD_BITS - 1) })
+        .collect();
+    if let Some(last) = words.last_mut() {
+        *last &= last_mask;
+    }
+    words
+}
+
+// Return words of a row with each bit holding the cell to its east, wrapping around
+fn east(row: &[u64], width: usize) -> Vec<u64> {
+    let last = row.len() - 1;
+    row.iter().enumerate()
+        .map(|(w, word)| (word >> 1) | if w == last { (row[0] & 1) << ((width - 1) % WORD_BITS) } else { (row[w + 1] & 1) << (WORD_BITS - 1) })
+        .collect()
+}
+
+impl From<&Grid> for BitGrid {
+    fn from(grid: &Grid) -> BitGrid {
+        let mut bits = BitGrid::dead(grid.width(), grid.height());
+        for (x, y) in grid.live_cells() {
+            bits.rows[y][x / WORD_BITS] |= 1 << (x % WORD_BITS);
+        }
+        bits
+    }
+}
+
+impl From<&BitGrid> for Grid {
+    fn from(bits: &BitGrid) -> Grid {
+        Grid {
+            cells:  bits.rows.iter()
+                    .flat_map(|row| (0..bits.width).map(move |x| Cell::new(row[x / WORD_BITS] & (1 << (x % WORD_BITS)) != 0)))
+                    .collect(),
+            width:  bits.width,
+            height: bits.height
+        }
+    }
+}
+
+impl fmt::Display for BitGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Grid::from(self))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn next_should_match_grid_for_100_generations() {
+        for &(width, height) in &[(1, 1), (2, 3), (7, 5), (63, 20), (64, 17), (65, 9), (130, 40)] {
+            // given
+            let mut grid = Grid::random_seeded(width as u64, width, height);
+            let mut bits = BitGrid::from(&grid);
+
+            for generation in 0..100 {
+                // when
+                grid = grid.next(Cell::next);
+                bits = bits.next();
+
+                // then
+                assert_eq!(Grid::from(&bits), grid, "{}x{} generation {}", width, height, generation);
+            }
+        }
+    }
+
+    #[test]
+    fn next_with_rule_should_match_grid_for_other_rules_and_neighbourhoods() {
+        for text in &["B36/S23", "B2/S", "B2/S34H", "B1/S012V", "B0123478/S34678"] {
+            // given
+            let rule = Rule::parse(text).unwrap();
+            let mut grid = Grid::random_seeded(3, 70, 30);
+            let mut bits = BitGrid::from(&grid);
+
+            for _ in 0..20 {
+                // when
+                grid = grid.next_with_rule(&rule);
+                bits = bits.next_with_rule(&rule);
+
+                // then
+                assert_eq!(Grid::from(&bits), grid, "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn random_should_draw_same_cells_as_grid() {
+        // given
+        let rng = StdRng::seed_from_u64(7);
+
+        // when
+        let bits = BitGrid::random(rng.clone(), 70, 3);
+
+        // then
+        assert_eq!(Grid::from(&bits), Grid::random(rng.clone(), 70, 3));
+        assert_eq!(bits.population(), Grid::random(rng, 70, 3).population());
+    }
+
+    #[test]
+    fn get_and_set_should_address_cells_across_words() {
+        // given
+        let mut bits = BitGrid::new(100, 2).unwrap();
+
+        // when
+        bits.set(63, 0, true).unwrap();
+        bits.set(64, 1, true).unwrap();
+        bits.set(99, 1, true).unwrap();
+        bits.set(99, 1, false).unwrap();
+
+        // then
+        assert_eq!(bits.get(63, 0), Some(Cell::alive()));
+        assert_eq!(bits.get(64, 1), Some(Cell::alive()));
+        assert_eq!(bits.get(99, 1), Some(Cell::dead()));
+        assert_eq!(bits.get(100, 0), None);
+        assert_eq!(bits.set(0, 2, true), Err(LifeError::OutOfBounds { x: 0, y: 2 }));
+        assert_eq!(bits.population(), 2);
+        assert_eq!(BitGrid::new(0, 2), Err(LifeError::ZeroDimension { width: 0, height: 2 }));
+    }
+
+    #[test]
+    fn fmt_should_format_like_grid() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(BitGrid::from(&grid).to_string(), grid.to_string());
+    }
+}
+
+/*****************************************************************************/
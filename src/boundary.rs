@@ -0,0 +1,184 @@
+/* boundary.rs - Grid boundary conditions
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+/*****************************************************************************/
+
+// How neighbours beyond one pair of opposite edges are found
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum EdgeMode {
+    // Cells beyond an edge wrap around to the opposite edge
+    Wrap,
+    // Cells beyond an edge count as dead
+    Dead,
+    // Cells beyond an edge reflect the cells along it
+    Mirror,
+    // Cells beyond an edge wrap around to the opposite edge with the position
+    // along the other axis reversed, i.e. the edges are glued with a half twist
+    Twist
+}
+
+impl EdgeMode {
+    // Return position along an axis of given length, or None if it counts as dead
+    fn resolve(self, pos: isize, len: usize) -> Option<isize> {
+        let len = len as isize;
+        if pos >= 0 && pos < len {
+            return Some(pos);
+        }
+
+        match self {
+            EdgeMode::Wrap   => Some(pos.rem_euclid(len)),
+            EdgeMode::Dead   => None,
+            EdgeMode::Mirror => Some(if pos < 0 { -1 - pos } else { 2 * len - 1 - pos }.clamp(0, len - 1)),
+            EdgeMode::Twist  => Some(pos.rem_euclid(len))
+        }
+    }
+
+    // Return true if crossing an edge reverses the position along the other axis
+    fn twists(self, pos: isize, len: usize) -> bool {
+        self == EdgeMode::Twist && (pos < 0 || pos >= len as isize)
+    }
+}
+
+/*****************************************************************************/
+
+// How neighbours beyond the edges of a Grid are found
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Boundary {
+    // Opposite edges are joined, cells beyond an edge wrap around
+    #[default]
+    Torus,
+    // Cells beyond the edges count as dead
+    Dead,
+    // Cells beyond an edge reflect the cells along it
+    Mirror,
+    // Left/right edges use x mode, top/bottom edges use y mode,
+    // e.g. a cylinder wraps one axis and has dead edges on the other
+    PerAxis { x: EdgeMode, y: EdgeMode },
+    // Top/bottom edges wrap, left/right edges wrap with a twist so that
+    // leaving the right edge at row y re-enters the left edge at row height-1-y
+    Klein,
+    // Both pairs of edges wrap with a twist (the real projective plane),
+    // leaving the bottom edge at column x re-enters the top edge at column width-1-x.
+    // Beyond a corner the x edge is crossed first, then the y edge
+    CrossSurface
+}
+
+impl Boundary {
+    // Return edge modes for the x and y axes
+    pub fn edges(self) -> (EdgeMode, EdgeMode) {
+        match self {
+            Boundary::Torus           => (EdgeMode::Wrap,   EdgeMode::Wrap),
+            Boundary::Dead            => (EdgeMode::Dead,   EdgeMode::Dead),
+            Boundary::Mirror          => (EdgeMode::Mirror, EdgeMode::Mirror),
+            Boundary::PerAxis { x, y } => (x, y),
+            Boundary::Klein           => (EdgeMode::Twist,  EdgeMode::Wrap),
+            Boundary::CrossSurface    => (EdgeMode::Twist,  EdgeMode::Twist)
+        }
+    }
+
+    // Return position within a Grid of given size for a position which may lie
+    // beyond its edges, or None if the position counts as a dead cell
+    pub(crate) fn resolve(self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (x_mode, y_mode) = self.edges();
+
+        // Cross the left/right edges first, which may reverse y
+        let y = if x_mode.twists(x, width) { height as isize - 1 - y } else { y };
+        let x = x_mode.resolve(x, width)?;
+
+        // Then the top/bottom edges, which may reverse x
+        let x = if y_mode.twists(y, height) { width as isize - 1 - x } else { x };
+        let y = y_mode.resolve(y, height)?;
+
+        Some((x as usize, y as usize))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_should_return_positions_within_grid_unchanged() {
+        for &boundary in &[Boundary::Torus, Boundary::Dead, Boundary::Mirror] {
+            assert_eq!(boundary.resolve(0, 0, 4, 3), Some((0, 0)));
+            assert_eq!(boundary.resolve(3, 2, 4, 3), Some((3, 2)));
+        }
+    }
+
+    #[test]
+    fn resolve_should_wrap_positions_given_torus() {
+        assert_eq!(Boundary::Torus.resolve(-1, -1, 4, 3), Some((3, 2)));
+        assert_eq!(Boundary::Torus.resolve(4, 3, 4, 3),   Some((0, 0)));
+    }
+
+    #[test]
+    fn resolve_should_return_none_given_dead() {
+        assert_eq!(Boundary::Dead.resolve(-1, 0, 4, 3), None);
+        assert_eq!(Boundary::Dead.resolve(0, 3, 4, 3),  None);
+    }
+
+    #[test]
+    fn resolve_should_reflect_positions_given_mirror() {
+        assert_eq!(Boundary::Mirror.resolve(-1, -1, 4, 3), Some((0, 0)));
+        assert_eq!(Boundary::Mirror.resolve(4, 3, 4, 3),   Some((3, 2)));
+        assert_eq!(Boundary::Mirror.resolve(-2, 4, 4, 3),  Some((1, 1)));
+    }
+
+    #[test]
+    fn resolve_should_use_mode_per_axis() {
+        // given
+        let cylinder = Boundary::PerAxis { x: EdgeMode::Wrap, y: EdgeMode::Dead };
+
+        // then
+        assert_eq!(cylinder.resolve(-1, 1, 4, 3), Some((3, 1)));
+        assert_eq!(cylinder.resolve(4, 2, 4, 3),  Some((0, 2)));
+        assert_eq!(cylinder.resolve(1, -1, 4, 3), None);
+        assert_eq!(cylinder.resolve(1, 3, 4, 3),  None);
+    }
+
+    #[test]
+    fn resolve_should_reverse_y_across_left_right_edges_given_klein() {
+        assert_eq!(Boundary::Klein.resolve(5, 0, 5, 4),  Some((0, 3)));
+        assert_eq!(Boundary::Klein.resolve(5, 1, 5, 4),  Some((0, 2)));
+        assert_eq!(Boundary::Klein.resolve(-1, 3, 5, 4), Some((4, 0)));
+        assert_eq!(Boundary::Klein.resolve(2, -1, 5, 4), Some((2, 3)));
+        assert_eq!(Boundary::Klein.resolve(2, 4, 5, 4),  Some((2, 0)));
+        assert_eq!(Boundary::Klein.resolve(5, 4, 5, 4),  Some((0, 3)));
+        assert_eq!(Boundary::Klein.resolve(-1, -1, 5, 4), Some((4, 0)));
+    }
+
+    #[test]
+    fn resolve_should_reverse_both_axes_across_edges_given_cross_surface() {
+        assert_eq!(Boundary::CrossSurface.resolve(5, 0, 5, 4),   Some((0, 3)));
+        assert_eq!(Boundary::CrossSurface.resolve(1, -1, 5, 4),  Some((3, 3)));
+        assert_eq!(Boundary::CrossSurface.resolve(0, 4, 5, 4),   Some((4, 0)));
+        assert_eq!(Boundary::CrossSurface.resolve(-1, -1, 5, 4), Some((0, 0)));
+        assert_eq!(Boundary::CrossSurface.resolve(5, 4, 5, 4),   Some((4, 3)));
+    }
+
+    #[test]
+    fn resolve_should_twist_one_axis_given_mobius_strip() {
+        // given
+        let mobius = Boundary::PerAxis { x: EdgeMode::Twist, y: EdgeMode::Dead };
+
+        // then
+        assert_eq!(mobius.resolve(5, 1, 5, 4),  Some((0, 2)));
+        assert_eq!(mobius.resolve(2, -1, 5, 4), None);
+        assert_eq!(mobius.resolve(5, 4, 5, 4),  None);
+    }
+
+    #[test]
+    fn edges_should_match_named_boundaries() {
+        assert_eq!(Boundary::Torus.edges(),  (EdgeMode::Wrap, EdgeMode::Wrap));
+        assert_eq!(Boundary::Dead.edges(),   (EdgeMode::Dead, EdgeMode::Dead));
+        assert_eq!(Boundary::Mirror.edges(), (EdgeMode::Mirror, EdgeMode::Mirror));
+    }
+}
+
+/*****************************************************************************/
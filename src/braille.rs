@@ -0,0 +1,106 @@
+/* braille.rs - Rendering 2x4 blocks of Grid cells per Braille char
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::string::String;
+
+use crate::{Grid, LineEnding};
+
+/*****************************************************************************/
+
+// Codepoint of the Braille pattern with no dots raised
+const BRAILLE_BLANK: u32 = 0x2800;
+
+// Bit of the Braille codepoint for the dot of each cell in a block, indexed by
+// row then column. Dots 1-3 then 7 run down the left column, 4-6 then 8 the right
+const DOT_BITS: [[u32; 2]; 4] = [
+    [0x01, 0x08],
+    [0x02, 0x10],
+    [0x04, 0x20],
+    [0x40, 0x80]
+];
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return blocks of 2x4 cells rendered as one Braille char each, with a dot
+    // raised per live cell and rows of blocks separated by "\n". Blocks beyond the
+    // right or bottom edge are padded with dead cells, a block of only dead cells
+    // being the blank Braille char rather than a space
+    pub fn render_braille(&self) -> String {
+        let mut text = String::new();
+        self.render_braille_into(&mut text, LineEnding::Lf);
+        text
+    }
+
+    // Render blocks of cells as render_braille does, separated by given line
+    // ending, into buf, replacing its contents and reusing its capacity
+    pub fn render_braille_into(&self, buf: &mut String, ending: LineEnding) {
+        let columns = self.width.div_ceil(2);
+        let lines = if self.cells.is_empty() { 0 } else { self.height.div_ceil(4) };
+        buf.clear();
+        buf.reserve((columns * 3 + ending.as_str().len()) * lines);
+
+        for line in 0..lines {
+            if line > 0 {
+                buf.push_str(ending.as_str());
+            }
+            for column in 0..columns {
+                let mut bits = 0;
+                for (dy, row_bits) in DOT_BITS.iter().enumerate() {
+                    for (dx, bit) in row_bits.iter().enumerate() {
+                        let (x, y) = (column * 2 + dx, line * 4 + dy);
+                        if x < self.width && y < self.height && self.cells[y * self.width + x].alive {
+                            bits |= bit;
+                        }
+                    }
+                }
+                buf.push(char::from_u32(BRAILLE_BLANK + bits).unwrap_or_else(|| unreachable!("Braille codepoints are valid")));
+            }
+        }
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn braille_of(text: &str) -> String {
+        Grid::from_text(text, 'O').unwrap().render_braille()
+    }
+
+    #[test]
+    fn render_braille_should_map_cells_to_dots() {
+        assert_eq!(braille_of("..\n..\n..\n.."), "\u{2800}");
+        assert_eq!(braille_of("OO\nOO\nOO\nOO"), "\u{28ff}");
+        // Dots 1, 2, 3 and 7 down the left column
+        assert_eq!(braille_of("O.\n..\n..\n.."), "\u{2801}");
+        assert_eq!(braille_of("..\nO.\n..\n.."), "\u{2802}");
+        assert_eq!(braille_of("..\n..\nO.\n.."), "\u{2804}");
+        assert_eq!(braille_of("..\n..\n..\nO."), "\u{2840}");
+        // Dots 4, 5, 6 and 8 down the right column
+        assert_eq!(braille_of(".O\n..\n..\n.."), "\u{2808}");
+        assert_eq!(braille_of("..\n.O\n..\n.."), "\u{2810}");
+        assert_eq!(braille_of("..\n..\n.O\n.."), "\u{2820}");
+        assert_eq!(braille_of("..\n..\n..\n.O"), "\u{2880}");
+    }
+
+    #[test]
+    fn render_braille_should_pad_partial_blocks_with_dead_cells() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO\n...\nO.O", 'O').unwrap();
+        let mut buf = String::from("previous frame");
+
+        // when
+        grid.render_braille_into(&mut buf, LineEnding::CrLf);
+
+        // then
+        assert_eq!(buf, "\u{282c}\u{2806}\r\n\u{2801}\u{2801}");
+        assert_eq!(grid.render_braille(), "\u{282c}\u{2806}\n\u{2801}\u{2801}");
+        assert_eq!(Grid::new(1, 1).unwrap().render_braille(), "\u{2800}");
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,171 @@
+/* cells.rs - Plaintext .cells pattern format
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Grid, LifeError, Pattern, PatternMeta};
+
+/*****************************************************************************/
+
+const NAME_PREFIX:   &str = "!Name:";
+// Comment naming the author, by convention the first after the name
+const AUTHOR_PREFIX: &str = "Author:";
+
+impl Grid {
+    // Return a Grid parsed from plaintext .cells text, with the name and other
+    // comment lines starting with '!'. Short rows are padded with dead cells
+    pub fn from_cells(input: &str) -> Result<(Grid, PatternMeta), LifeError> {
+        let mut meta = PatternMeta::default();
+        let mut rows = Vec::new();
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim_end();
+            if let Some(name) = line.strip_prefix(NAME_PREFIX) {
+                meta.name = Some(name.trim().to_string());
+            }
+            else if let Some(comment) = line.strip_prefix('!') {
+                meta.comments.push(comment.to_string());
+            }
+            else if let Some(c) = line.chars().find(|&c| c != '.' && c != 'O') {
+                return Err(LifeError::ParseError { line: index + 1, msg: format!("unexpected '{}', expected '.' or 'O'", c) });
+            }
+            else {
+                rows.push(line);
+            }
+        }
+
+        // Blank lines within the pattern are rows of dead cells, trailing ones are ignored
+        while rows.last() == Some(&"") {
+            rows.pop();
+        }
+        Ok((Grid::from_text(&rows.join("\n"), 'O')?, meta))
+    }
+
+    // Return plaintext .cells text of the Grid with given name and comment lines
+    pub fn to_cells(&self, name: &str, comments: &[&str]) -> String {
+        let mut text = format!("{} {}\n", NAME_PREFIX, name);
+        for comment in comments {
+            text.push_str(&format!("!{}\n", comment));
+        }
+        for row in self.rows() {
+            text.extend(row.iter().map(|cell| if cell.alive { 'O' } else { '.' }));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl Pattern {
+    // Return a Pattern parsed from plaintext .cells text, with the name, the
+    // author from an "!Author:" line and the other comment lines
+    pub fn from_cells(input: &str) -> Result<Pattern, LifeError> {
+        let (cells, meta) = Grid::from_cells(input)?;
+        let mut pattern = Pattern::new(cells);
+        pattern.name = meta.name.filter(|name| !name.is_empty());
+        for comment in meta.comments {
+            match comment.strip_prefix(AUTHOR_PREFIX) {
+                Some(author) if pattern.author.is_none() => pattern.author = Some(author.trim().to_string()),
+                _ => pattern.comments.push(comment)
+            }
+        }
+        Ok(pattern)
+    }
+
+    // Return plaintext .cells text of the cells with the name, author and comments
+    pub fn to_cells(&self) -> String {
+        let author = self.author.as_ref().map(|author| format!("{} {}", AUTHOR_PREFIX, author));
+        let comments: Vec<&str> = author.iter().chain(&self.comments).map(String::as_str).collect();
+        self.cells().to_cells(self.name.as_deref().unwrap_or(""), &comments)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLINKER: &str = "\
+!Name: Blinker
+!Author: John Conway
+!The smallest and most common oscillator.
+!www.conwaylife.com/wiki/index.php?title=Blinker
+OOO
+";
+
+    #[test]
+    fn from_cells_should_parse_published_blinker() {
+        // when
+        let (grid, meta) = Grid::from_cells(BLINKER).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text("OOO", 'O').unwrap());
+        assert_eq!(meta.name, Some("Blinker".to_string()));
+        assert_eq!(meta.comments, vec![
+            "Author: John Conway",
+            "The smallest and most common oscillator.",
+            "www.conwaylife.com/wiki/index.php?title=Blinker"]);
+    }
+
+    #[test]
+    fn from_cells_should_pad_short_rows_and_keep_blank_rows() {
+        // when
+        let (grid, meta) = Grid::from_cells("!\n.O\n\nO..O\n\n").unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".O..\n....\nO..O", 'O').unwrap());
+        assert_eq!(meta.name, None);
+        assert_eq!(meta.comments, vec![""]);
+    }
+
+    #[test]
+    fn from_cells_should_return_error_given_invalid_input() {
+        assert_eq!(Grid::from_cells("!Name: Oops\n.O.\n.*.\n").unwrap_err().to_string(),
+            "line 3: unexpected '*', expected '.' or 'O'");
+        assert_eq!(Grid::from_cells("!Name: Empty\n"), Err(LifeError::EmptyPattern));
+    }
+
+    #[test]
+    fn to_cells_should_write_name_comments_and_rows() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_cells("Glider", &["The smallest spaceship.", ""]),
+            "!Name: Glider\n!The smallest spaceship.\n!\n.O.\n..O\nOOO\n");
+    }
+
+    #[test]
+    fn from_cells_should_round_trip_to_cells() {
+        // given
+        let grid = Grid::random_seeded(11, 17, 12);
+        let published = Grid::from_cells(BLINKER).unwrap();
+
+        // when
+        let (parsed, meta) = Grid::from_cells(&grid.to_cells("Soup", &["Random", "soup"])).unwrap();
+
+        // then
+        assert_eq!(parsed, grid);
+        assert_eq!(meta.name, Some("Soup".to_string()));
+        assert_eq!(meta.comments, vec!["Random", "soup"]);
+        let comments: Vec<&str> = published.1.comments.iter().map(String::as_str).collect();
+        assert_eq!(published.0.to_cells("Blinker", &comments), BLINKER);
+    }
+
+    #[test]
+    fn pattern_from_cells_should_round_trip_metadata() {
+        // when
+        let pattern = Pattern::from_cells(BLINKER).unwrap();
+
+        // then
+        assert_eq!(pattern.name, Some("Blinker".to_string()));
+        assert_eq!(pattern.author, Some("John Conway".to_string()));
+        assert_eq!(pattern.comments, vec!["The smallest and most common oscillator.", "www.conwaylife.com/wiki/index.php?title=Blinker"]);
+        assert_eq!(pattern.to_cells(), BLINKER);
+        assert_eq!(Pattern::from_cells(&Pattern::new(Grid::from_text("OO", 'O').unwrap()).to_cells()).unwrap().name, None);
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,162 @@
+/* census.rs - Counting the objects making up settled ash
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::collections::BTreeMap;
+use core::fmt;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::Grid;
+use crate::apgcode::least_wechsler;
+
+/*****************************************************************************/
+
+// Live cells at most this far apart in both directions belong to the same object
+const SEPARATION: usize = 2;
+
+// Names of common objects by apgcode
+const OBJECTS: [(&str, &str); 9] = [
+    ("xs4_33",   "block"),
+    ("xs6_696",  "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253",  "boat"),
+    ("xs4_252",  "tub"),
+    ("xp2_7",    "blinker"),
+    ("xs6_356",  "ship"),
+    ("xs8_6996", "pond"),
+    ("xq4_153",  "glider")
+];
+
+// Number of each object found in a Grid, by name if common and otherwise by
+// apgcode, objects that do not repeat being xx and their least Wechsler text
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Census {
+    counts: BTreeMap<String, usize>
+}
+
+impl Census {
+    // Return number of objects of given name or apgcode
+    pub fn count(&self, object: &str) -> usize {
+        self.counts.get(object).copied().unwrap_or(0)
+    }
+
+    // Return number of objects found
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    // Return an iterator over each object found and its number, in order of name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> + '_ {
+        self.counts.iter().map(|(object, &count)| (object.as_str(), count))
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}",
+            self.iter()
+            .map(|(object, count)| format!("{} {}", object, count))
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return a Census of the objects made of the live cells under Conway's Life,
+    // each object being the cells connected through live cells at most SEPARATION
+    // apart. Objects are found on the Grid as a plane, not wrapping at the edges
+    pub fn census(&self) -> Census {
+        let mut census = Census::default();
+        let mut seen = vec![false; self.cells.len()];
+        for (x, y) in self.live_cells() {
+            if seen[y * self.width + x] {
+                continue;
+            }
+
+            seen[y * self.width + x] = true;
+            let mut object = vec![(x, y)];
+            let mut next = 0;
+            while let Some(&(x, y)) = object.get(next) {
+                next += 1;
+                for ny in y.saturating_sub(SEPARATION)..(y + SEPARATION + 1).min(self.height) {
+                    for nx in x.saturating_sub(SEPARATION)..(x + SEPARATION + 1).min(self.width) {
+                        let i = ny * self.width + nx;
+                        if self.cells[i].alive && !seen[i] {
+                            seen[i] = true;
+                            object.push((nx, ny));
+                        }
+                    }
+                }
+            }
+
+            let min_x = object.iter().map(|&(x, _)| x).min().unwrap_or(0);
+            let min_y = object.iter().map(|&(_, y)| y).min().unwrap_or(0);
+            let width  = object.iter().map(|&(x, _)| x - min_x).max().unwrap_or(0) + 1;
+            let height = object.iter().map(|&(_, y)| y - min_y).max().unwrap_or(0) + 1;
+            let pattern = Grid::from_coords(width, height, object.iter().map(|&(x, y)| (x - min_x, y - min_y)))
+                .unwrap_or_else(|_| unreachable!("objects have at least one cell"));
+
+            let code = match pattern.to_apgcode() {
+                Some(code) => code,
+                None => format!("xx_{}", least_wechsler(&[pattern]).unwrap_or_default())
+            };
+            let name = match OBJECTS.iter().find(|&&(apgcode, _)| apgcode == code) {
+                Some(&(_, name)) => name.to_string(),
+                None => code
+            };
+            *census.counts.entry(name).or_insert(0) += 1;
+        }
+        census
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InsertMode, Overflow};
+
+    #[test]
+    fn census_should_count_common_objects() {
+        // given
+        let mut grid = Grid::new(30, 20).unwrap();
+        for &(text, x, y) in &[("OO\nOO", 1, 1), ("OO\nOO", 20, 15), (".OO.\nO..O\n.OO.", 10, 2), ("O\nO\nO", 3, 12)] {
+            grid.insert(&Grid::from_text(text, 'O').unwrap(), x, y, InsertMode::Replace, Overflow::Clip);
+        }
+
+        // when
+        let census = grid.census();
+
+        // then
+        assert_eq!(census.iter().collect::<Vec<(&str, usize)>>(), vec![("beehive", 1), ("blinker", 1), ("block", 2)]);
+        assert_eq!(census.total(), 4);
+        assert_eq!(census.count("block"), 2);
+        assert_eq!(census.count("glider"), 0);
+        assert_eq!(census.to_string(), "beehive 1\nblinker 1\nblock 2");
+    }
+
+    #[test]
+    fn census_should_report_unknown_objects_by_apgcode() {
+        // given
+        let mut grid = Grid::new(20, 10).unwrap();
+        for &(text, x, y) in &[("OO.OO\nOO.OO", 1, 1), ("OO", 8, 7), (".O.\nO.O\n.O.", 14, 1)] {
+            grid.insert(&Grid::from_text(text, 'O').unwrap(), x, y, InsertMode::Replace, Overflow::Clip);
+        }
+
+        // when
+        let census = grid.census();
+
+        // then
+        assert_eq!(census.count("xs8_rr"), 1);
+        assert_eq!(census.count("xx_3"), 1);
+        assert_eq!(census.count("xs4_252"), 0);
+        assert_eq!(census.count("tub"), 1);
+        assert_eq!(Grid::new(4, 4).unwrap().census(), Census::default());
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,231 @@
+/* charset.rs - Chars and line endings cells are rendered with and parsed from
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use alloc::string::String;
+
+use crate::{Cell, Grid, LifeError};
+
+/*****************************************************************************/
+
+// Chars for dead and live cells
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CharSet {
+    pub dead:   char,
+    pub alive:  char
+}
+
+impl CharSet {
+    // Chars used by Display
+    pub const DEFAULT: CharSet = CharSet { dead: Cell::EMPTY_CELL, alive: Cell::LIVE_CELL };
+
+    // Return a CharSet of given chars
+    pub const fn new(dead: char, alive: char) -> CharSet {
+        CharSet { dead, alive }
+    }
+
+    // Map cell state to a char
+    pub fn to_char(&self, cell: &Cell) -> char {
+        if cell.alive { self.alive } else { self.dead }
+    }
+}
+
+impl Default for CharSet {
+    fn default() -> CharSet {
+        CharSet::DEFAULT
+    }
+}
+
+// Terminator written between rows, parsers accepting either
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineEnding {
+    // "\n", used by Display
+    #[default]
+    Lf,
+    // "\r\n", needed by terminals in raw mode
+    CrLf
+}
+
+impl LineEnding {
+    // Return the terminator as text
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf   => "\n",
+            LineEnding::CrLf => "\r\n"
+        }
+    }
+}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return a Grid parsed from lines of text rendered with given CharSet, any
+    // char other than the live one is a dead cell and short lines are padded
+    pub fn from_text_with(text: &str, charset: &CharSet) -> Result<Grid, LifeError> {
+        Grid::from_text(text, charset.alive)
+    }
+
+    // Return rows of cells rendered with given CharSet, separated by "\n"
+    pub fn to_string_with(&self, charset: &CharSet) -> String {
+        let mut text = String::new();
+        self.push_rows(&mut text, charset, LineEnding::Lf);
+        text
+    }
+
+    // Return rows of cells as Display renders them but separated by "\r\n", for
+    // terminals in raw mode
+    pub fn to_string_crlf(&self) -> String {
+        let mut text = String::new();
+        self.push_rows(&mut text, &CharSet::DEFAULT, LineEnding::CrLf);
+        text
+    }
+
+    // Render rows of cells as Display does, separated by given line ending, into
+    // buf, replacing its contents and reusing its capacity so that a buffer kept
+    // across frames is allocated once
+    pub fn render_into(&self, buf: &mut String, ending: LineEnding) {
+        self.render_into_with(buf, &CharSet::DEFAULT, ending);
+    }
+
+    // Render rows of cells with given CharSet, separated by given line ending,
+    // into buf as render_into does
+    pub fn render_into_with(&self, buf: &mut String, charset: &CharSet, ending: LineEnding) {
+        buf.clear();
+        self.push_rows(buf, charset, ending);
+    }
+
+    // Write rows of cells rendered with given CharSet, separated by given line
+    // ending, each row being rendered into a buffer then written whole
+    pub fn write_with<W: fmt::Write>(&self, writer: &mut W, charset: &CharSet, ending: LineEnding) -> fmt::Result {
+        let mut line = String::with_capacity(self.width * charset.alive.len_utf8().max(charset.dead.len_utf8()));
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writer.write_str(ending.as_str())?;
+            }
+            line.clear();
+            line.extend(row.iter().map(|cell| charset.to_char(cell)));
+            writer.write_str(&line)?;
+        }
+        Ok(())
+    }
+
+    // Append rows of cells rendered with given CharSet and separated by given
+    // line ending to text, reserving space for all of them first
+    fn push_rows(&self, text: &mut String, charset: &CharSet, ending: LineEnding) {
+        let cell_len = charset.alive.len_utf8().max(charset.dead.len_utf8());
+        text.reserve((self.width * cell_len + ending.as_str().len()) * self.height);
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                text.push_str(ending.as_str());
+            }
+            text.extend(row.iter().map(|cell| charset.to_char(cell)));
+        }
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_string_with_should_default_to_display() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_string_with(&CharSet::default()), grid.to_string());
+    }
+
+    #[test]
+    fn from_text_with_should_parse_rendered_unicode() {
+        // given
+        let charset = CharSet::new('·', '█');
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // when
+        let text = grid.to_string_with(&charset);
+
+        // then
+        assert_eq!(text, "·█·\n··█\n███");
+        assert_eq!(text.chars().count(), 11);
+        // '·' is 2 bytes and '█' 3 bytes in UTF-8
+        assert_eq!(text.len(), 4 * 2 + 5 * 3 + 2);
+        assert_eq!(Grid::from_text_with(&text, &charset).unwrap(), grid);
+    }
+
+    #[test]
+    fn display_should_match_rows_joined_by_lf() {
+        // given
+        let grid = Grid::random_seeded(3, 37, 11);
+        let joined = grid.rows()
+            .map(|row| row.iter().map(Cell::to_char).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        // then
+        assert_eq!(grid.to_string(), joined);
+        assert_eq!(grid.to_string_crlf(), joined.replace('\n', "\r\n"));
+    }
+
+    #[test]
+    fn render_into_should_replace_buffer_contents() {
+        // given
+        let grid = Grid::random_seeded(5, 40, 20);
+        let mut buf = String::from("previous frame");
+
+        // when
+        grid.render_into(&mut buf, LineEnding::Lf);
+        let capacity = buf.capacity();
+        grid.next(Cell::next).render_into(&mut buf, LineEnding::Lf);
+
+        // then
+        assert_eq!(buf, grid.next(Cell::next).to_string());
+        assert_eq!(buf.capacity(), capacity);
+    }
+
+    #[test]
+    fn render_into_with_should_replace_buffer_contents_with_chars_of_charset() {
+        // given
+        let grid = Grid::from_text("O.\n.O", 'O').unwrap();
+        let mut buf = String::from("previous frame");
+
+        // when
+        grid.render_into_with(&mut buf, &CharSet::new('·', '█'), LineEnding::Lf);
+
+        // then
+        assert_eq!(buf, "█·\n·█");
+    }
+
+    #[test]
+    fn write_with_should_append_to_writer() {
+        // given
+        let grid = Grid::from_text("O.\n.O", 'O').unwrap();
+        let mut text = String::from("> ");
+
+        // when
+        grid.write_with(&mut text, &CharSet::new('-', '#'), LineEnding::CrLf).unwrap();
+
+        // then
+        assert_eq!(text, "> #-\r\n-#");
+    }
+
+    #[test]
+    fn crlf_rendering_should_parse_as_display_does() {
+        // given
+        let grid = Grid::random_seeded(8, 12, 7);
+        let mut buf = String::new();
+
+        // when
+        grid.render_into(&mut buf, LineEnding::CrLf);
+
+        // then
+        assert_eq!(buf, grid.to_string_crlf());
+        assert_eq!(buf.parse::<Grid>().unwrap(), grid);
+        assert_eq!(grid.to_string().parse::<Grid>().unwrap(), grid);
+    }
+}
+
+/*****************************************************************************/
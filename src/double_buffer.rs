@@ -0,0 +1,106 @@
+/* double_buffer.rs - Allocation free stepping between two Grids
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::mem;
+
+use crate::{Cell, Grid, MaybeSync, Parallelism};
+
+/*****************************************************************************/
+
+// Two Grids of the same size, each step writing the next state from the front
+// Grid into the back Grid and swapping them, so no cells are allocated after
+// construction
+#[derive(Clone, Debug)]
+pub struct DoubleBuffer {
+    front:  Grid,
+    back:   Grid
+}
+
+impl DoubleBuffer {
+    // Return a DoubleBuffer showing given Grid
+    pub fn new(grid: Grid) -> DoubleBuffer {
+        DoubleBuffer { back: grid.clone(), front: grid }
+    }
+
+    // Return current Grid state
+    pub fn grid(&self) -> &Grid {
+        &self.front
+    }
+
+    // Replace current Grid state, reallocating the back Grid if the size changes
+    pub fn replace(&mut self, grid: Grid) {
+        if grid.width() != self.back.width() || grid.height() != self.back.height() {
+            self.back = grid.clone();
+        }
+        self.front = grid;
+    }
+
+    // Advance one generation using given cell function
+    pub fn step<F: Fn(&Cell, u8)->Cell + MaybeSync>(&mut self, cell_func: F) {
+        self.back.step_cells_into(&self.front, cell_func, &Parallelism::Auto);
+        mem::swap(&mut self.front, &mut self.back);
+    }
+
+    // Return current Grid state, consuming the DoubleBuffer
+    pub fn into_grid(self) -> Grid {
+        self.front
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_should_match_next_over_many_generations() {
+        // given
+        let mut grid = Grid::random_seeded(9, 31, 23);
+        let mut buffer = DoubleBuffer::new(grid.clone());
+
+        for _ in 0..50 {
+            // when
+            grid = grid.next(Cell::next);
+            buffer.step(Cell::next);
+
+            // then
+            assert_eq!(buffer.grid(), &grid);
+        }
+        assert_eq!(buffer.into_grid(), grid);
+    }
+
+    #[test]
+    fn step_should_reuse_both_buffers() {
+        // given
+        let mut buffer = DoubleBuffer::new(Grid::random_seeded(1, 64, 64));
+        let first  = buffer.grid().cells.as_ptr();
+        buffer.step(Cell::next);
+        let second = buffer.grid().cells.as_ptr();
+
+        // when
+        buffer.step(Cell::next);
+
+        // then
+        assert_ne!(first, second);
+        assert_eq!(buffer.grid().cells.as_ptr(), first);
+    }
+
+    #[test]
+    fn replace_should_resize_back_buffer() {
+        // given
+        let mut buffer = DoubleBuffer::new(Grid::new(4, 4).unwrap());
+        let blinker = Grid::from_text(".....\n.....\n.OOO.\n.....\n.....", 'O').unwrap();
+
+        // when
+        buffer.replace(blinker.clone());
+        buffer.step(Cell::next);
+        buffer.step(Cell::next);
+
+        // then
+        assert_eq!(buffer.grid(), &blinker);
+    }
+}
+
+/*****************************************************************************/
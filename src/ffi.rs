@@ -0,0 +1,204 @@
+/* ffi.rs - C interface for embedding Grids in programs not written in Rust
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+// Every function takes a Grid pointer returned by life_grid_new and not yet passed
+// to life_grid_free, or null, which is checked for. Panics are caught so that none
+// unwind into C, the function returning its error value instead. The declarations
+// are in include/rs_life.h
+#![allow(clippy::missing_safety_doc)]
+
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::{Grid, Rule};
+
+/*****************************************************************************/
+
+// Value returned by functions returning c_int on success and failure
+const OK:    c_int = 0;
+const ERROR: c_int = -1;
+
+// Call func, returning given value if it panics
+fn catch<T, F: FnOnce() -> T>(error: T, func: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(func)).unwrap_or(error)
+}
+
+/*****************************************************************************/
+
+// Return a new Grid of given size with every cell dead, to be freed with
+// life_grid_free, or null if either dimension is zero
+#[no_mangle]
+pub extern "C" fn life_grid_new(width: usize, height: usize) -> *mut Grid {
+    catch(ptr::null_mut(), || match Grid::new(width, height) {
+        Ok(grid) => Box::into_raw(Box::new(grid)),
+        Err(_) => ptr::null_mut()
+    })
+}
+
+// Free a Grid returned by life_grid_new, doing nothing given null
+#[no_mangle]
+pub unsafe extern "C" fn life_grid_free(grid: *mut Grid) {
+    if !grid.is_null() {
+        catch((), || drop(Box::from_raw(grid)));
+    }
+}
+
+// Replace every cell with a random state, a fraction density of them alive, the
+// same seed giving the same cells. Returns 0, or -1 given null or a density
+// outside 0.0 to 1.0
+#[no_mangle]
+pub unsafe extern "C" fn life_grid_randomize(grid: *mut Grid, seed: u64, density: f64) -> c_int {
+    match grid.as_mut() {
+        Some(grid) if (0.0..=1.0).contains(&density) => catch(ERROR, || {
+            *grid = Grid::random_seeded_with_density(seed, grid.width(), grid.height(), density);
+            OK
+        }),
+        _ => ERROR
+    }
+}
+
+// Advance to the next generation under Conway's Life. Returns 0, or -1 given null
+#[no_mangle]
+pub unsafe extern "C" fn life_grid_step(grid: *mut Grid) -> c_int {
+    match grid.as_mut() {
+        Some(grid) => catch(ERROR, || {
+            *grid = grid.next_with_rule(&Rule::CONWAY);
+            OK
+        }),
+        None => ERROR
+    }
+}
+
+// Return 1 if the cell at given position is alive, 0 if dead, or -1 given null
+// or a position out of bounds
+#[no_mangle]
+pub unsafe extern "C" fn life_grid_get(grid: *const Grid, x: usize, y: usize) -> c_int {
+    match grid.as_ref() {
+        Some(grid) => catch(ERROR, || grid.get(x, y).map_or(ERROR, |cell| cell.is_alive() as c_int)),
+        None => ERROR
+    }
+}
+
+// Set the cell at given position alive if alive is non-zero, otherwise dead.
+// Returns 0, or -1 given null or a position out of bounds
+#[no_mangle]
+pub unsafe extern "C" fn life_grid_set(grid: *mut Grid, x: usize, y: usize, alive: c_int) -> c_int {
+    match grid.as_mut() {
+        Some(grid) => catch(ERROR, || grid.set(x, y, alive != 0).map_or(ERROR, |_| OK)),
+        None => ERROR
+    }
+}
+
+// Write rows of cells as ASCII, 'O' for live and ' ' for dead cells separated by
+// '\n', to buf as a NUL terminated string of at most buf_len bytes including the
+// NUL, truncating the text if it is longer. Returns the length of the whole text
+// without the NUL, so a result of buf_len or more means it was truncated, or 0
+// given a null grid. Nothing is written given a null buf or buf_len of 0
+#[no_mangle]
+pub unsafe extern "C" fn life_grid_render_ascii(grid: *const Grid, buf: *mut c_char, buf_len: usize) -> usize {
+    let grid = match grid.as_ref() {
+        Some(grid) => grid,
+        None => return 0
+    };
+
+    catch(0, || {
+        let text = grid.to_string();
+        if !buf.is_null() && buf_len > 0 {
+            let len = text.len().min(buf_len - 1);
+            ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, buf, len);
+            *buf.add(len) = 0;
+        }
+        text.len()
+    })
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn life_grid_new_should_return_null_given_zero_dimension() {
+        assert!(life_grid_new(0, 5).is_null());
+        unsafe { life_grid_free(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn life_grid_functions_should_step_blinker() {
+        unsafe {
+            // given
+            let grid = life_grid_new(5, 5);
+            for x in 1..4 {
+                assert_eq!(life_grid_set(grid, x, 2, 1), OK);
+            }
+
+            // when
+            assert_eq!(life_grid_step(grid), OK);
+
+            // then
+            assert_eq!((0..5).map(|y| life_grid_get(grid, 2, y)).collect::<Vec<c_int>>(), vec![0, 1, 1, 1, 0]);
+            assert_eq!(life_grid_get(grid, 1, 2), 0);
+            assert_eq!(life_grid_get(grid, 5, 0), ERROR);
+            assert_eq!(life_grid_set(grid, 0, 5, 1), ERROR);
+            life_grid_free(grid);
+        }
+    }
+
+    #[test]
+    fn life_grid_functions_should_return_error_given_null() {
+        unsafe {
+            assert_eq!(life_grid_randomize(ptr::null_mut(), 1, 0.5), ERROR);
+            assert_eq!(life_grid_step(ptr::null_mut()), ERROR);
+            assert_eq!(life_grid_get(ptr::null(), 0, 0), ERROR);
+            assert_eq!(life_grid_set(ptr::null_mut(), 0, 0, 1), ERROR);
+            assert_eq!(life_grid_render_ascii(ptr::null(), ptr::null_mut(), 0), 0);
+        }
+    }
+
+    #[test]
+    fn life_grid_randomize_should_match_seeded_grid() {
+        unsafe {
+            // given
+            let grid = life_grid_new(16, 8);
+
+            // when
+            assert_eq!(life_grid_randomize(grid, 7, 0.25), OK);
+
+            // then
+            assert_eq!(*grid, Grid::random_seeded_with_density(7, 16, 8, 0.25));
+            assert_eq!(life_grid_randomize(grid, 7, 1.5), ERROR);
+            life_grid_free(grid);
+        }
+    }
+
+    #[test]
+    fn life_grid_render_ascii_should_truncate_to_buffer() {
+        unsafe {
+            // given
+            let grid = life_grid_new(3, 2);
+            life_grid_set(grid, 0, 0, 1);
+            life_grid_set(grid, 2, 1, 1);
+            let mut buf = [0x7f as c_char; 8];
+
+            // then
+            assert_eq!(life_grid_render_ascii(grid, buf.as_mut_ptr(), buf.len()), 7);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str(), Ok("O  \n  O"));
+
+            buf = [0x7f as c_char; 8];
+            assert_eq!(life_grid_render_ascii(grid, buf.as_mut_ptr(), 5), 7);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str(), Ok("O  \n"));
+            assert_eq!(buf[5], 0x7f);
+
+            assert_eq!(life_grid_render_ascii(grid, buf.as_mut_ptr(), 0), 7);
+            assert_eq!(life_grid_render_ascii(grid, ptr::null_mut(), 8), 7);
+            assert_eq!(buf[0], b'O' as c_char);
+            life_grid_free(grid);
+        }
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,111 @@
+/* frame.rs - Rendering Grids inside a bordered frame
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{CharSet, Grid};
+
+/*****************************************************************************/
+
+// Chars a frame is drawn with
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameStyle {
+    // "+", "-" and "|"
+    Ascii,
+    // Box-drawing chars
+    Unicode
+}
+
+impl FrameStyle {
+    // Return the top left, top right, bottom left and bottom right corners then
+    // the horizontal and vertical edges
+    fn chars(self) -> [char; 6] {
+        match self {
+            FrameStyle::Ascii   => ['+', '+', '+', '+', '-', '|'],
+            FrameStyle::Unicode => ['┌', '┐', '└', '┘', '─', '│']
+        }
+    }
+}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return rows of cells rendered as Display does inside a frame of given style,
+    // separated by "\n"
+    pub fn to_string_framed(&self, style: FrameStyle) -> String {
+        self.to_string_framed_with(style, &CharSet::DEFAULT, None)
+    }
+
+    // Return rows of cells rendered with given CharSet inside a frame of given
+    // style, with the title centred in the top edge if given. A title too long
+    // for the edge is truncated, or left out if the grid is under 3 cells wide
+    pub fn to_string_framed_with(&self, style: FrameStyle, charset: &CharSet, title: Option<&str>) -> String {
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] = style.chars();
+        let mut text = String::with_capacity((self.width + 3) * (self.height + 2) * 3);
+
+        // Top edge, with the title padded by a space either side
+        let caption: Vec<char> = match title {
+            Some(title) if self.width >= 3 => format!(" {} ", title).chars().take(self.width).collect(),
+            _ => Vec::new()
+        };
+        let left = (self.width - caption.len()) / 2;
+        text.push(top_left);
+        text.extend((0..left).map(|_| horizontal));
+        text.extend(caption.iter());
+        text.extend((left + caption.len()..self.width).map(|_| horizontal));
+        text.push(top_right);
+
+        for row in self.rows() {
+            text.push('\n');
+            text.push(vertical);
+            text.extend(row.iter().map(|cell| charset.to_char(cell)));
+            text.push(vertical);
+        }
+
+        text.push('\n');
+        text.push(bottom_left);
+        text.extend((0..self.width).map(|_| horizontal));
+        text.push(bottom_right);
+        text
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> Grid {
+        Grid::from_text(".O.\n..O\nOOO", 'O').unwrap()
+    }
+
+    #[test]
+    fn to_string_framed_should_draw_ascii_frame() {
+        assert_eq!(glider().to_string_framed(FrameStyle::Ascii), "+---+\n| O |\n|  O|\n|OOO|\n+---+");
+    }
+
+    #[test]
+    fn to_string_framed_should_draw_unicode_frame() {
+        assert_eq!(glider().to_string_framed(FrameStyle::Unicode), "┌───┐\n│ O │\n│  O│\n│OOO│\n└───┘");
+    }
+
+    #[test]
+    fn to_string_framed_with_should_centre_title_and_use_charset() {
+        // given
+        let charset = CharSet::new('.', '#');
+        let grid = Grid::from_text("O.........", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_string_framed_with(FrameStyle::Ascii, &charset, Some("Gen 5")),
+            "+- Gen 5 --+\n|#.........|\n+----------+");
+        assert_eq!(grid.to_string_framed_with(FrameStyle::Unicode, &charset, Some("Glider gun")),
+            "┌ Glider gu┐\n│#.........│\n└──────────┘");
+        assert_eq!(glider().to_string_framed_with(FrameStyle::Ascii, &charset, Some("Glider")),
+            "+ Gl+\n|.#.|\n|..#|\n|###|\n+---+");
+    }
+}
+
+/*****************************************************************************/
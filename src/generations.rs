@@ -0,0 +1,318 @@
+/* generations.rs - Generations rules with multi-state decaying cells
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use core::str::FromStr;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Grid, LifeError, Rule};
+
+/*****************************************************************************/
+
+// Birth/survival Rule where live cells which fail to survive decay through
+// states 2 to states-1 before dying, only state 1 cells count as neighbours
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GenRule {
+    rule:   Rule,
+    states: u8
+}
+
+impl GenRule {
+    // B2/S/3
+    pub const BRIANS_BRAIN: GenRule = GenRule {
+        rule:   Rule::SEEDS,
+        states: 3
+    };
+
+    // Return a GenRule of given Rule and total number of states including dead
+    pub fn new(rule: Rule, states: u8) -> Result<GenRule, LifeError> {
+        if states < 2 {
+            return Err(LifeError::InvalidRule(format!("{} states is too few, at least 2 are needed", states)));
+        }
+        Ok(GenRule { rule, states })
+    }
+
+    // Return a GenRule parsed from B/S/C ("B2/S/3", "B2/S/C3") or S/B/C ("/2/3") notation
+    pub fn parse(text: &str) -> Result<GenRule, LifeError> {
+        let text = text.trim();
+        let (rule, states) = match text.rfind('/') {
+            Some(index) => (&text[..index], &text[index + 1..]),
+            None => return Err(LifeError::InvalidRule(format!("'{}' should have three parts separated by '/'", text)))
+        };
+
+        let states = states.trim_start_matches(['C', 'c']);
+        match states.parse::<u8>() {
+            Ok(states) => GenRule::new(Rule::parse(rule)?, states),
+            Err(_) => Err(LifeError::InvalidRule(format!("'{}' should end with a number of states 2 to 255", text)))
+        }
+    }
+
+    // Return birth/survival Rule
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    // Return total number of states including dead
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    // Return next cell state given number of state 1 neighbours
+    pub fn apply(&self, state: u8, neighbours: u8) -> u8 {
+        match state {
+            0 => if self.rule.birth(neighbours) { 1 } else { 0 },
+            1 if self.rule.survival(neighbours) => 1,
+            _ => (state + 1) % self.states
+        }
+    }
+}
+
+impl FromStr for GenRule {
+    type Err = LifeError;
+
+    fn from_str(text: &str) -> Result<GenRule, LifeError> {
+        GenRule::parse(text)
+    }
+}
+
+// Format in canonical B/S/C notation
+impl fmt::Display for GenRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/C{}", self.rule, self.states)
+    }
+}
+
+/*****************************************************************************/
+
+// Grid of multi-state cells, 0 is dead, 1 is alive and higher states are decaying
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GenGrid {
+    cells:  Vec<Vec<u8>>,
+    states: u8
+}
+
+impl GenGrid {
+    const DEAD_CELL:    char = ' ';
+    const LIVE_CELL:    char = 'O';
+    const DYING_CELLS:  [char; 4] = ['o', '+', ':', '.'];
+
+    // Return a GenGrid of dead cells for given number of states
+    pub fn new(width: usize, height: usize, states: u8) -> Result<GenGrid, LifeError> {
+        if width == 0 || height == 0 {
+            return Err(LifeError::ZeroDimension { width, height });
+        }
+        Ok(GenGrid { cells: vec![vec![0; width]; height], states: states.max(2) })
+    }
+
+    // Return a GenGrid with cells alive where they are alive in given Grid
+    pub fn from_grid(grid: &Grid, states: u8) -> GenGrid {
+        GenGrid {
+            cells:  grid.to_bool_rows().into_iter()
+                    .map(|row| row.into_iter().map(|alive| alive as u8).collect())
+                    .collect(),
+            states: states.max(2)
+        }
+    }
+
+    // Return number of cells in a row
+    pub fn width(&self) -> usize {
+        self.cells.first().map_or(0, Vec::len)
+    }
+
+    // Return number of rows
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    // Return number of state 1 cells
+    pub fn population(&self) -> usize {
+        self.cells.iter().flatten().filter(|&&state| state == 1).count()
+    }
+
+    // Return state of cell at given position, or None if out of bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        self.cells.get(y).and_then(|row| row.get(x)).copied()
+    }
+
+    // Set state of cell at given position
+    pub fn set(&mut self, x: usize, y: usize, state: u8) -> Result<(), LifeError> {
+        match self.cells.get_mut(y).and_then(|row| row.get_mut(x)) {
+            Some(cell) => { *cell = state % self.states; Ok(()) },
+            None => Err(LifeError::OutOfBounds { x, y })
+        }
+    }
+
+    // Return a Grid with only state 1 cells alive
+    pub fn to_grid(&self) -> Grid {
+        Grid::from_coords(self.width(), self.height(),
+            self.cells.iter().enumerate()
+                .flat_map(|(y, row)| row.iter().enumerate()
+                    .filter(|(_, &state)| state == 1)
+                    .map(move |(x, _)| (x, y))))
+            .unwrap_or_else(|_| unreachable!("GenGrid dimensions are non-zero"))
+    }
+
+    // Return next GenGrid state, wrapping around the edges
+    pub fn next(&self, rule: &GenRule) -> GenGrid {
+        GenGrid {
+            cells:  self.cells.iter().enumerate()
+                    .map(|(y, row)| row.iter().enumerate()
+                        .map(|(x, &state)| rule.apply(state, self.neighbours(x, y)))
+                        .collect())
+                    .collect(),
+            states: rule.states
+        }
+    }
+
+    // Map cell state to a char, decaying states fade out through DYING_CELLS and
+    // states past the number of states are shown as the last of them
+    pub fn to_char(&self, state: u8) -> char {
+        match state {
+            0 => GenGrid::DEAD_CELL,
+            1 => GenGrid::LIVE_CELL,
+            _ => {
+                let dying = (self.states - 2).max(1) as usize;
+                let index = (state as usize - 2) * GenGrid::DYING_CELLS.len() / dying;
+                GenGrid::DYING_CELLS[index.min(GenGrid::DYING_CELLS.len() - 1)]
+            }
+        }
+    }
+
+    // Return number of state 1 neighbours for given cell position
+    fn neighbours(&self, x: usize, y: usize) -> u8 {
+        let cells  = &self.cells;
+        let height = cells.len();
+        let width  = cells[0].len();
+
+        let left   = if x > 0 { x - 1 } else { width - 1 };
+        let right  = if x < width - 1 { x + 1 } else { 0 };
+        let top    = if y > 0 { y - 1 } else { height - 1 };
+        let bottom = if y < height - 1 { y + 1 } else { 0 };
+
+        [cells[top][left],    cells[top][x],    cells[top][right],
+         cells[y][left],                        cells[y][right],
+         cells[bottom][left], cells[bottom][x], cells[bottom][right]]
+            .iter().filter(|&&state| state == 1).count() as u8
+    }
+}
+
+impl fmt::Display for GenGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}",
+            self.cells.iter()
+            .map(|row| row.iter().map(|&state| self.to_char(state)).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_should_accept_b_s_c_notation() {
+        assert_eq!(GenRule::parse("B2/S/3").unwrap(),  GenRule::BRIANS_BRAIN);
+        assert_eq!(GenRule::parse("B2/S/C3").unwrap(), GenRule::BRIANS_BRAIN);
+        assert_eq!(GenRule::parse("/2/3").unwrap(),    GenRule::BRIANS_BRAIN);
+        assert_eq!(GenRule::parse("B2/S345/4").unwrap(), GenRule::new(Rule::new(&[2], &[3, 4, 5]).unwrap(), 4).unwrap());
+    }
+
+    #[test]
+    fn parse_should_reject_invalid_rules() {
+        assert_eq!(GenRule::parse("B2/S").is_err(),     true);
+        assert_eq!(GenRule::parse("B2/S/1").is_err(),   true);
+        assert_eq!(GenRule::parse("B2/S/256").is_err(), true);
+        assert_eq!(GenRule::parse("B9/S/3").is_err(),   true);
+        assert_eq!(GenRule::parse("B2/S/x").is_err(),   true);
+    }
+
+    #[test]
+    fn fmt_should_format_canonical_b_s_c_notation() {
+        assert_eq!(GenRule::BRIANS_BRAIN.to_string(), "B2/S/C3");
+        assert_eq!(GenRule::parse("345/2/4").unwrap().to_string(), "B2/S345/C4");
+    }
+
+    #[test]
+    fn apply_should_decay_cells_which_fail_to_survive() {
+        // given
+        let star_wars = GenRule::parse("B2/S345/4").unwrap();
+
+        // then
+        assert_eq!(star_wars.apply(0, 2), 1);
+        assert_eq!(star_wars.apply(0, 3), 0);
+        assert_eq!(star_wars.apply(1, 4), 1);
+        assert_eq!(star_wars.apply(1, 2), 2);
+        assert_eq!(star_wars.apply(2, 2), 3);
+        assert_eq!(star_wars.apply(3, 2), 0);
+    }
+
+    #[test]
+    fn next_should_move_brians_brain_spaceship_at_light_speed() {
+        // given
+        let mut grid = GenGrid::new(6, 8, 3).unwrap();
+        grid.set(2, 5, 1).unwrap();
+        grid.set(3, 5, 1).unwrap();
+        grid.set(2, 6, 2).unwrap();
+        grid.set(3, 6, 2).unwrap();
+
+        // when
+        let next = grid.next(&GenRule::BRIANS_BRAIN);
+
+        // then
+        assert_eq!(next.to_string(), "      \n      \n      \n      \n  OO  \n  oo  \n      \n      ");
+    }
+
+    #[test]
+    fn next_should_return_brians_brain_spaceship_to_start_around_torus() {
+        // given
+        let mut grid = GenGrid::new(6, 8, 3).unwrap();
+        grid.set(2, 5, 1).unwrap();
+        grid.set(3, 5, 1).unwrap();
+        grid.set(2, 6, 2).unwrap();
+        grid.set(3, 6, 2).unwrap();
+        let start = grid.clone();
+
+        // when
+        for _ in 0..8 {
+            grid = grid.next(&GenRule::BRIANS_BRAIN);
+            assert_eq!(grid.population(), 2);
+        }
+
+        // then
+        assert_eq!(grid, start);
+    }
+
+    #[test]
+    fn next_should_match_grid_next_given_two_states() {
+        // given
+        let grid = Grid::random_seeded(10, 20, 15);
+        let rule = GenRule::new(Rule::CONWAY, 2).unwrap();
+
+        // then
+        assert_eq!(GenGrid::from_grid(&grid, 2).next(&rule).to_grid(), grid.next_with_rule(&Rule::CONWAY));
+    }
+
+    #[test]
+    fn to_char_should_map_decaying_states_to_distinct_chars() {
+        // given
+        let grid = GenGrid::new(1, 1, 6).unwrap();
+
+        // then
+        assert_eq!((0..6).map(|state| grid.to_char(state)).collect::<String>(), " Oo+:.");
+        assert_eq!(GenGrid::new(1, 1, 3).unwrap().to_char(2), 'o');
+    }
+
+    #[test]
+    fn to_char_should_give_last_dying_char_given_state_past_number_of_states() {
+        assert_eq!(GenGrid::new(1, 1, 6).unwrap().to_char(6), '.');
+        assert_eq!(GenGrid::new(1, 1, 3).unwrap().to_char(255), '.');
+    }
+}
+
+/*****************************************************************************/
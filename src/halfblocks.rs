@@ -0,0 +1,77 @@
+/* halfblocks.rs - Rendering two Grid rows per line with half-block chars
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::string::String;
+
+use crate::{Cell, Grid, LineEnding};
+
+/*****************************************************************************/
+
+// Chars for a pair of vertically adjacent cells, indexed by the upper cell being
+// alive plus twice the lower cell being alive
+const HALF_BLOCKS: [char; 4] = [' ', '▀', '▄', '█'];
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return pairs of rows of cells rendered as one line each of half-block chars,
+    // separated by "\n". Given an odd height the last row is paired with dead cells
+    pub fn render_halfblocks(&self) -> String {
+        let mut text = String::new();
+        self.render_halfblocks_into(&mut text, LineEnding::Lf);
+        text
+    }
+
+    // Render pairs of rows as render_halfblocks does, separated by given line
+    // ending, into buf, replacing its contents and reusing its capacity
+    pub fn render_halfblocks_into(&self, buf: &mut String, ending: LineEnding) {
+        let lines = self.height.div_ceil(2);
+        buf.clear();
+        buf.reserve((self.width * 3 + ending.as_str().len()) * lines);
+
+        let dead = Cell::default();
+        for (y, pair) in self.cells.chunks((self.width * 2).max(1)).enumerate() {
+            if y > 0 {
+                buf.push_str(ending.as_str());
+            }
+            let (upper, lower) = pair.split_at(self.width);
+            for (x, cell) in upper.iter().enumerate() {
+                let below = lower.get(x).unwrap_or(&dead);
+                buf.push(HALF_BLOCKS[cell.alive as usize | (below.alive as usize) << 1]);
+            }
+        }
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_halfblocks_should_pair_rows() {
+        // given
+        let grid = Grid::from_text("O.O.\n..OO\nO...\nO..O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.render_halfblocks(), "▀ █▄\n█  ▄");
+    }
+
+    #[test]
+    fn render_halfblocks_should_pair_last_row_with_dead_cells_given_odd_height() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+        let mut buf = String::from("previous frame");
+
+        // when
+        grid.render_halfblocks_into(&mut buf, LineEnding::CrLf);
+
+        // then
+        assert_eq!(buf, " ▀▄\r\n▀▀▀");
+        assert_eq!(grid.render_halfblocks(), " ▀▄\n▀▀▀");
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,420 @@
+/* hashlife.rs - HashLife engine, enabled by the hashlife feature
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use std::collections::HashMap;
+
+use crate::{Cell, Grid, LifeError, Rule};
+
+/*****************************************************************************/
+
+type NodeId = usize;
+
+const DEAD:     NodeId = 0;
+const ALIVE:    NodeId = 1;
+
+// Highest level of the root, so that coordinates of its corners fit in i64
+const MAX_LEVEL: u32 = 62;
+
+// Quadtree node of given level covering a square of 2^level cells, with nw,
+// ne, sw and se children of the level below. Level 0 nodes are single cells
+struct Node {
+    children:   [NodeId; 4],
+    level:      u32,
+    population: u64
+}
+
+// Pattern on an unbounded plane stored as a quadtree of hashed, shared nodes,
+// with the result of advancing each node memoised so that repetitive patterns
+// can be advanced exponentially far. The root is centred on the origin, cells
+// of the seeding Grid keep their positions. Rules with B0 are rejected, as
+// empty space always stays empty
+pub struct HashLife {
+    nodes:      Vec<Node>,
+    index:      HashMap<[NodeId; 4], NodeId>,
+    results:    HashMap<(NodeId, u32), NodeId>,
+    empty:      Vec<NodeId>,
+    root:       NodeId,
+    rule:       Rule,
+    generation: u64
+}
+
+impl HashLife {
+    // Return a HashLife universe of the live cells of a Grid evolving under given rule,
+    // or an error if the rule has B0
+    pub fn new(grid: &Grid, rule: Rule) -> Result<HashLife, LifeError> {
+        if rule.birth(0) {
+            return Err(LifeError::InvalidRule(format!("{} has B0, which HashLife does not support", rule)));
+        }
+        let mut life = HashLife {
+            nodes:      vec![
+                Node { children: [DEAD; 4], level: 0, population: 0 },
+                Node { children: [DEAD; 4], level: 0, population: 1 }],
+            index:      HashMap::new(),
+            results:    HashMap::new(),
+            empty:      vec![DEAD],
+            root:       DEAD,
+            rule,
+            generation: 0
+        };
+
+        let size = grid.width().max(grid.height()).max(4).next_power_of_two();
+        let level = size.trailing_zeros() + 1;
+        life.root = life.build(grid, -(size as i64), -(size as i64), level);
+        Ok(life)
+    }
+
+    // Return number of generations advanced
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Return number of live cells
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root].population
+    }
+
+    // Return the rule
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    // Advance given number of generations, as a step of each power of two in it,
+    // or return an error once the root would grow past 2^62 cells wide, having
+    // advanced the steps before it
+    pub fn advance(&mut self, generations: u64) -> Result<(), LifeError> {
+        for step in (0..64).filter(|step| generations & (1 << step) != 0) {
+            // The pattern must lie within the centre quarter of the root, on a root large
+            // enough to step 2^step at once, so that nothing escapes the result
+            while self.nodes[self.root].level < step + 3 || !self.is_padded(self.root) {
+                if self.nodes[self.root].level == MAX_LEVEL {
+                    let size = 1usize.checked_shl(MAX_LEVEL + 1).unwrap_or(usize::MAX);
+                    return Err(LifeError::TooLarge { width: size, height: size });
+                }
+                self.root = self.expand(self.root);
+            }
+            self.root = self.successor(self.root, step);
+            self.generation += 1 << step;
+        }
+        Ok(())
+    }
+
+    // Return (min_x, min_y, max_x, max_y) of live cells, or None if there are none
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let half = self.half_size();
+        self.bounds(self.root, -half, -half)
+    }
+
+    // Return a Grid of given size showing the cells with top left at (x, y)
+    pub fn to_grid(&self, x: i64, y: i64, width: usize, height: usize) -> Result<Grid, LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        let half = self.half_size();
+        self.fill(&mut grid, self.root, (-half).saturating_sub(x), (-half).saturating_sub(y));
+        Ok(grid)
+    }
+
+    // Return half the width of the root
+    fn half_size(&self) -> i64 {
+        1 << (self.nodes[self.root].level - 1)
+    }
+
+    // Return node of given level for cells of a Grid in the square with top left at (x, y)
+    fn build(&mut self, grid: &Grid, x: i64, y: i64, level: u32) -> NodeId {
+        let size = 1i64 << level;
+        if x + size <= 0 || y + size <= 0 || x >= grid.width() as i64 || y >= grid.height() as i64 {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return if grid[(x as usize, y as usize)].is_alive() { ALIVE } else { DEAD };
+        }
+        let half = size / 2;
+        let nw = self.build(grid, x, y, level - 1);
+        let ne = self.build(grid, x + half, y, level - 1);
+        let sw = self.build(grid, x, y + half, level - 1);
+        let se = self.build(grid, x + half, y + half, level - 1);
+        self.join(nw, ne, sw, se)
+    }
+
+    // Return the node with given children, creating it if new
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let children = [nw, ne, sw, se];
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+        let node = Node {
+            children,
+            level:      self.nodes[nw].level + 1,
+            population: children.iter().map(|&child| self.nodes[child].population).sum()
+        };
+        self.nodes.push(node);
+        self.index.insert(children, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    // Return the node of given level with no live cells
+    fn empty(&mut self, level: u32) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = self.empty[self.empty.len() - 1];
+            let empty = self.join(below, below, below, below);
+            self.empty.push(empty);
+        }
+        self.empty[level as usize]
+    }
+
+    // Return a node one level up with given node at its centre
+    fn expand(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.nodes[id].children;
+        let e = self.empty(self.nodes[id].level - 1);
+        let nw = self.join(e, e, e, nw);
+        let ne = self.join(e, e, ne, e);
+        let sw = self.join(e, sw, e, e);
+        let se = self.join(se, e, e, e);
+        self.join(nw, ne, sw, se)
+    }
+
+    // Return true if all live cells of a node lie within its centre quarter
+    fn is_padded(&self, id: NodeId) -> bool {
+        let node = &self.nodes[id];
+        if node.level < 3 {
+            return false;
+        }
+        // Grandchild of each child nearest the centre
+        [(0, 3), (1, 2), (2, 1), (3, 0)].iter().all(|&(child, inner)| {
+            let child = &self.nodes[node.children[child]];
+            let inner = &self.nodes[self.nodes[child.children[inner]].children[inner]];
+            inner.population == child.population
+        })
+    }
+
+    // Return the centre node one level down
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.nodes[id].children;
+        self.join(self.nodes[nw].children[3], self.nodes[ne].children[2], self.nodes[sw].children[1], self.nodes[se].children[0])
+    }
+
+    // Return the node of the same level straddling two side by side nodes
+    fn horizontal(&mut self, w: NodeId, e: NodeId) -> NodeId {
+        let (w, e) = (self.nodes[w].children, self.nodes[e].children);
+        self.join(w[1], e[0], w[3], e[2])
+    }
+
+    // Return the node of the same level straddling two nodes one above the other
+    fn vertical(&mut self, n: NodeId, s: NodeId) -> NodeId {
+        let (n, s) = (self.nodes[n].children, self.nodes[s].children);
+        self.join(n[2], n[3], s[0], s[1])
+    }
+
+    // Return the centre node one level down of a node of level k advanced 2^step
+    // generations, step being at most k - 2
+    fn successor(&mut self, id: NodeId, step: u32) -> NodeId {
+        let level = self.nodes[id].level;
+        if self.nodes[id].population == 0 {
+            return self.empty(level - 1);
+        }
+        if level == 2 {
+            return self.base_case(id);
+        }
+        if let Some(&result) = self.results.get(&(id, step)) {
+            return result;
+        }
+
+        // Nine overlapping nodes one level down covering the node
+        let [nw, ne, sw, se] = self.nodes[id].children;
+        let n01 = self.horizontal(nw, ne);
+        let n10 = self.vertical(nw, sw);
+        let n11 = self.centre(id);
+        let n12 = self.vertical(ne, se);
+        let n21 = self.horizontal(sw, se);
+        let nine = [nw, n01, ne, n10, n11, n12, sw, n21, se];
+
+        // Stepping at full speed advances 2^(k-3) in each of two stages, otherwise
+        // the first stage only takes the centres
+        let full = step == level - 2;
+        let mut stage = [DEAD; 9];
+        for (result, &node) in stage.iter_mut().zip(nine.iter()) {
+            *result = if full { self.successor(node, level - 3) } else { self.centre(node) };
+        }
+
+        let next_step = step.min(level - 3);
+        let mut quadrants = [DEAD; 4];
+        for (quadrant, &(x, y)) in quadrants.iter_mut().zip([(0, 0), (1, 0), (0, 1), (1, 1)].iter()) {
+            let at = |dx: usize, dy: usize| stage[(y + dy) * 3 + x + dx];
+            let joined = self.join(at(0, 0), at(1, 0), at(0, 1), at(1, 1));
+            *quadrant = self.successor(joined, next_step);
+        }
+
+        let result = self.join(quadrants[0], quadrants[1], quadrants[2], quadrants[3]);
+        self.results.insert((id, step), result);
+        result
+    }
+
+    // Return the centre 2x2 node of a 4x4 node advanced one generation
+    fn base_case(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (quadrant, &child) in self.nodes[id].children.iter().enumerate() {
+            for (i, &cell) in self.nodes[child].children.iter().enumerate() {
+                cells[(quadrant / 2) * 2 + i / 2][(quadrant % 2) * 2 + i % 2] = cell == ALIVE;
+            }
+        }
+
+        let offsets = self.rule.neighbourhood().offsets();
+        let next = |x: usize, y: usize| {
+            let neighbours = offsets.iter()
+                .filter(|&&(dx, dy)| cells[(y as isize + dy) as usize][(x as isize + dx) as usize])
+                .count() as u8;
+            if self.rule.apply(&Cell::new(cells[y][x]), neighbours).is_alive() { ALIVE } else { DEAD }
+        };
+        let (nw, ne, sw, se) = (next(1, 1), next(2, 1), next(1, 2), next(2, 2));
+        self.join(nw, ne, sw, se)
+    }
+
+    // Return bounding box of live cells of a node with top left at (x, y)
+    fn bounds(&self, id: NodeId, x: i64, y: i64) -> Option<(i64, i64, i64, i64)> {
+        let node = &self.nodes[id];
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some((x, y, x, y));
+        }
+        let half = 1i64 << (node.level - 1);
+        node.children.iter().enumerate()
+            .filter_map(|(i, &child)| self.bounds(child, x + (i as i64 % 2) * half, y + (i as i64 / 2) * half))
+            .fold(None, |bounds, (x0, y0, x1, y1)| match bounds {
+                None => Some((x0, y0, x1, y1)),
+                Some((bx0, by0, bx1, by1)) => Some((bx0.min(x0), by0.min(y0), bx1.max(x1), by1.max(y1)))
+            })
+    }
+
+    // Set the live cells of a node with top left at (x, y) relative to the Grid
+    fn fill(&self, grid: &mut Grid, id: NodeId, x: i64, y: i64) {
+        let node = &self.nodes[id];
+        let size = 1i64 << node.level;
+        if node.population == 0 || x.saturating_add(size) <= 0 || y.saturating_add(size) <= 0
+            || x >= grid.width() as i64 || y >= grid.height() as i64 {
+            return;
+        }
+        if node.level == 0 {
+            grid[(x as usize, y as usize)].alive = true;
+            return;
+        }
+        let half = size / 2;
+        for (i, &child) in node.children.iter().enumerate() {
+            self.fill(grid, child, x + (i as i64 % 2) * half, y + (i as i64 / 2) * half);
+        }
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const R_PENTOMINO:  &str = ".OO\nOO.\n.O.";
+    const ACORN:        &str = ".O.....\n...O...\nOO..OOO";
+
+    #[test]
+    fn new_should_keep_grid_cells_at_their_positions() {
+        // given
+        let grid = Grid::random_seeded(4, 13, 7);
+
+        // when
+        let life = HashLife::new(&grid, Rule::CONWAY).unwrap();
+
+        // then
+        assert_eq!(life.population(), grid.population() as u64);
+        assert_eq!(life.to_grid(0, 0, 13, 7).unwrap(), grid);
+        assert_eq!(life.bounding_box().map(|(x0, y0, _, _)| x0 >= 0 && y0 >= 0), Some(true));
+    }
+
+    #[test]
+    fn new_should_reject_rule_with_b0() {
+        // given
+        let grid = Grid::random_seeded(4, 13, 7);
+
+        // when
+        let result = HashLife::new(&grid, Rule::parse("B0/S8").unwrap());
+
+        // then
+        assert_eq!(result.err(), Some(LifeError::InvalidRule("B0/S8 has B0, which HashLife does not support".to_string())));
+    }
+
+    #[test]
+    fn advance_should_match_grid_for_each_generation() {
+        // given
+        let mut grid = Grid::new(64, 64).unwrap();
+        grid.insert(&Grid::random_seeded(8, 12, 12), 26, 26, crate::InsertMode::Replace, crate::Overflow::Clip);
+        let mut life = HashLife::new(&grid, Rule::HIGHLIFE).unwrap();
+
+        for generation in 1..=20 {
+            // when
+            grid = grid.next_with_rule(&Rule::HIGHLIFE);
+            life.advance(1).unwrap();
+
+            // then
+            assert_eq!(life.to_grid(0, 0, 64, 64).unwrap(), grid, "generation {}", generation);
+            assert_eq!(life.generation(), generation);
+        }
+    }
+
+    #[test]
+    fn advance_should_move_glider() {
+        // given
+        let glider = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+        let mut life = HashLife::new(&glider, Rule::CONWAY).unwrap();
+
+        // when
+        life.advance(4000).unwrap();
+
+        // then
+        assert_eq!(life.bounding_box(), Some((1000, 1000, 1002, 1002)));
+        assert_eq!(life.to_grid(1000, 1000, 3, 3).unwrap(), glider);
+    }
+
+    #[test]
+    fn advance_should_stabilise_r_pentomino_at_116_cells() {
+        // given
+        let mut life = HashLife::new(&Grid::from_text(R_PENTOMINO, 'O').unwrap(), Rule::CONWAY).unwrap();
+
+        // when
+        life.advance(1103).unwrap();
+
+        // then
+        assert_eq!(life.population(), 116);
+        life.advance(1000).unwrap();
+        assert_eq!(life.population(), 116);
+    }
+
+    #[test]
+    fn advance_should_run_acorn_a_million_generations() {
+        // given
+        let mut life = HashLife::new(&Grid::from_text(ACORN, 'O').unwrap(), Rule::CONWAY).unwrap();
+
+        // when
+        life.advance(1_000_000).unwrap();
+
+        // then
+        assert_eq!(life.generation(), 1_000_000);
+        assert_eq!(life.population(), 633);
+    }
+
+    #[test]
+    fn advance_should_give_error_given_generations_past_largest_root() {
+        // given
+        let glider = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+        let mut life = HashLife::new(&glider, Rule::CONWAY).unwrap();
+
+        // when
+        let result = life.advance(u64::MAX);
+
+        // then
+        let size = 1usize.checked_shl(63).unwrap_or(usize::MAX);
+        assert_eq!(result, Err(LifeError::TooLarge { width: size, height: size }));
+        assert_eq!(life.population(), 5);
+        assert!(life.bounding_box().is_some());
+        assert_eq!(life.to_grid(i64::MIN, i64::MAX, 4, 4).unwrap().population(), 0);
+    }
+}
+
+/*****************************************************************************/
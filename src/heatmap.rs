@@ -0,0 +1,127 @@
+/* heatmap.rs - Number of generations each cell of a Simulation has been alive
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::Grid;
+
+/*****************************************************************************/
+
+// Count for each cell of the generations it has been alive, saturating
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Heatmap {
+    counts: Vec<u32>,
+    width:  usize,
+    height: usize
+}
+
+impl Heatmap {
+    // Chars shading counts from none to the highest
+    const SHADES: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+    // Heatmap of no cells
+    pub(crate) const EMPTY: Heatmap = Heatmap { counts: Vec::new(), width: 0, height: 0 };
+
+    // Return a Heatmap of given size with every count 0
+    pub fn new(width: usize, height: usize) -> Heatmap {
+        Heatmap { counts: vec![0; width * height], width, height }
+    }
+
+    // Return number of cells in a row
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // Return number of rows
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Return count of cell at given position, or None if out of bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<u32> {
+        if x < self.width && y < self.height { Some(self.counts[y * self.width + x]) } else { None }
+    }
+
+    // Return the highest count
+    pub fn max(&self) -> u32 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    // Return rows of counts scaled so that the highest is 1.0, all 0.0 if none are counted
+    pub fn normalized(&self) -> Vec<Vec<f32>> {
+        let max = self.max().max(1) as f32;
+        self.counts.chunks(self.width.max(1))
+            .map(|row| row.iter().map(|&count| count as f32 / max).collect())
+            .collect()
+    }
+
+    // Add one to the count of each live cell of given Grid, starting again if its size differs
+    pub(crate) fn record(&mut self, grid: &Grid) {
+        if grid.width() != self.width || grid.height() != self.height {
+            *self = Heatmap::new(grid.width(), grid.height());
+        }
+        for (count, cell) in self.counts.iter_mut().zip(&grid.cells) {
+            *count = count.saturating_add(cell.alive as u32);
+        }
+    }
+
+    // Take one from the count of each live cell of given Grid, undoing record
+    pub(crate) fn forget(&mut self, grid: &Grid) {
+        for (count, cell) in self.counts.iter_mut().zip(&grid.cells) {
+            *count = count.saturating_sub(cell.alive as u32);
+        }
+    }
+
+    // Map count to a char of SHADES relative to the highest count
+    fn to_char(count: u32, max: u32) -> char {
+        let shades = Heatmap::SHADES.len() as u64 - 1;
+        Heatmap::SHADES[(count as u64 * shades / max.max(1) as u64) as usize]
+    }
+}
+
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let max = self.max();
+        write!(f, "{}",
+            self.counts.chunks(self.width.max(1))
+            .map(|row| row.iter().map(|&count| Heatmap::to_char(count, max)).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_should_shade_counts_relative_to_highest() {
+        // given
+        let mut heatmap = Heatmap::new(3, 2);
+        for _ in 0..9 {
+            heatmap.record(&Grid::from_coords(3, 2, vec![(0, 0)]).unwrap());
+        }
+        for _ in 0..3 {
+            heatmap.record(&Grid::from_coords(3, 2, vec![(1, 0), (2, 1)]).unwrap());
+        }
+
+        // when
+        let text = heatmap.to_string();
+
+        // then
+        assert_eq!(text, "@- \n  -");
+        assert_eq!(heatmap.normalized(), vec![vec![1.0, 1.0 / 3.0, 0.0], vec![0.0, 0.0, 1.0 / 3.0]]);
+    }
+
+    #[test]
+    fn normalized_should_be_zero_given_no_counts() {
+        assert_eq!(Heatmap::new(2, 1).normalized(), vec![vec![0.0, 0.0]]);
+    }
+}
+
+/*****************************************************************************/
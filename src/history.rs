@@ -0,0 +1,112 @@
+/* history.rs - Bounded history of Simulation steps for rewinding
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::{error, fmt};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+/*****************************************************************************/
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum HistoryError {
+    Disabled,
+    Exhausted { requested: usize, available: usize }
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HistoryError::Disabled => write!(f, "history is not enabled"),
+            HistoryError::Exhausted { requested, available } =>
+                write!(f, "cannot rewind {} generations, {} recorded", requested, available)
+        }
+    }
+}
+
+impl error::Error for HistoryError {}
+
+/*****************************************************************************/
+
+// A step recorded as the cells it changed, which toggled again undo it, and the
+// background state before it
+pub(crate) struct Step {
+    pub(crate) changed:     Vec<(usize, usize)>,
+    pub(crate) background:  bool
+}
+
+// The most recent steps up to a capacity, the oldest being dropped first
+pub(crate) struct History {
+    steps:      VecDeque<Step>,
+    capacity:   usize
+}
+
+impl History {
+    // Return an empty History of given capacity
+    pub(crate) fn new(capacity: usize) -> History {
+        History { steps: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    // Return number of steps recorded
+    pub(crate) fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    // Return the most recent step
+    pub(crate) fn last(&self) -> Option<&Step> {
+        self.steps.back()
+    }
+
+    // Record a step, dropping the oldest if full
+    pub(crate) fn push(&mut self, step: Step) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.steps.len() == self.capacity {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(step);
+    }
+
+    // Remove and return the most recent step
+    pub(crate) fn pop(&mut self) -> Option<Step> {
+        self.steps.pop_back()
+    }
+
+    // Forget all recorded steps
+    pub(crate) fn clear(&mut self) {
+        self.steps.clear();
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_should_drop_oldest_step_given_full_history() {
+        // given
+        let mut history = History::new(2);
+
+        // when
+        for x in 0..3 {
+            history.push(Step { changed: vec![(x, 0)], background: false });
+        }
+
+        // then
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.pop().map(|step| step.changed), Some(vec![(2, 0)]));
+        assert_eq!(history.pop().map(|step| step.changed), Some(vec![(1, 0)]));
+        assert_eq!(history.pop().map(|step| step.changed), None);
+    }
+
+    #[test]
+    fn history_error_should_describe_each_variant() {
+        assert_eq!(HistoryError::Disabled.to_string(), "history is not enabled");
+        assert_eq!(HistoryError::Exhausted { requested: 5, available: 3 }.to_string(), "cannot rewind 5 generations, 3 recorded");
+    }
+}
+
+/*****************************************************************************/
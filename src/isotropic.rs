@@ -0,0 +1,278 @@
+/* isotropic.rs - Isotropic non-totalistic rules in Hensel notation
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use core::str::FromStr;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{LifeError, Rule};
+
+/*****************************************************************************/
+
+// Neighbourhoods are u8 masks of the 8 surrounding cells, bit 7 to bit 0 being
+// NW, N, NE, W, E, SW, S, SE i.e. row major order skipping the centre cell.
+// Hensel letters for each neighbour count, with one representative mask of the
+// configurations each letter stands for. Counts above 4 use the complement of
+// the representative for 8 minus the count.
+const LETTERS: [&[(char, u8)]; 5] = [
+    &[],
+    &[('c', 0x01), ('e', 0x02)],
+    &[('c', 0x05), ('e', 0x0a), ('k', 0x0c), ('a', 0x03), ('i', 0x18), ('n', 0x24)],
+    &[('c', 0x25), ('e', 0x1a), ('k', 0x32), ('a', 0x0b), ('i', 0x07),
+      ('n', 0x0d), ('y', 0x31), ('q', 0x26), ('j', 0x0e), ('r', 0x19)],
+    &[('c', 0xa5), ('e', 0x5a), ('k', 0x33), ('a', 0x0f), ('i', 0x1d), ('n', 0x27), ('y', 0x35),
+      ('q', 0x36), ('j', 0x3a), ('r', 0x1b), ('t', 0x39), ('w', 0x2e), ('z', 0x3c)]
+];
+
+// Return Hensel letters and representative masks for given neighbour count
+fn letters(count: usize) -> impl Iterator<Item = (char, u8)> {
+    let complement = count > 4;
+    LETTERS[if complement { 8 - count } else { count }].iter()
+        .map(move |&(letter, mask)| (letter, if complement { !mask } else { mask }))
+}
+
+// Return mask rotated 90 degrees clockwise
+fn rotate(mask: u8) -> u8 {
+    // Destination bit for each source bit, NW moves to NE, N to E and so on
+    const DESTINATIONS: [u8; 8] = [2, 4, 7, 1, 6, 0, 3, 5];
+    (0..8).filter(|&bit| mask & (0x80 >> bit) != 0).fold(0, |rotated, bit| rotated | (0x80 >> DESTINATIONS[bit]))
+}
+
+// Return mask mirrored left to right
+fn flip(mask: u8) -> u8 {
+    const DESTINATIONS: [u8; 8] = [2, 1, 0, 4, 3, 7, 6, 5];
+    (0..8).filter(|&bit| mask & (0x80 >> bit) != 0).fold(0, |flipped, bit| flipped | (0x80 >> DESTINATIONS[bit]))
+}
+
+// Return all 8 rotations and reflections of mask
+fn orientations(mask: u8) -> [u8; 8] {
+    let mut result = [0; 8];
+    let mut current = mask;
+    for i in 0..4 {
+        result[2 * i] = current;
+        result[2 * i + 1] = flip(current);
+        current = rotate(current);
+    }
+    result
+}
+
+/*****************************************************************************/
+
+// Birth and survival conditions indexed by centre cell state and neighbourhood mask
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct IsotropicRule {
+    table:  [bool; 512]
+}
+
+impl IsotropicRule {
+    // Return an IsotropicRule parsed from Hensel notation e.g. "B3/S2-i34q"
+    pub fn parse(text: &str) -> Result<IsotropicRule, LifeError> {
+        let text = text.trim();
+        let error = |msg: &str| LifeError::InvalidRule(format!("'{}' {}", text, msg));
+
+        let mut parts = text.split('/');
+        let (birth, survival) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(birth), Some(survival), None) => (birth, survival),
+            _ => return Err(error("should have two parts separated by '/'"))
+        };
+
+        let strip = |part: &str, prefix: char| -> Option<String> {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(c) if c.to_ascii_lowercase() == prefix => Some(chars.as_str().to_string()),
+                _ => None
+            }
+        };
+        let (birth, survival) = match (strip(birth, 'b'), strip(survival, 's')) {
+            (Some(birth), Some(survival)) => (birth, survival),
+            _ => return Err(error("should be in B/S notation"))
+        };
+
+        let mut table = [false; 512];
+        for (centre, conditions) in [(0, birth), (1, survival)] {
+            for mask in IsotropicRule::masks(&conditions).map_err(|msg| error(&msg))? {
+                table[(centre << 8) | mask as usize] = true;
+            }
+        }
+        Ok(IsotropicRule { table })
+    }
+
+    // Return true if a cell with given centre state and neighbourhood mask is alive next
+    pub fn apply_neighbourhood(&self, mask: u8, centre: bool) -> bool {
+        self.table[((centre as usize) << 8) | mask as usize]
+    }
+
+    // Return masks selected by the conditions for one of birth or survival
+    fn masks(conditions: &str) -> Result<Vec<u8>, String> {
+        let mut masks = Vec::new();
+        let mut chars = conditions.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let count = match c.to_digit(10) {
+                Some(count) if count <= 8 => count as usize,
+                _ => return Err(format!("contains '{}', expected neighbour counts 0 to 8", c))
+            };
+
+            let negate = chars.peek() == Some(&'-');
+            if negate {
+                chars.next();
+            }
+            let mut selected = Vec::new();
+            while let Some(&letter) = chars.peek().filter(|c| c.is_ascii_lowercase()) {
+                if !letters(count).any(|(l, _)| l == letter) {
+                    return Err(format!("contains '{}{}', which is not a configuration of {} neighbours", count, letter, count));
+                }
+                selected.push(letter);
+                chars.next();
+            }
+            if negate && selected.is_empty() {
+                return Err(format!("contains '{}-' without letters", count));
+            }
+
+            let all = selected.is_empty();
+            for (letter, mask) in letters(count) {
+                if all || selected.contains(&letter) != negate {
+                    masks.extend_from_slice(&orientations(mask));
+                }
+            }
+            if count == 0 || count == 8 {
+                masks.push(if count == 0 { 0x00 } else { 0xff });
+            }
+        }
+        Ok(masks)
+    }
+
+    // Return Hensel notation for the conditions of given centre state
+    fn conditions(&self, centre: bool) -> String {
+        let mut text = String::new();
+        for count in 0..=8 {
+            let (on, off): (Vec<char>, Vec<char>) = letters(count)
+                .map(|(letter, mask)| (letter, self.apply_neighbourhood(mask, centre)))
+                .fold((Vec::new(), Vec::new()), |(mut on, mut off), (letter, alive)| {
+                    if alive { on.push(letter) } else { off.push(letter) }
+                    (on, off)
+                });
+
+            let digit = (b'0' + count as u8) as char;
+            if count == 0 || count == 8 {
+                if self.apply_neighbourhood(if count == 0 { 0x00 } else { 0xff }, centre) {
+                    text.push(digit);
+                }
+            }
+            else if off.is_empty() {
+                text.push(digit);
+            }
+            else if !on.is_empty() {
+                text.push(digit);
+                if off.len() < on.len() {
+                    text.push('-');
+                    text.extend(off);
+                }
+                else {
+                    text.extend(on);
+                }
+            }
+        }
+        text
+    }
+}
+
+// Expand a totalistic Rule to every neighbourhood configuration
+impl From<Rule> for IsotropicRule {
+    fn from(rule: Rule) -> IsotropicRule {
+        let mut table = [false; 512];
+        for (index, entry) in table.iter_mut().enumerate() {
+            *entry = rule.apply_neighbourhood(index as u8, index >= 256);
+        }
+        IsotropicRule { table }
+    }
+}
+
+impl FromStr for IsotropicRule {
+    type Err = LifeError;
+
+    fn from_str(text: &str) -> Result<IsotropicRule, LifeError> {
+        IsotropicRule::parse(text)
+    }
+}
+
+// Format in Hensel notation, using whichever of listed or negated letters is shorter
+impl fmt::Display for IsotropicRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B{}/S{}", self.conditions(false), self.conditions(true))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orientations_should_partition_neighbourhoods_by_letter() {
+        // Every mask should belong to exactly one letter of its neighbour count
+        for mask in 0..=255u8 {
+            let count = mask.count_ones() as usize;
+            let matches = letters(count).filter(|&(_, rep)| orientations(rep).contains(&mask)).count();
+            assert_eq!(matches, if count == 0 || count == 8 { 0 } else { 1 }, "mask {:#04x}", mask);
+        }
+    }
+
+    #[test]
+    fn rotate_should_return_original_after_four_rotations() {
+        for mask in 0..=255u8 {
+            assert_eq!(rotate(rotate(rotate(rotate(mask)))), mask);
+            assert_eq!(flip(flip(mask)), mask);
+            assert_eq!(rotate(mask).count_ones(), mask.count_ones());
+        }
+        assert_eq!(rotate(0x80), 0x20);
+        assert_eq!(rotate(0x40), 0x08);
+        assert_eq!(flip(0x80),   0x20);
+    }
+
+    #[test]
+    fn parse_should_expand_totalistic_digits_to_all_configurations() {
+        assert_eq!(IsotropicRule::parse("B3/S23").unwrap(), IsotropicRule::from(Rule::CONWAY));
+        assert_eq!(IsotropicRule::parse("B3/S2cekain3").unwrap(), IsotropicRule::from(Rule::CONWAY));
+        assert_eq!(IsotropicRule::parse("B0/S8").unwrap(), IsotropicRule::from(Rule::new(&[0], &[8]).unwrap()));
+    }
+
+    #[test]
+    fn parse_should_select_and_exclude_configurations() {
+        // given
+        let tlife = IsotropicRule::parse("B3/S2-i34q").unwrap();
+
+        // then
+        assert_eq!(tlife.apply_neighbourhood(0x42, true),  false);
+        assert_eq!(tlife.apply_neighbourhood(0x18, true),  false);
+        assert_eq!(tlife.apply_neighbourhood(0x24, true),  true);
+        assert_eq!(tlife.apply_neighbourhood(0x36, true),  true);
+        assert_eq!(tlife.apply_neighbourhood(0x6c, true),  true);
+        assert_eq!(tlife.apply_neighbourhood(0x0f, true),  false);
+        assert_eq!(tlife.apply_neighbourhood(0x07, false), true);
+        assert_eq!(tlife.apply_neighbourhood(0x36, false), false);
+    }
+
+    #[test]
+    fn parse_should_reject_invalid_rules() {
+        assert_eq!(IsotropicRule::parse("B3/S2x").is_err(),  true);
+        assert_eq!(IsotropicRule::parse("B3/S1k").is_err(),  true);
+        assert_eq!(IsotropicRule::parse("B3/S2-").is_err(),  true);
+        assert_eq!(IsotropicRule::parse("B9/S23").is_err(),  true);
+        assert_eq!(IsotropicRule::parse("23/3").is_err(),    true);
+        assert_eq!(IsotropicRule::parse("B3/S23/4").is_err(), true);
+    }
+
+    #[test]
+    fn fmt_should_round_trip_hensel_notation() {
+        for text in &["B3/S23", "B3/S2-i34q", "B2ce3-k/S12a4iny", "B0/S8", "B/S"] {
+            assert_eq!(&IsotropicRule::parse(text).unwrap().to_string(), text);
+        }
+    }
+}
+
+/*****************************************************************************/
@@ -2,14 +2,160 @@
 *  (c)2020 James Wright, see LICENSE file.
 */
 
+// Without the std feature only core and alloc are used, so the library builds for
+// embedded targets. Tests always link std
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
 extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+
+mod aged;
+#[cfg(feature = "gif")]
+mod animation;
+mod apgcode;
+#[cfg(feature = "ndarray")]
+mod array;
+mod bitgrid;
+mod boundary;
+mod braille;
+mod cells;
+mod charset;
+mod census;
+mod double_buffer;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod frame;
+mod generations;
+mod halfblocks;
+#[cfg(feature = "hashlife")]
+mod hashlife;
+mod heatmap;
+mod history;
+mod isotropic;
+mod life105;
+mod life106;
+mod ltl;
+#[cfg(feature = "std")]
+mod macrocell;
+mod parallelism;
+mod par;
+mod pattern;
+#[cfg(feature = "std")]
+pub mod patterns;
+mod raster;
+mod rle;
+mod rule;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "simd")]
+mod simd;
+mod simulation;
+mod snapshot;
+#[cfg(feature = "std")]
+mod sparse;
+mod stochastic;
+#[cfg(feature = "async")]
+mod stream;
+mod svg;
+mod symmetry;
+mod universe;
+mod weighted;
 
-use std::fmt;
+use core::{error, fmt, iter};
+#[cfg(feature = "std")]
+use std::io;
+use core::hash::{Hash, Hasher};
+use core::ops::{BitAnd, BitOr, BitXor, Index, IndexMut};
+use core::slice::Chunks;
+use core::convert::TryFrom;
+use core::str::FromStr;
 use rand::Rng;
+use alloc::string::String;
+use alloc::vec::Vec;
+use par::*;
+
+pub use aged::AgedGrid;
+#[cfg(feature = "gif")]
+pub use animation::GifOptions;
+pub use bitgrid::BitGrid;
+pub use boundary::{Boundary, EdgeMode};
+pub use census::Census;
+pub use charset::{CharSet, LineEnding};
+pub use double_buffer::DoubleBuffer;
+pub use frame::FrameStyle;
+pub use generations::{GenGrid, GenRule};
+#[cfg(feature = "hashlife")]
+pub use hashlife::HashLife;
+pub use heatmap::Heatmap;
+pub use history::HistoryError;
+pub use isotropic::IsotropicRule;
+pub use ltl::{LtlRule, Neighbourhood, Shape};
+pub use parallelism::{MaybeSync, Parallelism};
+pub use pattern::Pattern;
+pub use rle::RleError;
+pub use rule::Rule;
+pub use simulation::{CycleInfo, GenerationStats, Generations, Simulation, StabilityOptions, StabilityReport};
+pub use snapshot::SnapshotError;
+#[cfg(feature = "std")]
+pub use sparse::{SpaceshipInfo, SparseGrid};
+pub use stochastic::StochasticRule;
+pub use svg::SvgOptions;
+pub use symmetry::{Symmetry, SymmetrySet};
+pub use universe::Universe;
+pub use weighted::WeightedRule;
 
 /*****************************************************************************/
 
 #[derive(Debug, Eq, PartialEq)]
+pub enum LifeError {
+    OutOfBounds { x: usize, y: usize },
+    ZeroDimension { width: usize, height: usize },
+    // More cells than can be addressed
+    TooLarge { width: usize, height: usize },
+    EmptyPattern,
+    DimensionMismatch { expected: (usize, usize), found: (usize, usize) },
+    RaggedRows { row: usize, expected: usize, found: usize },
+    InvalidRule(String),
+    ParseError { line: usize, msg: String },
+    // Kind and message of an io::Error, kept comparable
+    #[cfg(feature = "std")]
+    Io { kind: io::ErrorKind, msg: String }
+}
+
+impl fmt::Display for LifeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LifeError::OutOfBounds { x, y } => write!(f, "cell position ({}, {}) is out of bounds", x, y),
+            LifeError::ZeroDimension { width, height } => write!(f, "grid dimensions {}x{} must be non-zero", width, height),
+            LifeError::TooLarge { width, height } => write!(f, "grid dimensions {}x{} hold too many cells", width, height),
+            LifeError::EmptyPattern => write!(f, "pattern text contains no cells"),
+            LifeError::DimensionMismatch { expected, found } =>
+                write!(f, "grid dimensions {}x{} do not match {}x{}", found.0, found.1, expected.0, expected.1),
+            LifeError::RaggedRows { row, expected, found } =>
+                write!(f, "row {} has {} cells, expected {}", row, found, expected),
+            LifeError::InvalidRule(msg) => write!(f, "invalid rule: {}", msg),
+            LifeError::ParseError { line, msg } => write!(f, "line {}: {}", line, msg),
+            #[cfg(feature = "std")]
+            LifeError::Io { msg, .. } => write!(f, "i/o error: {}", msg)
+        }
+    }
+}
+
+impl error::Error for LifeError {}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for LifeError {
+    fn from(err: io::Error) -> LifeError {
+        LifeError::Io { kind: err.kind(), msg: err.to_string() }
+    }
+}
+
+/*****************************************************************************/
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct Cell {
     alive:  bool
 }
@@ -18,191 +164,2595 @@ impl Cell {
     const EMPTY_CELL:   char = ' ';
     const LIVE_CELL:    char = 'O';
 
+    // Return a cell with given state
+    pub const fn new(alive: bool) -> Cell {
+        Cell { alive }
+    }
+
+    // Return a live cell
+    pub const fn alive() -> Cell {
+        Cell { alive: true }
+    }
+
+    // Return a dead cell
+    pub const fn dead() -> Cell {
+        Cell { alive: false }
+    }
+
     // Return next cell state
     pub fn next(&self, neighbours: u8) -> Cell {
         Cell { alive: (neighbours == 3) || ((neighbours == 2) && self.alive) }
     }
 
-    // Map cell state to a char
+    // Return true if cell is alive
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    // Map cell state to a char of the default CharSet
     pub fn to_char(&self) -> char {
-        if self.alive { Cell::LIVE_CELL } else { Cell::EMPTY_CELL }
+        CharSet::DEFAULT.to_char(self)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(alive: bool) -> Cell {
+        Cell { alive }
+    }
+}
+
+impl From<Cell> for bool {
+    fn from(cell: Cell) -> bool {
+        cell.alive
+    }
+}
+
+/*****************************************************************************/
+
+// SplitMix64 PRNG (Steele, Lea & Flood 2014), kept in the crate so that seeded
+// output is identical across platforms and versions of the rand crate
+struct SplitMix64 {
+    state:  u64
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    // Return next 64 bit output
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Return true with given probability, using the top 53 bits of the next output
+    fn next_bool(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+// FNV-1a hash (Fowler, Noll & Vo), used for looking up Grid states as std's
+// DefaultHasher is not available without std
+pub(crate) struct Fnv1a {
+    state:  u64
+}
+
+impl Fnv1a {
+    pub(crate) fn new() -> Fnv1a {
+        Fnv1a { state: 0xcbf2_9ce4_8422_2325 }
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = (self.state ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    // Words are hashed little endian so that hashes match on every target
+    fn write_u64(&mut self, word: u64) {
+        self.write(&word.to_le_bytes());
     }
 }
 
 /*****************************************************************************/
 
+// How cells of an inserted Grid combine with existing cells
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InsertMode {
+    Replace,
+    Or
+}
+
+// How cells falling outside of a Grid are handled
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Overflow {
+    Wrap,
+    Clip
+}
+
+// Where a pattern read from a file is positioned within a Grid
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Placement {
+    // The top left of the pattern's bounding box is at (x, y)
+    Origin { x: usize, y: usize },
+    // The pattern's bounding box is centred, rounding up and left
+    Centre
+}
+
+// Which part of a Grid stays in place when it is resized
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Anchor {
+    // Cells keep their positions, rows and columns are added or removed at the
+    // right and bottom edges
+    TopLeft,
+    // The centre keeps its position, rows and columns being added or removed
+    // evenly at opposite edges, an odd one at the right or bottom edge
+    Centre
+}
+
+// Descriptive information read from a pattern file
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PatternMeta {
+    pub name:       Option<String>,
+    pub comments:   Vec<String>,
+    pub rule:       Option<Rule>
+}
+
+// Cells which changed between two Grids
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GridDiff {
+    pub births: Vec<(usize, usize)>,
+    pub deaths: Vec<(usize, usize)>
+}
+
+impl GridDiff {
+    // Return true if no cells changed
+    pub fn is_empty(&self) -> bool {
+        self.births.is_empty() && self.deaths.is_empty()
+    }
+}
+
+// Cells are stored in a single row major buffer, the cell at (x, y) being at
+// index y * width + x.
+// Wrapping around the edges, each of the 8 positions around a cell wraps on its
+// own, so in a Grid less than 3 cells across a cell is counted once per position
+// wrapping onto it: in a Grid 1 cell wide a cell's left and right neighbours are
+// itself, in one 2 cells wide they are both the other cell of its row, and the
+// cell of a 1x1 Grid is its own neighbour 8 times. Every engine counts this way.
+// Grid::new and the parsers reject zero dimensions, but the random constructors
+// return a Grid with no cells given one, which steps to itself and renders as ""
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Grid {
-    cells:  Vec<Vec<Cell>>
+    cells:  Vec<Cell>,
+    width:  usize,
+    height: usize
 }
 
 impl Grid {
-    // Return a Grid of randomised cell states
-    pub fn random<R: Rng>(mut rng: R, width: usize, height: usize) -> Grid {
+    // Return a Grid of dead cells, dimensions must be non-zero and their cells
+    // must fit in memory
+    pub fn new(width: usize, height: usize) -> Result<Grid, LifeError> {
+        if width == 0 || height == 0 {
+            return Err(LifeError::ZeroDimension { width, height });
+        }
+        let count = width.checked_mul(height).ok_or(LifeError::TooLarge { width, height })?;
+        let mut cells = Vec::new();
+        cells.try_reserve_exact(count).map_err(|_| LifeError::TooLarge { width, height })?;
+        cells.resize(count, Cell { alive: false });
+
+        Ok(Grid {
+            cells,
+            width,
+            height
+        })
+    }
+
+    // Return a Grid of randomised cell states, each cell is drawn with gen_bool
+    // rather than from the low bit of gen::<u32> as it was before density was
+    // added, so a given RNG or seed no longer reproduces the Grid it gave before
+    pub fn random<R: Rng>(rng: R, width: usize, height: usize) -> Grid {
+        Grid::random_with_density(rng, width, height, 0.5)
+    }
+
+    // Return a Grid of randomised cell states with given probability of each cell being alive,
+    // panics if density is outside of the range 0.0 to 1.0
+    pub fn random_with_density<R: Rng>(mut rng: R, width: usize, height: usize, density: f64) -> Grid {
+        assert!((0.0..=1.0).contains(&density), "density {} is outside of the range 0.0 to 1.0", density);
         Grid {
-            cells:  (0..height)
-                    .map(|_| (0..width).map(|_| Cell { alive: (rng.gen::<u32>() & 1) != 0 }).collect())
-                    .collect()
+            cells:  (0..width * height).map(|_| Cell { alive: rng.gen_bool(density) }).collect(),
+            width,
+            height
         }
     }
 
-    // Return next Grid state
-    pub fn next<F: Fn(&Cell, u8)->Cell>(&self, cell_func: F) -> Grid {
+    // Return a reproducible Grid of randomised cell states for given seed
+    pub fn random_seeded(seed: u64, width: usize, height: usize) -> Grid {
+        Grid::random_seeded_with_density(seed, width, height, 0.5)
+    }
+
+    // Return a reproducible Grid of randomised cell states for given seed and density,
+    // cells are generated in row major order from a SplitMix64 stream,
+    // panics if density is outside of the range 0.0 to 1.0
+    pub fn random_seeded_with_density(seed: u64, width: usize, height: usize, density: f64) -> Grid {
+        assert!((0.0..=1.0).contains(&density), "density {} is outside of the range 0.0 to 1.0", density);
+        let mut rng = SplitMix64::new(seed);
         Grid {
-            cells:  self.cells.iter().enumerate()
-                    .map(|(y, row)| row.iter().enumerate()
-                        .map(|(x, cell)| cell_func(&cell, self.neighbours(x, y)))
-                        .collect())
-                    .collect()
+            cells:  (0..width * height).map(|_| Cell { alive: rng.next_bool(density) }).collect(),
+            width,
+            height
         }
     }
 
-    // Return number of neighbours for given cell position
-    fn neighbours(&self, x: usize, y: usize) -> u8 {
-        let cells  = &self.cells;
-        let height = cells.len();
-        let width  = cells[0].len();
+    // Return a Grid parsed from lines of text, short lines are padded with dead cells
+    pub fn from_text(text: &str, alive_char: char) -> Result<Grid, LifeError> {
+        let width = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        if width == 0 {
+            return Err(LifeError::EmptyPattern);
+        }
 
-        let left   = if x > 0 { x - 1 } else { width - 1 };
-        let right  = if x < width - 1 { x + 1 } else { 0 };
-        let top    = if y > 0 { y - 1 } else { height - 1 };
-        let bottom = if y < height - 1 { y + 1 } else { 0 };
+        Ok(Grid {
+            cells:  text.lines()
+                    .flat_map(|line| line.chars()
+                        .map(|c| Cell { alive: c == alive_char })
+                        .chain(iter::repeat(Cell { alive: false }))
+                        .take(width))
+                    .collect(),
+            width,
+            height: text.lines().count()
+        })
+    }
 
-        (cells[top][left].alive    as u8) + (cells[top][x].alive    as u8) + (cells[top][right].alive    as u8) +
-        (cells[y][left].alive      as u8) +                                  (cells[y][right].alive      as u8) +
-        (cells[bottom][left].alive as u8) + (cells[bottom][x].alive as u8) + (cells[bottom][right].alive as u8)
+    // Return a Grid with cells at given positions alive, positions must be within the Grid
+    pub fn from_coords<I: IntoIterator<Item = (usize, usize)>>(width: usize, height: usize, coords: I) -> Result<Grid, LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        for (x, y) in coords {
+            grid.set(x, y, true)?;
+        }
+        Ok(grid)
     }
-}
 
-impl fmt::Display for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", 
-            self.cells.iter()
-            .map(|row| row.iter().map(Cell::to_char).collect::<String>() )
-            .collect::<Vec<String>>()
-            .join("\r\n"))
+    // Return a Grid with cells at given positions alive, ignoring positions outside the Grid
+    pub fn from_coords_clipped<I: IntoIterator<Item = (usize, usize)>>(width: usize, height: usize, coords: I) -> Result<Grid, LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        for (x, y) in coords {
+            grid.set(x, y, true).ok();
+        }
+        Ok(grid)
     }
-}
 
-/*****************************************************************************/
+    // Return cell states as rows of bools
+    pub fn to_bool_rows(&self) -> Vec<Vec<bool>> {
+        self.rows()
+            .map(|row| row.iter().map(Cell::is_alive).collect())
+            .collect()
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rand::rngs::mock::StepRng;
+    // Return number of cells in a row
+    pub fn width(&self) -> usize {
+        self.width
+    }
 
-    const CELL_DEAD:    Cell = Cell { alive: false };
-    const CELL_ALIVE:   Cell = Cell { alive: true  };
+    // Return number of rows
+    pub fn height(&self) -> usize {
+        self.height
+    }
 
-    #[test]
-    fn random_should_generate_grid_with_radomnly_populated_cells() {
-        // given
-        let width:  usize = 5;
-        let height: usize = 4;
-        let rng = StepRng::new(0, 1);
+    // Return number of live cells
+    pub fn population(&self) -> usize {
+        self.par_rows()
+            .map(|row| row.iter().filter(|cell| cell.alive).count())
+            .sum()
+    }
 
-        // when
-        let grid = Grid::random(rng, width, height);
-        
-        // then
-        assert_eq!(grid.cells.len(), height, "Grid should have correct # of rows");
-        assert_eq!(grid.cells[0].len(), width, "Grid should have correct # of cells in a row");
-        assert_eq!(grid.cells.iter().any(|row| row.len() != width), false);
-        assert_eq!(grid.cells.iter()
-            .map(|row| row.iter().filter(|&cell| cell.alive == true).count())
-            .sum::<usize>(), 
-            (width * height) / 2, "Half of grid cells should be alive");
+    // Return true if there are no live cells
+    pub fn is_empty(&self) -> bool {
+        !self.cells.par_iter().any(Cell::is_alive)
+    }
+
+    // Return (min_x, min_y, max_x, max_y) of live cells, or None if there are none.
+    // Extents are literal coordinates, patterns straddling the wrap seam are not joined up.
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        self.rows().enumerate()
+            .filter_map(|(y, row)| {
+                let min_x = row.iter().position(Cell::is_alive)?;
+                let max_x = row.iter().rposition(Cell::is_alive)?;
+                Some((min_x, y, max_x))
+            })
+            .fold(None, |bounds, (min_x, y, max_x)| match bounds {
+                None => Some((min_x, y, max_x, y)),
+                Some((bx0, by0, bx1, _)) => Some((bx0.min(min_x), by0, bx1.max(max_x), y))
+            })
     }
 
+    // Return positions of live cells in row major order
+    pub fn live_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.rows().enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate()
+                .filter(|(_, cell)| cell.alive)
+                .map(move |(x, _)| (x, y)))
+    }
 
-    #[test]
-    fn next_should_return_next_grid_given_cell_inversion_function() {
-        // given
-        fn cell_function(cell: &Cell, _neighbours: u8) -> Cell  { Cell { alive: !cell.alive } };
-        let grid = Grid {
-            cells:  vec!(vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD),
-                         vec!(CELL_DEAD, CELL_ALIVE, CELL_DEAD),
-                         vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD))
-        };
+    // Return cell at given position, or None if out of bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        self.offset(x, y).map(|offset| &self.cells[offset])
+    }
 
-        // when
-        let next_grid = grid.next(cell_function); 
+    // Set state of cell at given position
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) -> Result<(), LifeError> {
+        let offset = self.offset(x, y).ok_or(LifeError::OutOfBounds { x, y })?;
+        self.cells[offset].alive = alive;
+        Ok(())
+    }
 
-        // then
-        assert_eq!(next_grid.cells.len(),       grid.cells.len());
-        assert_eq!(next_grid.cells[0].len(),    grid.cells[0].len());
-        assert_eq!(next_grid.cells,             vec!(vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE),
-                                                     vec!(CELL_ALIVE, CELL_DEAD,   CELL_ALIVE),
-                                                     vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE)));
+    // Flip state of cell at given position
+    pub fn toggle(&mut self, x: usize, y: usize) -> Result<(), LifeError> {
+        let offset = self.offset(x, y).ok_or(LifeError::OutOfBounds { x, y })?;
+        self.cells[offset].alive = !self.cells[offset].alive;
+        Ok(())
     }
 
-    #[test]
-    fn next_should_return_next_grid_given_cell_neighbour_function() {
-        // given
-        fn cell_function(_cell: &Cell, neighbours: u8) -> Cell  { Cell { alive: neighbours == 8 } };
-        let grid = Grid {
-            cells:  vec!(vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE),
-                         vec!(CELL_ALIVE, CELL_DEAD,   CELL_ALIVE),
-                         vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE))
+    // Set state of all cells in given rectangle, wrapping around the edges of the Grid
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, alive: bool) -> Result<(), LifeError> {
+        let grid_width  = self.width;
+        let grid_height = self.height;
+        if x >= grid_width || y >= grid_height {
+            return Err(LifeError::OutOfBounds { x, y });
+        }
+
+        for fy in 0..height.min(grid_height) {
+            let row = ((y + fy) % grid_height) * grid_width;
+            for fx in 0..width.min(grid_width) {
+                self.cells[row + (x + fx) % grid_width].alive = alive;
+            }
+        }
+        Ok(())
+    }
+
+    // Insert another Grid with its top left corner at given position, doing nothing
+    // if this Grid has no cells
+    pub fn insert(&mut self, other: &Grid, x: usize, y: usize, mode: InsertMode, overflow: Overflow) {
+        let width  = self.width;
+        let height = self.height;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        for (oy, row) in other.rows().enumerate() {
+            for (ox, cell) in row.iter().enumerate() {
+                let (tx, ty) = match overflow {
+                    Overflow::Wrap => ((x + ox) % width, (y + oy) % height),
+                    Overflow::Clip => (x + ox, y + oy)
+                };
+
+                if let Some(offset) = self.offset(tx, ty) {
+                    let target = &mut self.cells[offset];
+                    target.alive = match mode {
+                        InsertMode::Replace => cell.alive,
+                        InsertMode::Or      => cell.alive || target.alive
+                    };
+                }
+            }
+        }
+    }
+
+    // Return a copy of the bounding box of live cells, or None if there are none
+    pub fn crop(&self) -> Option<Grid> {
+        let (min_x, min_y, max_x, max_y) = self.bounding_box()?;
+        self.extract(min_x, min_y, max_x - min_x + 1, max_y - min_y + 1).ok()
+    }
+
+    // Return a copy of given rectangle, wrapping around the edges of the Grid, or
+    // an error if either the rectangle or the Grid has no cells
+    pub fn extract(&self, x: usize, y: usize, width: usize, height: usize) -> Result<Grid, LifeError> {
+        if width == 0 || height == 0 {
+            return Err(LifeError::ZeroDimension { width, height });
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(LifeError::ZeroDimension { width: self.width, height: self.height });
+        }
+
+        let grid_width  = self.width;
+        let grid_height = self.height;
+
+        Ok(Grid {
+            cells:  (0..height)
+                    .flat_map(|ey| {
+                        let row = &self[(y + ey) % grid_height];
+                        (0..width).map(move |ex| row[(x + ex) % grid_width])
+                    })
+                    .collect(),
+            width,
+            height
+        })
+    }
+
+    // Return a Grid of given size holding the cells of this one where they overlap,
+    // positioned by anchor. Shrinking clips cells and growing adds dead ones, so
+    // resizing back to the original size restores the cells kept. A zero dimension
+    // gives a Grid with no cells
+    pub fn resize(&self, width: usize, height: usize, anchor: Anchor) -> Grid {
+        // Position in the new Grid of the top left cell of this one
+        let (dx, dy) = match anchor {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Centre  => ((width as isize - self.width as isize) / 2, (height as isize - self.height as isize) / 2)
         };
+        let left  = dx.max(0) as usize;
+        let right = (self.width as isize + dx).clamp(0, width as isize) as usize;
 
-        // when
-        let next_grid = grid.next(cell_function); 
+        let mut cells = vec![Cell::dead(); width * height];
+        if left < right {
+            for (y, row) in cells.chunks_mut(width).enumerate() {
+                let source_y = y as isize - dy;
+                if source_y >= 0 && (source_y as usize) < self.height {
+                    let source = &self[source_y as usize];
+                    row[left..right].copy_from_slice(&source[(left as isize - dx) as usize..(right as isize - dx) as usize]);
+                }
+            }
+        }
+        Grid { cells, width, height }
+    }
 
-        // then
-        assert_eq!(next_grid.cells.len(),       grid.cells.len());
-        assert_eq!(next_grid.cells[0].len(),    grid.cells[0].len());
-        assert_eq!(next_grid.cells,             vec!(vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD),
-                                                     vec!(CELL_DEAD, CELL_ALIVE, CELL_DEAD),
-                                                     vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD)));
+    // Return a copy of the Grid with cells shifted by given offsets, wrapping around
+    // the edges, or an unchanged copy if it has no cells
+    pub fn translate(&self, dx: isize, dy: isize) -> Grid {
+        let width  = self.width;
+        let height = self.height;
+        if width == 0 || height == 0 {
+            return self.clone();
+        }
+
+        // Source offsets which undo the shift
+        let sx = width  - dx.rem_euclid(width as isize) as usize;
+        let sy = height - dy.rem_euclid(height as isize) as usize;
+
+        Grid {
+            cells:  (0..height)
+                    .flat_map(|y| {
+                        let row = &self[(y + sy) % height];
+                        (0..width).map(move |x| row[(x + sx) % width])
+                    })
+                    .collect(),
+            width,
+            height
+        }
     }
 
-    #[test]
-    fn fmt_should_format_grid_as_string() {
-        // given
-        let grid = Grid { 
-            cells:  vec!(vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE),
-                         vec!(CELL_ALIVE, CELL_DEAD,   CELL_ALIVE),
-                         vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE))
+    // Return top left positions, in row major order, of every window of the Grid
+    // matching the bounding box of the live cells of pattern cell for cell,
+    // windows wrapping around the edges. Empty if pattern has no live cells or
+    // its bounding box is larger than the Grid
+    pub fn find(&self, pattern: &Grid) -> Vec<(usize, usize)> {
+        let pattern = match pattern.crop() {
+            Some(pattern) if pattern.width <= self.width && pattern.height <= self.height => pattern,
+            _ => return Vec::new()
         };
 
-        // when
-        let formatted = format!("{}", grid); 
+        let (width, height) = (self.width, self.height);
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| pattern.rows().enumerate().all(|(py, row)| {
+                let cells = &self[(y + py) % height];
+                row.iter().enumerate().all(|(px, cell)| cells[(x + px) % width] == *cell)
+            }))
+            .collect()
+    }
 
-        // then
-        assert_eq!(formatted, "OOO\r\nO O\r\nOOO");
+    // Return top left positions, in row major order, of every window matching
+    // pattern in any of its rotations and reflections
+    pub fn find_all_orientations(&self, pattern: &Grid) -> Vec<(usize, usize)> {
+        let mut orientations: Vec<Grid> = Vec::new();
+        for orientation in pattern.orientations().iter().filter_map(Grid::crop) {
+            if !orientations.contains(&orientation) {
+                orientations.push(orientation);
+            }
+        }
+
+        let mut found: Vec<(usize, usize)> = orientations.iter().flat_map(|orientation| self.find(orientation)).collect();
+        found.sort_unstable_by_key(|&(x, y)| (y, x));
+        found
     }
 
-/*****************************************************************************/
+    // Return copies of the Grid in each of its 4 rotations, then each of those mirrored
+    pub(crate) fn orientations(&self) -> Vec<Grid> {
+        let rotations = vec![self.clone(), self.rotate_cw(), self.rotate_cw().rotate_cw(), self.rotate_ccw()];
+        let reflections: Vec<Grid> = rotations.iter().map(Grid::flip_horizontal).collect();
+        rotations.into_iter().chain(reflections).collect()
+    }
+
+    // Return a copy of the Grid rotated 90 degrees clockwise
+    pub fn rotate_cw(&self) -> Grid {
+        let (width, height) = (self.width, self.height);
+        Grid {
+            cells:  (0..width)
+                    .flat_map(|x| (0..height).rev().map(move |y| self.cells[y * width + x]))
+                    .collect(),
+            width:  height,
+            height: width
+        }
+    }
+
+    // Return a copy of the Grid rotated 90 degrees counter clockwise
+    pub fn rotate_ccw(&self) -> Grid {
+        let (width, height) = (self.width, self.height);
+        Grid {
+            cells:  (0..width).rev()
+                    .flat_map(|x| (0..height).map(move |y| self.cells[y * width + x]))
+                    .collect(),
+            width:  height,
+            height: width
+        }
+    }
+
+    // Return a copy of the Grid mirrored left to right
+    pub fn flip_horizontal(&self) -> Grid {
+        Grid {
+            cells:  self.rows().flat_map(|row| row.iter().rev().copied()).collect(),
+            ..*self
+        }
+    }
+
+    // Return a copy of the Grid mirrored top to bottom
+    pub fn flip_vertical(&self) -> Grid {
+        Grid {
+            cells:  self.rows().rev().flatten().copied().collect(),
+            ..*self
+        }
+    }
+
+    // Return a copy of the Grid with all cell states flipped
+    pub fn invert(&self) -> Grid {
+        Grid {
+            cells:  self.cells.par_iter().map(|cell| Cell { alive: !cell.alive }).collect(),
+            ..*self
+        }
+    }
+
+    // Return a Grid of cells alive in either Grid
+    pub fn union(&self, other: &Grid) -> Result<Grid, LifeError> {
+        self.combine(other, |a, b| a || b)
+    }
+
+    // Return a Grid of cells alive in both Grids
+    pub fn intersect(&self, other: &Grid) -> Result<Grid, LifeError> {
+        self.combine(other, |a, b| a && b)
+    }
+
+    // Return a Grid of cells alive in this Grid but not the other
+    pub fn difference(&self, other: &Grid) -> Result<Grid, LifeError> {
+        self.combine(other, |a, b| a && !b)
+    }
+
+    // Return a Grid of cells alive in exactly one of the Grids
+    pub fn symmetric_difference(&self, other: &Grid) -> Result<Grid, LifeError> {
+        self.combine(other, |a, b| a != b)
+    }
+
+    // Return a Grid combining the cell states of two same sized Grids
+    fn combine<F: Fn(bool, bool)->bool + MaybeSync>(&self, other: &Grid, op: F) -> Result<Grid, LifeError> {
+        self.check_dimensions(other)?;
+        Ok(Grid {
+            cells:  self.cells.par_iter().zip(other.cells.par_iter())
+                    .map(|(cell, other_cell)| Cell { alive: op(cell.alive, other_cell.alive) })
+                    .collect(),
+            ..*self
+        })
+    }
+
+    // Return positions of cells born and died going from this Grid to the other
+    pub fn diff(&self, other: &Grid) -> Result<GridDiff, LifeError> {
+        self.check_dimensions(other)?;
+        let row_diffs: Vec<GridDiff> = self.par_rows().zip(other.par_rows()).enumerate()
+            .map(|(y, (row, other_row))| {
+                let mut diff = GridDiff::default();
+                for (x, (cell, other_cell)) in row.iter().zip(other_row).enumerate() {
+                    match (cell.alive, other_cell.alive) {
+                        (false, true) => diff.births.push((x, y)),
+                        (true, false) => diff.deaths.push((x, y)),
+                        _ => {}
+                    }
+                }
+                diff
+            })
+            .collect();
+
+        Ok(row_diffs.into_iter().fold(GridDiff::default(), |mut diff, row_diff| {
+            diff.births.extend(row_diff.births);
+            diff.deaths.extend(row_diff.deaths);
+            diff
+        }))
+    }
+
+    // Return an error if other Grid has different dimensions
+    fn check_dimensions(&self, other: &Grid) -> Result<(), LifeError> {
+        if self.width() != other.width() || self.height() != other.height() {
+            return Err(LifeError::DimensionMismatch {
+                expected:   (self.width(), self.height()),
+                found:      (other.width(), other.height())
+            });
+        }
+        Ok(())
+    }
+
+    // Return next Grid state, counting neighbours of many cells at once given the simd feature
+    pub fn next<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, cell_func: F) -> Grid {
+        self.next_with_parallelism(cell_func, &Parallelism::Auto)
+    }
+
+    // Return next Grid state computed on the calling thread
+    pub fn next_serial<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, cell_func: F) -> Grid {
+        self.next_with_parallelism(cell_func, &Parallelism::Serial)
+    }
+
+    // Return next Grid state, spreading rows across threads as given
+    pub fn next_with_parallelism<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, cell_func: F, parallelism: &Parallelism) -> Grid {
+        let mut next = self.clone();
+        next.step_cells_into(self, cell_func, parallelism);
+        next
+    }
+
+    // Return next Grid state counting the neighbours of one cell at a time, as
+    // next does without the simd feature
+    pub fn next_scalar<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, cell_func: F) -> Grid {
+        self.map_cells(|x, y, cell| cell_func(cell, self.neighbours(x, y)))
+    }
+
+    // Write next Grid state into target without allocating, target must be the same size
+    pub fn step_into<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, target: &mut Grid, cell_func: F) -> Result<(), LifeError> {
+        self.check_dimensions(target)?;
+        target.step_cells_into(self, cell_func, &Parallelism::Auto);
+        Ok(())
+    }
+
+    // Return Grid state after n generations, stepping between two Grids
+    pub fn step_n<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, n: usize, cell_func: F) -> Grid {
+        let mut buffer = DoubleBuffer::new(self.clone());
+        for _ in 0..n {
+            buffer.step(&cell_func);
+        }
+        buffer.into_grid()
+    }
+
+    // Return first Grid state and generation for which pred holds, checking from
+    // generation 0, or the state after max generations if it never holds
+    pub fn step_until<F, P>(&self, cell_func: F, pred: P, max: usize) -> (Grid, usize)
+        where F: Fn(&Cell, u8)->Cell + MaybeSync, P: Fn(&Grid, usize)->bool {
+        let mut buffer = DoubleBuffer::new(self.clone());
+        let mut generation = 0;
+        while generation < max && !pred(buffer.grid(), generation) {
+            buffer.step(&cell_func);
+            generation += 1;
+        }
+        (buffer.into_grid(), generation)
+    }
+
+    // Set cells to the next state of a Grid of the same size
+    #[cfg(feature = "simd")]
+    pub(crate) fn step_cells_into<F: Fn(&Cell, u8)->Cell + MaybeSync>(&mut self, grid: &Grid, cell_func: F, parallelism: &Parallelism) {
+        grid.simd_step_into(self, cell_func, parallelism);
+    }
+
+    // Set cells to the next state of a Grid of the same size
+    #[cfg(not(feature = "simd"))]
+    pub(crate) fn step_cells_into<F: Fn(&Cell, u8)->Cell + MaybeSync>(&mut self, grid: &Grid, cell_func: F, parallelism: &Parallelism) {
+        let count = grid.neighbour_counter(Neighbourhood::MOORE, Boundary::Torus);
+        grid.map_cells_into_with(self, parallelism, |x, y, cell| cell_func(cell, count(x, y)));
+    }
+
+    // Return next Grid state using given birth/survival rule, counting neighbours in its Neighbourhood
+    pub fn next_with_rule(&self, rule: &Rule) -> Grid {
+        self.next_with(|cell, neighbours| rule.apply(cell, neighbours), rule.neighbourhood(), Boundary::Torus)
+    }
+
+    // Return next Grid state using given isotropic non-totalistic rule
+    pub fn next_isotropic(&self, rule: &IsotropicRule) -> Grid {
+        self.map_cells(|x, y, cell| Cell::new(rule.apply_neighbourhood(self.neighbourhood(x, y), cell.alive)))
+    }
+
+    // Return next Grid state using given Larger than Life rule, wrapping around the edges
+    pub fn next_ltl(&self, rule: &LtlRule) -> Grid {
+        let counts = rule.neighbourhood().counts(self);
+        self.map_cells(|x, y, cell| rule.apply(cell, counts[y][x]))
+    }
+
+    // Return next Grid state using given stochastic rule, wrapping around the edges.
+    // A master seed drawn from rng seeds a SplitMix64 for each row, so rows are
+    // computed in parallel and the result is reproducible given the state of rng
+    pub fn next_stochastic<R: Rng>(&self, rule: &StochasticRule, rng: &mut R) -> Grid {
+        let seed    = rng.gen::<u64>();
+        let base    = rule.rule();
+        let count   = self.neighbour_counter(base.neighbourhood(), Boundary::Torus);
+
+        let mut next = self.clone();
+        next.cells.par_chunks_mut(self.width.max(1)).enumerate()
+            .for_each(|(y, row)| {
+                let mut row_rng = SplitMix64::new(seed.wrapping_add(y as u64));
+                for (x, cell) in row.iter_mut().enumerate() {
+                    let prob = if cell.alive { rule.survival_prob() } else { rule.birth_prob() };
+                    *cell = Cell::new(base.apply(cell, count(x, y)).alive && row_rng.next_bool(prob));
+                }
+            });
+        next
+    }
+
+    // Return next Grid state using given weighted neighbour rule, wrapping around the edges
+    pub fn next_weighted(&self, rule: &WeightedRule) -> Grid {
+        self.map_cells(|x, y, cell| rule.apply(cell, self.weighted_sum(x, y, rule.weights())))
+    }
+
+    // Return next Grid state, counting neighbours in given Neighbourhood and
+    // finding neighbours beyond the edges using given boundary
+    pub fn next_with<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, cell_func: F, neighbourhood: Neighbourhood, boundary: Boundary) -> Grid {
+        let mut next = self.clone();
+        self.step_with_into(&mut next, cell_func, neighbourhood, boundary, &Parallelism::Auto);
+        next
+    }
+
+    // Write next Grid state as next_with does into target of the same size,
+    // spreading rows across threads as given
+    pub(crate) fn step_with_into<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, target: &mut Grid, cell_func: F,
+        neighbourhood: Neighbourhood, boundary: Boundary, parallelism: &Parallelism) {
+        if neighbourhood == Neighbourhood::MOORE && boundary == Boundary::Torus {
+            target.step_cells_into(self, cell_func, parallelism);
+            return;
+        }
+
+        let count = self.neighbour_counter(neighbourhood, boundary);
+        self.map_cells_into_with(target, parallelism, |x, y, cell| cell_func(cell, count(x, y)));
+    }
+
+    // Return number of live neighbours of each cell indexed [y][x], wrapping
+    // around the edges as next does
+    pub fn neighbour_counts(&self) -> Vec<Vec<u8>> {
+        self.neighbour_counts_with(Neighbourhood::MOORE, Boundary::Torus)
+    }
+
+    // Return number of live neighbours of each cell indexed [y][x] in given
+    // Neighbourhood and boundary, as next_with counts them
+    pub fn neighbour_counts_with(&self, neighbourhood: Neighbourhood, boundary: Boundary) -> Vec<Vec<u8>> {
+        let count = self.neighbour_counter(neighbourhood, boundary);
+        (0..self.height).into_par_iter()
+            .map(|y| (0..self.width).map(|x| count(x, y)).collect())
+            .collect()
+    }
+
+    // Return function of a cell position giving its number of live neighbours in
+    // given Neighbourhood and boundary, which every step not using simd counts with
+    fn neighbour_counter(&self, neighbourhood: Neighbourhood, boundary: Boundary) -> impl Fn(usize, usize)->u8 + MaybeSync + '_ {
+        let moore_torus = neighbourhood == Neighbourhood::MOORE && boundary == Boundary::Torus;
+        let offsets = if moore_torus { Vec::new() } else { neighbourhood.offsets() };
+        move |x, y| if moore_torus { self.neighbours(x, y) } else { self.neighbours_with(x, y, &offsets, boundary) }
+    }
+
+    // Return a Grid of the same size with each cell given by cell_func of its
+    // position and current state
+    fn map_cells<F: Fn(usize, usize, &Cell)->Cell + MaybeSync>(&self, cell_func: F) -> Grid {
+        let mut next = self.clone();
+        self.map_cells_into(&mut next, cell_func);
+        next
+    }
+
+    // Write each cell given by cell_func of its position and current state into
+    // a Grid of the same size, bands of rows computed in parallel for large grids
+    fn map_cells_into<F: Fn(usize, usize, &Cell)->Cell + MaybeSync>(&self, target: &mut Grid, cell_func: F) {
+        self.map_cells_into_with(target, &Parallelism::Auto, cell_func);
+    }
+
+    // Write each cell given by cell_func of its position and current state into
+    // a Grid of the same size, spreading rows across threads as given
+    fn map_cells_into_with<F: Fn(usize, usize, &Cell)->Cell + MaybeSync>(&self, target: &mut Grid, parallelism: &Parallelism, cell_func: F) {
+        let width = self.width;
+        parallelism.for_each_band(&mut target.cells, width, |top, band| {
+            for (dy, row) in band.chunks_mut(width.max(1)).enumerate() {
+                let y = top + dy;
+                for (x, cell) in row.iter_mut().enumerate() {
+                    *cell = cell_func(x, y, &self.cells[y * width + x]);
+                }
+            }
+        });
+    }
+
+    // Return offset of given position in the cell buffer, or None if out of bounds
+    fn offset(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height { Some(y * self.width + x) } else { None }
+    }
+
+    // Return rows of cells from top to bottom
+    pub(crate) fn rows(&self) -> Chunks<'_, Cell> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    // Return rows of cells from top to bottom, to be processed in parallel
+    fn par_rows(&self) -> ParChunks<'_, Cell> {
+        self.cells.par_chunks(self.width.max(1))
+    }
+
+    // Return number of neighbours at given offsets from a cell position using given
+    // boundary, saturating at 255 for wide neighbourhoods
+    fn neighbours_with(&self, x: usize, y: usize, offsets: &[(isize, isize)], boundary: Boundary) -> u8 {
+        let width  = self.width;
+        let height = self.height;
+
+        offsets.iter()
+            .filter_map(|&(dx, dy)| boundary.resolve(x as isize + dx, y as isize + dy, width, height))
+            .filter(|&(nx, ny)| self.cells[ny * width + nx].alive)
+            .count().min(u8::MAX as usize) as u8
+    }
+
+    // Return the cells of the 3x3 block around given cell position in row major
+    // order, wrapping around the edges
+    fn block(&self, x: usize, y: usize) -> [Cell; 9] {
+        let cells  = &self.cells;
+        let width  = self.width;
+        let height = self.height;
+
+        let left   = if x > 0 { x - 1 } else { width - 1 };
+        let right  = if x < width - 1 { x + 1 } else { 0 };
+        let top    = (if y > 0 { y - 1 } else { height - 1 }) * width;
+        let middle = y * width;
+        let bottom = (if y < height - 1 { y + 1 } else { 0 }) * width;
+
+        [cells[top + left],    cells[top + x],    cells[top + right],
+         cells[middle + left], cells[middle + x], cells[middle + right],
+         cells[bottom + left], cells[bottom + x], cells[bottom + right]]
+    }
+
+    // Return sum of weights of live cells in the 3x3 block around given cell position
+    fn weighted_sum(&self, x: usize, y: usize, weights: &[i32; 9]) -> i32 {
+        self.block(x, y)
+            .iter().zip(weights.iter())
+            .filter(|(cell, _)| cell.alive)
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    // Return neighbourhood mask for given cell position, bit 7 to bit 0 being
+    // the NW, N, NE, W, E, SW, S and SE neighbours
+    fn neighbourhood(&self, x: usize, y: usize) -> u8 {
+        self.block(x, y).iter().enumerate()
+            .filter(|&(i, _)| i != 4)
+            .fold(0, |mask, (_, cell)| (mask << 1) | cell.alive as u8)
+    }
+
+    // Return number of neighbours for given cell position
+    fn neighbours(&self, x: usize, y: usize) -> u8 {
+        let cells  = &self.cells;
+        let width  = self.width;
+        let height = self.height;
+
+        let left   = if x > 0 { x - 1 } else { width - 1 };
+        let right  = if x < width - 1 { x + 1 } else { 0 };
+        let top    = (if y > 0 { y - 1 } else { height - 1 }) * width;
+        let middle = y * width;
+        let bottom = (if y < height - 1 { y + 1 } else { 0 }) * width;
+
+        (cells[top + left].alive    as u8) + (cells[top + x].alive    as u8) + (cells[top + right].alive    as u8) +
+        (cells[middle + left].alive as u8) +                                   (cells[middle + right].alive as u8) +
+        (cells[bottom + left].alive as u8) + (cells[bottom + x].alive as u8) + (cells[bottom + right].alive as u8)
+    }
+}
+
+// Index cells by (x, y) i.e. (column, row), panics if out of bounds
+impl Index<(usize, usize)> for Grid {
+    type Output = Cell;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Cell {
+        &self[y][x]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Grid {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Cell {
+        let width = self.width;
+        assert!(y < self.height, "row {} is out of bounds for height {}", y, self.height);
+        &mut self.cells[y * width..(y + 1) * width][x]
+    }
+}
+
+// Index rows by y, panics if out of bounds
+impl Index<usize> for Grid {
+    type Output = [Cell];
+
+    fn index(&self, y: usize) -> &[Cell] {
+        assert!(y < self.height, "row {} is out of bounds for height {}", y, self.height);
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+}
+
+impl BitOr for &Grid {
+    type Output = Result<Grid, LifeError>;
+
+    fn bitor(self, other: &Grid) -> Result<Grid, LifeError> {
+        self.union(other)
+    }
+}
+
+impl BitAnd for &Grid {
+    type Output = Result<Grid, LifeError>;
+
+    fn bitand(self, other: &Grid) -> Result<Grid, LifeError> {
+        self.intersect(other)
+    }
+}
+
+impl BitXor for &Grid {
+    type Output = Result<Grid, LifeError>;
+
+    fn bitxor(self, other: &Grid) -> Result<Grid, LifeError> {
+        self.symmetric_difference(other)
+    }
+}
+
+impl Hash for Grid {
+    // Hash dimensions as u64, so hashes do not depend on the width of usize, and
+    // rows packed into 64 cell words
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.width() as u64).hash(state);
+        (self.height() as u64).hash(state);
+        for row in self.rows() {
+            for chunk in row.chunks(64) {
+                chunk.iter().enumerate()
+                    .fold(0u64, |word, (i, cell)| word | ((cell.alive as u64) << i))
+                    .hash(state);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_with(f, &CharSet::DEFAULT, LineEnding::Lf)
+    }
+}
+
+// Convert rows of bools to a Grid, rows must all have the same non-zero length
+impl TryFrom<Vec<Vec<bool>>> for Grid {
+    type Error = LifeError;
+
+    fn try_from(rows: Vec<Vec<bool>>) -> Result<Grid, LifeError> {
+        let width  = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        if width == 0 {
+            return Err(LifeError::ZeroDimension { width, height });
+        }
+        if let Some((row, ragged)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(LifeError::RaggedRows { row, expected: width, found: ragged.len() });
+        }
+
+        Ok(Grid {
+            cells:  rows.into_iter().flatten().map(Cell::from).collect(),
+            width,
+            height
+        })
+    }
+}
+
+impl FromStr for Grid {
+    type Err = LifeError;
+
+    fn from_str(text: &str) -> Result<Grid, LifeError> {
+        Grid::from_text(text, Cell::LIVE_CELL)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::bool_comparison)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    const CELL_DEAD:    Cell = Cell::dead();
+    const CELL_ALIVE:   Cell = Cell::alive();
+
+    // Return a Grid of given rows of cells
+    fn grid_of(rows: Vec<Vec<Cell>>) -> Grid {
+        let (width, height) = (rows[0].len(), rows.len());
+        Grid { cells: rows.into_iter().flatten().collect(), width, height }
+    }
+
+    // Return rows of cells of a Grid
+    fn rows_of(grid: &Grid) -> Vec<Vec<Cell>> {
+        grid.rows().map(<[Cell]>::to_vec).collect()
+    }
+
+    #[test]
+    fn life_error_should_describe_each_variant() {
+        for (err, msg) in &[
+            (LifeError::OutOfBounds { x: 3, y: 4 },                         "cell position (3, 4) is out of bounds"),
+            (LifeError::ZeroDimension { width: 0, height: 2 },              "grid dimensions 0x2 must be non-zero"),
+            (LifeError::TooLarge { width: 9, height: 8 },                   "grid dimensions 9x8 hold too many cells"),
+            (LifeError::EmptyPattern,                                       "pattern text contains no cells"),
+            (LifeError::DimensionMismatch { expected: (4, 3), found: (3, 4) }, "grid dimensions 3x4 do not match 4x3"),
+            (LifeError::RaggedRows { row: 2, expected: 3, found: 1 },       "row 2 has 1 cells, expected 3"),
+            (LifeError::InvalidRule("B9/S".to_string()),                    "invalid rule: B9/S"),
+            (LifeError::ParseError { line: 7, msg: "oops".to_string() },    "line 7: oops")] {
+            assert_eq!(err.to_string(), *msg);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn life_error_should_convert_from_io_error() {
+        // given
+        let io_err = io::Error::new(io::ErrorKind::NotFound, "glider.rle not found");
+
+        // when
+        let err = LifeError::from(io_err);
+
+        // then
+        assert_eq!(err, LifeError::Io { kind: io::ErrorKind::NotFound, msg: "glider.rle not found".to_string() });
+        assert_eq!(err.to_string(), "i/o error: glider.rle not found");
+    }
+
+    #[test]
+    fn fallible_constructors_should_return_errors_instead_of_panicking() {
+        assert_eq!(Grid::new(0, 5).err(), Some(LifeError::ZeroDimension { width: 0, height: 5 }));
+        assert_eq!(Grid::from_text("", 'O').err(), Some(LifeError::EmptyPattern));
+        assert_eq!(Grid::from_coords(2, 2, vec![(2, 0)]).err(), Some(LifeError::OutOfBounds { x: 2, y: 0 }));
+        assert_eq!(Grid::new(2, 2).unwrap().set(0, 2, true), Err(LifeError::OutOfBounds { x: 0, y: 2 }));
+        assert_eq!(Grid::try_from(vec![vec![true], vec![]]).err(), Some(LifeError::RaggedRows { row: 1, expected: 1, found: 0 }));
+        assert!(matches!(Rule::parse("B3/S2x"), Err(LifeError::InvalidRule(_))));
+        assert!(matches!(Grid::from_rle("x = 1"), Err(RleError::MissingHeader) | Err(RleError::InvalidHeader { .. })));
+    }
+
+    #[test]
+    fn random_should_generate_grid_with_radomnly_populated_cells() {
+        // given
+        let width:  usize = 5;
+        let height: usize = 4;
+        // Outputs alternate between 0 and 2^63, so gen_bool(0.5) alternates between true and false
+        let rng = StepRng::new(0, 1 << 63);
+
+        // when
+        let grid = Grid::random(rng, width, height);
+        
+        // then
+        assert_eq!(grid.height(), height, "Grid should have correct # of rows");
+        assert_eq!(grid.width(), width, "Grid should have correct # of cells in a row");
+        assert_eq!(grid.rows().any(|row| row.len() != width), false);
+        assert_eq!(grid.rows()
+            .map(|row| row.iter().filter(|&cell| cell.is_alive() == true).count())
+            .sum::<usize>(), 
+            (width * height) / 2, "Half of grid cells should be alive");
+    }
+
+
+    #[test]
+    fn random_should_draw_cells_from_high_bits_of_rng() {
+        // given
+        // Every output is even, alternating below and above 2^63
+        let rng = StepRng::new(0, (1 << 63) + 2);
+
+        // when
+        let grid = Grid::random(rng, 6, 4);
+
+        // then
+        assert_eq!(grid.population(), 12);
+        assert!(grid.rows().all(|row| row.iter().step_by(2).all(|cell| cell.is_alive())));
+    }
+
+    #[test]
+    fn random_should_give_grid_with_no_cells_given_zero_dimension() {
+        for &(width, height) in &[(0, 0), (0, 5), (5, 0)] {
+            // given
+            let grid = Grid::random_seeded(1, width, height);
+            let mut simulation = Simulation::new(grid.clone(), Rule::CONWAY).with_incremental(true);
+
+            // when
+            simulation.step_n(2);
+
+            // then
+            assert_eq!((grid.width(), grid.height(), grid.population()), (width, height, 0));
+            assert_eq!(Grid::random(StepRng::new(0, 1), width, height), grid);
+            assert_eq!(grid.next(Cell::next), grid);
+            assert_eq!(grid.next_scalar(Cell::next), grid);
+            assert_eq!(grid.next_with(Cell::next, Neighbourhood::HEXAGONAL, Boundary::Dead), grid);
+            assert_eq!(grid.next_ltl(&LtlRule::new(Neighbourhood::MOORE, 3..=3, 2..=3).unwrap()), grid);
+            assert_eq!(Grid::from(&BitGrid::from(&grid).next()), grid);
+            assert_eq!(simulation.grid(), &grid);
+            assert_eq!((grid.to_string(), grid.render_halfblocks(), grid.render_braille()), (String::new(), String::new(), String::new()));
+            assert_eq!(grid.translate(1, 1), grid);
+            assert_eq!(grid.extract(0, 0, 1, 1), Err(LifeError::ZeroDimension { width, height }));
+
+            let mut inserted = grid.clone();
+            inserted.insert(&Grid::new(2, 2).unwrap().invert(), 1, 1, InsertMode::Replace, Overflow::Wrap);
+            inserted.insert(&Grid::new(2, 2).unwrap().invert(), 0, 0, InsertMode::Or, Overflow::Clip);
+            assert_eq!(inserted, grid);
+        }
+    }
+
+    #[test]
+    fn random_with_density_should_populate_given_fraction_of_cells() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        for &density in &[0.05, 0.3, 0.9] {
+            // when
+            let grid = Grid::random_with_density(StdRng::seed_from_u64(42), 200, 100, density);
+
+            // then
+            let fraction = grid.population() as f64 / 20000.0;
+            assert!((fraction - density).abs() < 0.02, "density {} produced live fraction {}", density, fraction);
+        }
+    }
+
+    #[test]
+    fn random_with_density_should_populate_none_or_all_cells_at_limits() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        assert_eq!(Grid::random_with_density(StdRng::seed_from_u64(1), 20, 10, 0.0).population(), 0);
+        assert_eq!(Grid::random_with_density(StdRng::seed_from_u64(1), 20, 10, 1.0).population(), 200);
+    }
+
+    #[test]
+    #[should_panic]
+    fn random_with_density_should_panic_given_density_out_of_range() {
+        Grid::random_with_density(StepRng::new(0, 1), 4, 4, 1.5);
+    }
+
+    #[test]
+    fn random_seeded_should_generate_same_grid_given_same_seed() {
+        // when
+        let grid = Grid::random_seeded(1234, 8, 4);
+
+        // then
+        assert_eq!(grid, Grid::random_seeded(1234, 8, 4));
+        assert_ne!(grid, Grid::random_seeded(1235, 8, 4));
+        assert_eq!(format!("{}", grid), "  OO  OO\nO OO    \n  O OOOO\nO   OOOO");
+    }
+
+    #[test]
+    fn random_seeded_with_density_should_populate_given_fraction_of_cells() {
+        // when
+        let grid = Grid::random_seeded_with_density(7, 200, 100, 0.25);
+
+        // then
+        let fraction = grid.population() as f64 / 20000.0;
+        assert!((fraction - 0.25).abs() < 0.02, "live fraction {}", fraction);
+    }
+
+    #[test]
+    fn split_mix_64_should_generate_reference_sequence() {
+        // given
+        let mut rng = SplitMix64::new(1234567);
+
+        // then
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+        assert_eq!(rng.next_u64(), 9817491932198370423);
+    }
+
+    #[test]
+    fn new_should_generate_grid_of_dead_cells() {
+        // when
+        let grid = Grid::new(5, 4).unwrap();
+
+        // then
+        assert_eq!(grid.width(),  5);
+        assert_eq!(grid.height(), 4);
+        assert_eq!(grid.rows().any(|row| row.len() != 5), false);
+        assert_eq!(grid.cells.iter().any(Cell::is_alive), false);
+    }
+
+    #[test]
+    fn new_should_generate_1_x_1_grid() {
+        // when
+        let grid = Grid::new(1, 1).unwrap();
+
+        // then
+        assert_eq!(grid.width(),  1);
+        assert_eq!(grid.height(), 1);
+        assert_eq!(rows_of(&grid), vec!(vec!(CELL_DEAD)));
+    }
+
+    #[test]
+    fn new_should_give_error_given_dimensions_overflowing() {
+        assert_eq!(Grid::new(usize::MAX, 2), Err(LifeError::TooLarge { width: usize::MAX, height: 2 }));
+        assert_eq!(Grid::new(1 << (usize::BITS / 2), 1 << (usize::BITS / 2)).unwrap_err().to_string(),
+            format!("grid dimensions {0}x{0} hold too many cells", 1usize << (usize::BITS / 2)));
+    }
+
+    #[test]
+    fn new_should_give_error_given_cells_too_large_to_allocate() {
+        // given
+        // Cells of 2^32x2^31 on 64-bit targets take more than isize::MAX bytes
+        let width:  usize = 1 << (usize::BITS / 2);
+        let height: usize = 1 << (usize::BITS / 2 - 1);
+
+        // when
+        let result = Grid::new(width, height);
+
+        // then
+        assert_eq!(result, Err(LifeError::TooLarge { width, height }));
+    }
+
+    #[test]
+    fn new_should_generate_large_grid() {
+        // when
+        let grid = Grid::new(3840, 2160).unwrap();
+
+        // then
+        assert_eq!(grid.width(),  3840);
+        assert_eq!(grid.height(), 2160);
+        assert_eq!(grid.get(3839, 2159), Some(&CELL_DEAD));
+    }
+
+    #[test]
+    fn new_should_return_error_given_zero_dimension() {
+        assert_eq!(Grid::new(0, 4).err(), Some(LifeError::ZeroDimension { width: 0, height: 4 }));
+        assert_eq!(Grid::new(5, 0).err(), Some(LifeError::ZeroDimension { width: 5, height: 0 }));
+        assert_eq!(Grid::new(0, 0).err(), Some(LifeError::ZeroDimension { width: 0, height: 0 }));
+    }
+
+    #[test]
+    fn index_should_return_cell_at_x_y() {
+        // given
+        let grid = Grid::from_text("O..\n..O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid[(0, 0)], CELL_ALIVE);
+        assert_eq!(grid[(2, 1)], CELL_ALIVE);
+        assert_eq!(grid[(1, 0)], CELL_DEAD);
+        assert_eq!(grid[(0, 1)], CELL_DEAD);
+    }
+
+    #[test]
+    fn index_mut_should_update_cell_at_x_y() {
+        // given
+        let mut grid = Grid::new(3, 2).unwrap();
+
+        // when
+        grid[(2, 0)] = CELL_ALIVE;
+
+        // then
+        assert_eq!(grid.get(2, 0), Some(&CELL_ALIVE));
+        assert_eq!(grid.population(), 1);
+    }
+
+    #[test]
+    fn index_should_return_row_given_y() {
+        // given
+        let grid = Grid::from_text("O..\n..O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid[1], [CELL_DEAD, CELL_DEAD, CELL_ALIVE]);
+        assert_eq!(grid[1][2], grid[(2, 1)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_should_panic_given_x_out_of_bounds() {
+        let grid = Grid::new(3, 2).unwrap();
+        let _ = &grid[(3, 0)];
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_should_panic_given_y_out_of_bounds() {
+        let grid = Grid::new(3, 2).unwrap();
+        let _ = &grid[(0, 2)];
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_mut_should_panic_given_out_of_bounds() {
+        let mut grid = Grid::new(3, 2).unwrap();
+        grid[(2, 2)] = CELL_ALIVE;
+    }
+
+    #[test]
+    fn toggle_should_flip_cell_state() {
+        // given
+        let mut grid = Grid::new(2, 2).unwrap();
+
+        // when
+        grid.toggle(1, 0).unwrap();
+
+        // then
+        assert_eq!(grid.get(1, 0), Some(&CELL_ALIVE));
+        grid.toggle(1, 0).unwrap();
+        assert_eq!(grid.get(1, 0), Some(&CELL_DEAD));
+    }
+
+    #[test]
+    fn toggle_should_return_error_when_out_of_bounds() {
+        // given
+        let mut grid = Grid::new(2, 2).unwrap();
+
+        // then
+        assert_eq!(grid.toggle(0, 2), Err(LifeError::OutOfBounds { x: 0, y: 2 }));
+        assert_eq!(grid.is_empty(), true);
+    }
+
+    #[test]
+    fn fill_rect_should_set_cells_in_rectangle() {
+        // given
+        let mut grid = Grid::from_text("OOOOO\nOOOOO\nOOOOO\nOOOOO", 'O').unwrap();
+
+        // when
+        grid.fill_rect(1, 1, 3, 2, false).unwrap();
+
+        // then
+        assert_eq!(format!("{}", grid), "OOOOO\nO   O\nO   O\nOOOOO");
+    }
+
+    #[test]
+    fn fill_rect_should_wrap_rectangle_around_edges() {
+        // given
+        let mut grid = Grid::new(4, 3).unwrap();
+
+        // when
+        grid.fill_rect(3, 2, 2, 2, true).unwrap();
+
+        // then
+        assert_eq!(format!("{}", grid), "O  O\n    \nO  O");
+    }
+
+    #[test]
+    fn fill_rect_should_fill_whole_grid_given_oversized_rectangle() {
+        // given
+        let mut grid = Grid::new(4, 3).unwrap();
+
+        // when
+        grid.fill_rect(2, 1, 100, 100, true).unwrap();
+
+        // then
+        assert_eq!(grid.population(), 12);
+    }
+
+    #[test]
+    fn fill_rect_should_return_error_given_origin_out_of_bounds() {
+        // given
+        let mut grid = Grid::new(4, 3).unwrap();
+
+        // then
+        assert_eq!(grid.fill_rect(4, 0, 1, 1, true), Err(LifeError::OutOfBounds { x: 4, y: 0 }));
+        assert_eq!(grid.is_empty(), true);
+    }
+
+    #[test]
+    fn insert_should_replace_overlapping_cells_given_replace_mode() {
+        // given
+        let mut grid = Grid::from_text("OOOO\nOOOO\nOOOO", 'O').unwrap();
+        let other = Grid::from_text(".O\nO.", 'O').unwrap();
+
+        // when
+        grid.insert(&other, 1, 1, InsertMode::Replace, Overflow::Clip);
+
+        // then
+        assert_eq!(format!("{}", grid), "OOOO\nO OO\nOO O");
+    }
+
+    #[test]
+    fn insert_should_combine_overlapping_cells_given_or_mode() {
+        // given
+        let mut grid = Grid::from_text("O...\n....\n...O", 'O').unwrap();
+        let other = Grid::from_text(".O\nO.", 'O').unwrap();
+
+        // when
+        grid.insert(&other, 0, 0, InsertMode::Or, Overflow::Clip);
+
+        // then
+        assert_eq!(format!("{}", grid), "OO  \nO   \n   O");
+    }
+
+    #[test]
+    fn insert_should_wrap_cells_across_boundary_given_wrap_overflow() {
+        // given
+        let mut grid = Grid::new(4, 3).unwrap();
+        let other = Grid::from_text("OO\nO.", 'O').unwrap();
+
+        // when
+        grid.insert(&other, 3, 2, InsertMode::Or, Overflow::Wrap);
+
+        // then
+        assert_eq!(format!("{}", grid), "   O\n    \nO  O");
+    }
+
+    #[test]
+    fn insert_should_discard_cells_across_boundary_given_clip_overflow() {
+        // given
+        let mut grid = Grid::new(4, 3).unwrap();
+        let other = Grid::from_text("OO\nO.", 'O').unwrap();
+
+        // when
+        grid.insert(&other, 3, 2, InsertMode::Or, Overflow::Clip);
+
+        // then
+        assert_eq!(format!("{}", grid), "    \n    \n   O");
+    }
+
+    #[test]
+    fn crop_should_return_bounding_box_of_live_cells() {
+        // given
+        let grid = Grid::from_text(".....\n..O..\n...O.\n.OOO.\n.....", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.crop(), Some(Grid::from_text(".O.\n..O\nOOO", 'O').unwrap()));
+        assert_eq!(Grid::new(3, 3).unwrap().crop(), None);
+    }
+
+    #[test]
+    fn resize_should_pad_and_clip_at_right_and_bottom_given_top_left() {
+        // given
+        let grid = Grid::from_text("O.O\n.O.", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.resize(4, 3, Anchor::TopLeft), Grid::from_text("O.O.\n.O..\n....", 'O').unwrap());
+        assert_eq!(grid.resize(2, 1, Anchor::TopLeft), Grid::from_text("O.", 'O').unwrap());
+        assert_eq!(grid.resize(3, 2, Anchor::TopLeft), grid);
+    }
+
+    #[test]
+    fn resize_should_keep_centred_pattern_centred_given_centre() {
+        // given
+        let blinker = Grid::from_text("...\nOOO\n...", 'O').unwrap();
+
+        // when
+        let grown = blinker.resize(7, 5, Anchor::Centre);
+
+        // then
+        assert_eq!(grown, Grid::from_text(".......\n.......\n..OOO..\n.......\n.......", 'O').unwrap());
+        assert_eq!(grown.resize(5, 1, Anchor::Centre), Grid::from_text(".OOO.", 'O').unwrap());
+    }
+
+    #[test]
+    fn resize_should_restore_centre_region_given_grow_then_shrink() {
+        for (seed, &(width, height)) in [(9, 6), (10, 7), (14, 11), (4, 3)].iter().enumerate() {
+            // given
+            let grid = Grid::random_seeded(seed as u64, 7, 5);
+
+            // when
+            let resized = grid.resize(width, height, Anchor::Centre).resize(7, 5, Anchor::Centre);
+
+            // then
+            let (keep_width, keep_height) = (width.min(7), height.min(5));
+            let (x, y) = ((7 - keep_width) / 2, (5 - keep_height) / 2);
+            assert_eq!(resized.extract(x, y, keep_width, keep_height), grid.extract(x, y, keep_width, keep_height));
+            assert_eq!(resized.population(), resized.extract(x, y, keep_width, keep_height).unwrap().population());
+            if width >= 7 && height >= 5 {
+                assert_eq!(resized, grid);
+            }
+        }
+    }
+
+    #[test]
+    fn resize_should_give_grid_with_no_cells_given_zero_dimension() {
+        let grid = Grid::random_seeded(1, 4, 4);
+        assert_eq!((grid.resize(0, 3, Anchor::Centre).width(), grid.resize(0, 3, Anchor::Centre).population()), (0, 0));
+        assert_eq!(grid.resize(4, 0, Anchor::TopLeft).height(), 0);
+        assert_eq!(Grid::random_seeded(1, 0, 0).resize(2, 2, Anchor::Centre), Grid::new(2, 2).unwrap());
+    }
+
+    #[test]
+    fn extract_should_return_copy_of_rectangle() {
+        // given
+        let grid = Grid::from_text("O...\n.OO.\n..O.", 'O').unwrap();
+
+        // when
+        let extracted = grid.extract(1, 1, 2, 2).unwrap();
+
+        // then
+        assert_eq!(format!("{}", extracted), "OO\n O");
+    }
+
+    #[test]
+    fn extract_should_wrap_rectangle_around_edges() {
+        // given
+        let grid = Grid::from_text("O..O\n....\n.O.O", 'O').unwrap();
+
+        // when
+        let extracted = grid.extract(3, 2, 2, 2).unwrap();
+
+        // then
+        assert_eq!(format!("{}", extracted), "O \nOO");
+    }
+
+    #[test]
+    fn extract_should_return_copy_of_whole_grid() {
+        // given
+        let grid = Grid::from_text("O..O\n....\n.O.O", 'O').unwrap();
+
+        // when
+        let extracted = grid.extract(0, 0, 4, 3).unwrap();
+
+        // then
+        assert_eq!(rows_of(&extracted), rows_of(&grid));
+    }
+
+    #[test]
+    fn extract_should_return_error_given_zero_dimension() {
+        // given
+        let grid = Grid::new(4, 3).unwrap();
+
+        // then
+        assert_eq!(grid.extract(0, 0, 0, 2).err(), Some(LifeError::ZeroDimension { width: 0, height: 2 }));
+        assert_eq!(grid.extract(0, 0, 2, 0).err(), Some(LifeError::ZeroDimension { width: 2, height: 0 }));
+    }
+
+    #[test]
+    fn translate_should_shift_cells_by_offsets() {
+        // given
+        let grid = Grid::from_text("O...\n.O..\n....", 'O').unwrap();
+
+        // when
+        let translated = grid.translate(1, 1);
+
+        // then
+        assert_eq!(translated, Grid::from_text("....\n.O..\n..O.", 'O').unwrap());
+    }
+
+    #[test]
+    fn translate_should_wrap_given_negative_offsets() {
+        // given
+        let grid = Grid::from_text("O...\n.O..\n....", 'O').unwrap();
+
+        // when
+        let translated = grid.translate(-1, -2);
+
+        // then
+        assert_eq!(translated, Grid::from_text("....\n...O\nO...", 'O').unwrap());
+    }
+
+    #[test]
+    fn translate_should_wrap_given_offsets_larger_than_grid() {
+        // given
+        let grid = Grid::from_text("O...\n.O..\n....", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.translate(4, 3), grid);
+        assert_eq!(grid.translate(-8, 6), grid);
+        assert_eq!(grid.translate(9, 7), grid.translate(1, 1));
+        assert_eq!(grid.translate(-5, -4), grid.translate(-1, -1));
+    }
+
+    #[test]
+    fn find_should_return_overlapping_matches_with_dead_cells_matching() {
+        // given
+        let grid = Grid::from_text("O.O.O...\n........\n.OOO....", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.find(&Grid::from_text("O.O", 'O').unwrap()), vec![(0, 0), (2, 0)]);
+        assert_eq!(grid.find(&Grid::from_text("...\n.OO.\n", 'O').unwrap()), vec![(1, 2), (2, 2)]);
+        assert_eq!(grid.find(&Grid::from_text("OOOO", 'O').unwrap()), vec![]);
+        assert_eq!(grid.find(&Grid::new(2, 2).unwrap()), vec![]);
+    }
+
+    #[test]
+    fn find_should_match_across_wrapping_edges() {
+        // given
+        let grid = Grid::from_coords(6, 6, vec![(5, 5), (0, 5), (5, 0), (0, 0)]).unwrap();
+
+        // then
+        assert_eq!(grid.find(&Grid::from_text("OO\nOO", 'O').unwrap()), vec![(5, 5)]);
+    }
+
+    #[test]
+    fn find_all_orientations_should_match_rotated_and_reflected_patterns() {
+        // given
+        let glider = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+        let mut grid = Grid::new(16, 8).unwrap();
+        grid.insert(&glider, 1, 1, InsertMode::Replace, Overflow::Clip);
+        grid.insert(&glider.rotate_cw().flip_vertical(), 6, 4, InsertMode::Replace, Overflow::Clip);
+        grid.insert(&Grid::from_text("OO\nOO", 'O').unwrap(), 12, 1, InsertMode::Replace, Overflow::Clip);
+
+        // then
+        assert_eq!(grid.find(&glider), vec![(1, 1)]);
+        assert_eq!(grid.find_all_orientations(&glider), vec![(1, 1), (6, 4)]);
+        assert_eq!(grid.find_all_orientations(&Grid::from_text("OO\nOO", 'O').unwrap()), vec![(12, 1)]);
+    }
+
+    #[test]
+    fn rotate_cw_should_rotate_and_swap_dimensions() {
+        // given
+        let grid = Grid::from_text("OO.\n..O", 'O').unwrap();
+
+        // when
+        let rotated = grid.rotate_cw();
+
+        // then
+        assert_eq!(rotated, Grid::from_text(".O\n.O\nO.", 'O').unwrap());
+    }
+
+    #[test]
+    fn rotate_ccw_should_rotate_and_swap_dimensions() {
+        // given
+        let grid = Grid::from_text("OO.\n..O", 'O').unwrap();
+
+        // when
+        let rotated = grid.rotate_ccw();
+
+        // then
+        assert_eq!(rotated, Grid::from_text(".O\nO.\nO.", 'O').unwrap());
+    }
+
+    #[test]
+    fn flip_horizontal_should_mirror_left_to_right() {
+        // given
+        let grid = Grid::from_text("OO.\n..O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.flip_horizontal(), Grid::from_text(".OO\nO..", 'O').unwrap());
+    }
+
+    #[test]
+    fn flip_vertical_should_mirror_top_to_bottom() {
+        // given
+        let grid = Grid::from_text("OO.\n..O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.flip_vertical(), Grid::from_text("..O\nOO.", 'O').unwrap());
+    }
+
+    #[test]
+    fn transforms_should_return_original_grid_when_repeated() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        for seed in 0..8 {
+            // given
+            let grid = Grid::random(StdRng::seed_from_u64(seed), 7 + seed as usize, 5);
+
+            // then
+            assert_eq!(grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw(), grid);
+            assert_eq!(grid.rotate_ccw().rotate_ccw().rotate_ccw().rotate_ccw(), grid);
+            assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+            assert_eq!(grid.flip_horizontal().flip_horizontal(), grid);
+            assert_eq!(grid.flip_vertical().flip_vertical(), grid);
+            assert_eq!(grid.rotate_cw().rotate_cw(), grid.flip_horizontal().flip_vertical());
+        }
+    }
+
+    #[test]
+    fn invert_should_flip_cell_states() {
+        // given
+        let grid = Grid::from_text("O.\n.O\nOO", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.invert(), Grid::from_text(".O\nO.\n..", 'O').unwrap());
+        assert_eq!(grid.invert().invert(), grid);
+    }
+
+    #[test]
+    fn set_operations_should_combine_cell_states() {
+        // given
+        let a = Grid::from_text("OO..", 'O').unwrap();
+        let b = Grid::from_text("O.O.", 'O').unwrap();
+
+        // then
+        assert_eq!(a.union(&b).unwrap(),                Grid::from_text("OOO.", 'O').unwrap());
+        assert_eq!(a.intersect(&b).unwrap(),            Grid::from_text("O...", 'O').unwrap());
+        assert_eq!(a.difference(&b).unwrap(),           Grid::from_text(".O..", 'O').unwrap());
+        assert_eq!(a.symmetric_difference(&b).unwrap(), Grid::from_text(".OO.", 'O').unwrap());
+    }
+
+    #[test]
+    fn set_operators_should_match_set_operations() {
+        // given
+        let a = Grid::random_seeded(1, 12, 9);
+        let b = Grid::random_seeded(2, 12, 9);
+
+        // then
+        assert_eq!((&a | &b), a.union(&b));
+        assert_eq!((&a & &b), a.intersect(&b));
+        assert_eq!((&a ^ &b), a.symmetric_difference(&b));
+    }
+
+    #[test]
+    fn set_operations_should_return_error_given_dimension_mismatch() {
+        // given
+        let a = Grid::new(4, 3).unwrap();
+        let b = Grid::new(3, 4).unwrap();
+        let error = Some(LifeError::DimensionMismatch { expected: (4, 3), found: (3, 4) });
+
+        // then
+        assert_eq!(a.union(&b).err(),                error);
+        assert_eq!(a.intersect(&b).err(),            error);
+        assert_eq!(a.difference(&b).err(),           error);
+        assert_eq!(a.symmetric_difference(&b).err(), error);
+        assert_eq!((&a ^ &b).err(),                  error);
+    }
+
+    #[test]
+    fn diff_should_be_empty_given_same_grid() {
+        // given
+        let grid = Grid::random_seeded(3, 20, 10);
+
+        // then
+        assert_eq!(grid.diff(&grid).unwrap().is_empty(), true);
+    }
+
+    #[test]
+    fn diff_should_list_cells_changed_by_next() {
+        // given
+        let grid = Grid::from_text(".....\n..O..\n..O..\n..O..\n.....", 'O').unwrap();
+
+        // when
+        let diff = grid.diff(&grid.next(Cell::next)).unwrap();
+
+        // then
+        assert_eq!(diff.births, vec!((1, 2), (3, 2)));
+        assert_eq!(diff.deaths, vec!((2, 1), (2, 3)));
+    }
+
+    #[test]
+    fn diff_should_list_changes_in_row_major_order() {
+        // given
+        let grid = Grid::random_seeded(4, 64, 48);
+        let next = grid.next(Cell::next);
+
+        // when
+        let diff = grid.diff(&next).unwrap();
+
+        // then
+        let changed: Vec<(usize, usize)> = grid.symmetric_difference(&next).unwrap().live_cells().collect();
+        let mut listed = diff.births.iter().chain(&diff.deaths).map(|&(x, y)| (y, x)).collect::<Vec<_>>();
+        listed.sort();
+        assert_eq!(listed.into_iter().map(|(y, x)| (x, y)).collect::<Vec<_>>(), changed);
+        assert_eq!(diff.births.iter().all(|&(x, y)| next.get(x, y) == Some(&CELL_ALIVE)), true);
+        assert_eq!(diff.deaths.iter().all(|&(x, y)| next.get(x, y) == Some(&CELL_DEAD)), true);
+    }
+
+    #[test]
+    fn diff_should_return_error_given_dimension_mismatch() {
+        assert_eq!(Grid::new(2, 2).unwrap().diff(&Grid::new(2, 3).unwrap()).err(),
+            Some(LifeError::DimensionMismatch { expected: (2, 2), found: (2, 3) }));
+    }
+
+    #[test]
+    fn next_should_return_next_grid_given_cell_inversion_function() {
+        // given
+        fn cell_function(cell: &Cell, _neighbours: u8) -> Cell  { Cell::new(!cell.alive) }
+        let grid = grid_of(vec!(vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD),
+                                vec!(CELL_DEAD, CELL_ALIVE, CELL_DEAD),
+                                vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD)));
+
+        // when
+        let next_grid = grid.next(cell_function); 
+
+        // then
+        assert_eq!(next_grid.height(),          grid.height());
+        assert_eq!(next_grid.width(),           grid.width());
+        assert_eq!(rows_of(&next_grid),         vec!(vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE),
+                                                     vec!(CELL_ALIVE, CELL_DEAD,   CELL_ALIVE),
+                                                     vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE)));
+    }
+
+    #[test]
+    fn next_should_return_next_grid_given_cell_neighbour_function() {
+        // given
+        fn cell_function(_cell: &Cell, neighbours: u8) -> Cell  { Cell::new(neighbours == 8) }
+        let grid = grid_of(vec!(vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE),
+                                vec!(CELL_ALIVE, CELL_DEAD,   CELL_ALIVE),
+                                vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE)));
+
+        // when
+        let next_grid = grid.next(cell_function); 
+
+        // then
+        assert_eq!(next_grid.height(),          grid.height());
+        assert_eq!(next_grid.width(),           grid.width());
+        assert_eq!(rows_of(&next_grid),         vec!(vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD),
+                                                     vec!(CELL_DEAD, CELL_ALIVE, CELL_DEAD),
+                                                     vec!(CELL_DEAD, CELL_DEAD,  CELL_DEAD)));
+    }
+
+    #[test]
+    fn step_into_should_write_next_state_into_target() {
+        // given
+        let grid = Grid::random_seeded(5, 17, 11);
+        let mut target = Grid::random_seeded(6, 17, 11);
+
+        // when
+        grid.step_into(&mut target, Cell::next).unwrap();
+
+        // then
+        assert_eq!(target, grid.next(Cell::next));
+    }
+
+    #[test]
+    fn step_into_should_return_error_given_dimension_mismatch() {
+        // given
+        let grid = Grid::new(4, 3).unwrap();
+        let mut target = Grid::new(3, 4).unwrap();
+
+        // then
+        assert_eq!(grid.step_into(&mut target, Cell::next),
+            Err(LifeError::DimensionMismatch { expected: (4, 3), found: (3, 4) }));
+    }
+
+    #[test]
+    fn step_n_should_return_blinker_after_2_generations() {
+        // given
+        let grid = Grid::from_text(".....\n..O..\n..O..\n..O..\n.....", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.step_n(2, Cell::next), grid);
+        assert_eq!(grid.step_n(1, Cell::next), grid.next(Cell::next));
+        assert_eq!(grid.step_n(0, Cell::next), grid);
+    }
+
+    #[test]
+    fn step_until_should_stop_at_generation_pattern_dies() {
+        // given
+        let grid = Grid::from_text("......\n.O....\n..O...\n...O..\n......\n......", 'O').unwrap();
+
+        // when
+        let (dead, generation) = grid.step_until(Cell::next, |grid, _| grid.population() == 0, 100);
+
+        // then
+        assert_eq!(generation, 2);
+        assert_eq!(dead, Grid::new(6, 6).unwrap());
+        assert_eq!(grid.step_until(Cell::next, |grid, _| grid.population() == 0, 1), (grid.next(Cell::next), 1));
+        assert_eq!(grid.step_until(Cell::next, |_, generation| generation == 5, 100).1, 5);
+    }
+
+    #[test]
+    fn eq_should_compare_independently_constructed_grids() {
+        // given
+        let mut grid = Grid::new(3, 3).unwrap();
+        grid.set(1, 0, true).unwrap();
+        let parsed = Grid::from_text(".O.\n...\n...", 'O').unwrap();
+
+        // then
+        assert_eq!(grid, parsed);
+        assert_eq!(grid.clone(), parsed);
+        assert_ne!(grid, Grid::new(3, 3).unwrap());
+        assert_ne!(Grid::new(3, 3).unwrap(), Grid::new(3, 4).unwrap());
+    }
+
+    #[test]
+    fn hash_should_be_consistent_with_eq() {
+        use std::collections::HashSet;
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(grid: &Grid) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            grid.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        // given
+        let text = ".O...........................................................................O\nOOO";
+        let grid = Grid::from_text(text, 'O').unwrap();
+        let parsed = Grid::from_str(text).unwrap();
+
+        // then
+        assert_eq!(hash(&grid), hash(&parsed));
+        assert_ne!(hash(&Grid::new(2, 8).unwrap()), hash(&Grid::new(4, 4).unwrap()));
+
+        let mut set = HashSet::new();
+        set.insert(grid);
+        assert_eq!(set.contains(&parsed), true);
+        assert_eq!(set.contains(&Grid::new(3, 3).unwrap()), false);
+    }
+
+    #[test]
+    fn next_with_rule_should_copy_replicator_given_highlife() {
+        // given
+        let replicator = Grid::from_text("..OOO\n.O..O\nO...O\nO..O.\nOOO..", 'O').unwrap();
+        let mut grid = Grid::new(16, 16).unwrap();
+        grid.insert(&replicator, 5, 5, InsertMode::Replace, Overflow::Clip);
+
+        let mut copies = Grid::new(16, 16).unwrap();
+        copies.insert(&replicator, 3, 3, InsertMode::Or, Overflow::Clip);
+        copies.insert(&replicator, 7, 7, InsertMode::Or, Overflow::Clip);
+
+        // when
+        for _ in 0..12 {
+            grid = grid.next_with_rule(&Rule::HIGHLIFE);
+        }
+
+        // then
+        assert_eq!(grid, copies);
+    }
+
+    #[test]
+    fn next_with_should_count_single_cell_in_each_neighbourhood_across_wrap_seam() {
+        // given
+        let grid = Grid::from_coords(5, 5, vec![(0, 0)]).unwrap();
+        let seen = |neighbourhood| grid.next_with(|_, neighbours| Cell::new(neighbours == 1), neighbourhood, Boundary::Torus)
+            .live_cells().collect::<Vec<(usize, usize)>>();
+
+        // then
+        assert_eq!(seen(Neighbourhood::MOORE),       vec![(1, 0), (4, 0), (0, 1), (1, 1), (4, 1), (0, 4), (1, 4), (4, 4)]);
+        assert_eq!(seen(Neighbourhood::VON_NEUMANN), vec![(1, 0), (4, 0), (0, 1), (0, 4)]);
+        assert_eq!(seen(Neighbourhood::HEXAGONAL),   vec![(1, 0), (4, 0), (0, 1), (1, 1), (0, 4), (4, 4)]);
+    }
+
+    #[test]
+    fn neighbour_counts_should_count_around_lone_cell_across_wrap_seam() {
+        // given
+        let grid = Grid::from_coords(4, 3, vec![(0, 0)]).unwrap();
+
+        // then
+        assert_eq!(grid.neighbour_counts(), vec![
+            vec![0, 1, 0, 1],
+            vec![1, 1, 0, 1],
+            vec![1, 1, 0, 1]
+        ]);
+        assert_eq!(grid.neighbour_counts_with(Neighbourhood::MOORE, Boundary::Dead), vec![
+            vec![0, 1, 0, 0],
+            vec![1, 1, 0, 0],
+            vec![0, 0, 0, 0]
+        ]);
+    }
+
+    #[test]
+    fn neighbour_counts_should_count_around_block() {
+        // given
+        let grid = Grid::from_text("....\n.OO.\n.OO.\n....", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.neighbour_counts(), vec![
+            vec![1, 2, 2, 1],
+            vec![2, 3, 3, 2],
+            vec![2, 3, 3, 2],
+            vec![1, 2, 2, 1]
+        ]);
+    }
+
+    #[test]
+    fn neighbour_counts_should_count_cells_once_per_position_wrapping_onto_them_given_narrow_grids() {
+        let counts = |width, height, coords| Grid::from_coords(width, height, coords).unwrap().neighbour_counts();
+
+        // 1x1
+        assert_eq!(counts(1, 1, vec![]), vec![vec![0]]);
+        assert_eq!(counts(1, 1, vec![(0, 0)]), vec![vec![8]]);
+        // 1xN and Nx1
+        assert_eq!(counts(1, 3, vec![(0, 0)]), vec![vec![2], vec![3], vec![3]]);
+        assert_eq!(counts(1, 4, vec![(0, 0), (0, 1)]), vec![vec![5], vec![5], vec![3], vec![3]]);
+        assert_eq!(counts(3, 1, vec![(0, 0)]), vec![vec![2, 3, 3]]);
+        assert_eq!(counts(4, 1, vec![(0, 0), (1, 0)]), vec![vec![5, 5, 3, 3]]);
+        // 2x2 and 2xN
+        assert_eq!(counts(2, 2, vec![(0, 0)]), vec![vec![0, 2], vec![2, 4]]);
+        assert_eq!(counts(2, 2, vec![(0, 0), (1, 0), (0, 1), (1, 1)]), vec![vec![8, 8], vec![8, 8]]);
+        assert_eq!(counts(2, 3, vec![(0, 0)]), vec![vec![0, 2], vec![1, 2], vec![1, 2]]);
+        assert_eq!(counts(3, 2, vec![(0, 0)]), vec![vec![0, 1, 1], vec![2, 2, 2]]);
+    }
+
+    #[test]
+    fn next_should_match_across_engines_given_narrow_grids() {
+        let wrap = Boundary::PerAxis { x: EdgeMode::Wrap, y: EdgeMode::Wrap };
+        for (width, height) in (1..=3).flat_map(|width| (1..=4).map(move |height| (width, height))) {
+            for seed in 0..8 {
+                // given
+                let grid = Grid::random_seeded(seed, width, height);
+                let mut simulation = Simulation::new(grid.clone(), Rule::CONWAY).with_incremental(true);
+
+                // when
+                let next = grid.next(Cell::next);
+                simulation.step_n(2);
+
+                // then
+                assert_eq!(grid.next_scalar(Cell::next), next);
+                assert_eq!(grid.next_with(Cell::next, Neighbourhood::MOORE, wrap), next);
+                assert_eq!(Grid::from(&BitGrid::from(&grid).next()), next);
+                assert_eq!(simulation.grid(), &next.next(Cell::next));
+            }
+        }
+    }
+
+    #[test]
+    fn neighbour_counts_with_should_give_counts_next_with_steps_with() {
+        let boundaries = [Boundary::Torus, Boundary::Dead, Boundary::Mirror, Boundary::Klein, Boundary::CrossSurface];
+        let neighbourhoods = [Neighbourhood::MOORE, Neighbourhood::VON_NEUMANN, Neighbourhood::HEXAGONAL];
+        for (seed, (&boundary, &neighbourhood)) in boundaries.iter().flat_map(|b| neighbourhoods.iter().map(move |n| (b, n))).enumerate() {
+            // given
+            let grid = Grid::random_seeded(seed as u64, 9, 7);
+
+            // when
+            let counts = grid.neighbour_counts_with(neighbourhood, boundary);
+
+            // then
+            for count in 0..=8 {
+                let next = grid.next_with(|_, neighbours| Cell::new(neighbours == count), neighbourhood, boundary);
+                assert_eq!(next.live_cells().collect::<Vec<(usize, usize)>>(),
+                    (0..7).flat_map(|y| (0..9).map(move |x| (x, y))).filter(|&(x, y)| counts[y][x] == count).collect::<Vec<(usize, usize)>>());
+            }
+        }
+    }
+
+    #[test]
+    fn next_with_should_count_no_cells_across_edges_given_dead_boundary_and_hexagonal() {
+        // given
+        let grid = Grid::from_coords(5, 5, vec![(0, 0)]).unwrap();
+
+        // when
+        let seen = grid.next_with(|_, neighbours| Cell::new(neighbours == 1), Neighbourhood::HEXAGONAL, Boundary::Dead);
+
+        // then
+        assert_eq!(seen.live_cells().collect::<Vec<(usize, usize)>>(), vec![(1, 0), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn next_with_rule_should_count_von_neumann_neighbours_given_v_suffix() {
+        // given
+        let rule = Rule::parse("B1/S").unwrap().with_neighbourhood(Neighbourhood::VON_NEUMANN).unwrap();
+        let grid = Grid::from_coords(5, 5, vec![(2, 2)]).unwrap();
+
+        // then
+        assert_eq!(grid.next_with_rule(&rule).to_string(), "     \n  O  \n O O \n  O  \n     ");
+    }
+
+    #[test]
+    fn next_stochastic_should_match_rule_given_probability_1() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        // given
+        let rule = StochasticRule::new(Rule::HIGHLIFE, 1.0, 1.0).unwrap();
+        let grid = Grid::random_seeded(8, 30, 20);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // then
+        assert_eq!(grid.next_stochastic(&rule, &mut rng), grid.next_with_rule(&Rule::HIGHLIFE));
+    }
+
+    #[test]
+    fn next_stochastic_should_kill_all_cells_given_probability_0() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        // given
+        let rule = StochasticRule::new(Rule::CONWAY, 0.0, 0.0).unwrap();
+        let grid = Grid::random_seeded(8, 30, 20);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // then
+        assert_eq!(grid.next_stochastic(&rule, &mut rng).is_empty(), true);
+    }
+
+    #[test]
+    fn next_stochastic_should_generate_same_grid_given_same_seed() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        // given
+        let rule = StochasticRule::new(Rule::CONWAY, 0.5, 0.75).unwrap();
+        let grid = Grid::random_seeded(9, 40, 30);
+        let deterministic = grid.next_with_rule(&Rule::CONWAY);
+
+        // when
+        let first  = grid.next_stochastic(&rule, &mut StdRng::seed_from_u64(2));
+        let second = grid.next_stochastic(&rule, &mut StdRng::seed_from_u64(2));
+        let other  = grid.next_stochastic(&rule, &mut StdRng::seed_from_u64(3));
+
+        // then
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_ne!(first, deterministic);
+        assert_eq!(first.difference(&deterministic).unwrap().is_empty(), true);
+    }
+
+    #[test]
+    fn next_weighted_should_match_next_given_unit_weights() {
+        // given
+        let counting_centre = WeightedRule::new([1; 9], vec![3], vec![3, 4]);
+        let ignoring_centre = WeightedRule::new([1, 1, 1, 1, 0, 1, 1, 1, 1], vec![3], vec![2, 3]);
+        let grid = Grid::random_seeded(3, 21, 16);
+
+        // then
+        assert_eq!(grid.next_weighted(&counting_centre), grid.next(Cell::next));
+        assert_eq!(grid.next_weighted(&ignoring_centre), grid.next(Cell::next));
+    }
+
+    #[test]
+    fn next_weighted_should_apply_asymmetric_weights() {
+        // given
+        let rule = WeightedRule::new([0, 2, 0, 1, 0, -1, 0, 0, 0], vec![2], vec![1]);
+        let grid = Grid::from_coords(5, 5, vec![(1, 1), (2, 1)]).unwrap();
+
+        // when
+        let next = grid.next_weighted(&rule);
+
+        // then
+        assert_eq!(next.to_string(), "     \n  O  \n OO  \n     \n     ");
+    }
+
+    #[test]
+    fn next_ltl_should_match_next_given_radius_1_conway() {
+        // given
+        let rule = LtlRule::new(Neighbourhood::new(1, Shape::Moore), 3..=3, 2..=3).unwrap();
+        let grid = Grid::random_seeded(7, 20, 15);
+
+        // then
+        assert_eq!(grid.next_ltl(&rule), grid.next(Cell::next));
+    }
+
+    #[test]
+    fn next_ltl_should_kill_isolated_cells_given_bugs() {
+        // given
+        let grid = Grid::from_coords(20, 20, vec![(3, 3), (15, 12)]).unwrap();
+
+        // then
+        assert_eq!(grid.next_ltl(&LtlRule::BUGS).is_empty(), true);
+    }
+
+    #[test]
+    fn next_isotropic_should_match_next_given_totalistic_conway() {
+        // given
+        let rule = IsotropicRule::parse("B3/S23").unwrap();
+
+        for seed in 0..8 {
+            let grid = Grid::random_seeded(seed, 24, 17);
+
+            // then
+            assert_eq!(grid.next_isotropic(&rule), grid.next(Cell::next));
+        }
+    }
+
+    #[test]
+    fn next_isotropic_should_distinguish_configurations_with_same_count() {
+        // given
+        let tlife  = IsotropicRule::parse("B3/S2-i34q").unwrap();
+        let line   = Grid::from_text(".....\n.OOO.\n.....", 'O').unwrap();
+        let corner = Grid::from_text(".....\n.OO..\n..O..", 'O').unwrap();
+
+        // when
+        let line   = line.next_isotropic(&tlife);
+        let corner = corner.next_isotropic(&tlife);
+
+        // then
+        assert_eq!(line.get(2, 1).unwrap().is_alive(),   false);
+        assert_eq!(corner.get(2, 1).unwrap().is_alive(), true);
+    }
+
+    #[test]
+    fn neighbourhood_should_set_bits_in_row_major_order() {
+        // given
+        let grid = Grid::from_text("O..\n..O\n.O.", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.neighbourhood(1, 1), 0b1000_1010);
+        assert_eq!(grid.neighbourhood(0, 0), 0b0010_0100);
+    }
+
+    #[test]
+    fn next_with_rule_should_match_next_given_conway() {
+        // given
+        let grid = Grid::random_seeded(6, 32, 24);
+
+        // then
+        assert_eq!(grid.next_with_rule(&Rule::CONWAY), grid.next(Cell::next));
+    }
+
+    #[test]
+    fn neighbours_with_should_count_edge_and_corner_cells_for_all_boundaries() {
+        // given
+        let grid = Grid::from_text("OOOO\nOOOO\nOOOO\nOOOO", 'O').unwrap();
+        let moore = Neighbourhood::MOORE.offsets();
+
+        // then
+        for &(boundary, corner, edge, inner) in &[(Boundary::Torus, 8, 8, 8), (Boundary::Dead, 3, 5, 8), (Boundary::Mirror, 8, 8, 8)] {
+            assert_eq!(grid.neighbours_with(0, 0, &moore, boundary), corner, "{:?} corner", boundary);
+            assert_eq!(grid.neighbours_with(3, 3, &moore, boundary), corner, "{:?} corner", boundary);
+            assert_eq!(grid.neighbours_with(1, 0, &moore, boundary), edge,   "{:?} top edge", boundary);
+            assert_eq!(grid.neighbours_with(0, 2, &moore, boundary), edge,   "{:?} left edge", boundary);
+            assert_eq!(grid.neighbours_with(1, 1, &moore, boundary), inner,  "{:?} inner", boundary);
+        }
+    }
+
+    #[test]
+    fn neighbours_with_should_count_cells_beyond_edges_for_all_boundaries() {
+        // given
+        let grid = Grid::from_text("O...\n....\n....\n...O", 'O').unwrap();
+        let moore = Neighbourhood::MOORE.offsets();
+
+        // then
+        for &(boundary, corner, opposite, edge) in &[(Boundary::Torus, 1, 2, 1), (Boundary::Dead, 0, 0, 1), (Boundary::Mirror, 3, 0, 2)] {
+            assert_eq!(grid.neighbours_with(0, 0, &moore, boundary), corner,   "{:?} corner", boundary);
+            assert_eq!(grid.neighbours_with(3, 0, &moore, boundary), opposite, "{:?} opposite corner", boundary);
+            assert_eq!(grid.neighbours_with(1, 0, &moore, boundary), edge,     "{:?} edge", boundary);
+        }
+    }
+
+    #[test]
+    fn neighbours_with_should_match_neighbours_given_torus() {
+        // given
+        let grid = Grid::random_seeded(5, 9, 7);
+        let moore = Neighbourhood::MOORE.offsets();
+
+        // then
+        for y in 0..7 {
+            for x in 0..9 {
+                assert_eq!(grid.neighbours_with(x, y, &moore, Boundary::Torus), grid.neighbours(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn next_with_should_not_wrap_across_edges_given_dead_boundary() {
+        // given
+        let grid = Grid::from_text(".OOO.\n.....\n.....\n.....", 'O').unwrap();
+
+        // when
+        let dead  = grid.next_with(Cell::next, Neighbourhood::MOORE, Boundary::Dead);
+        let torus = grid.next_with(Cell::next, Neighbourhood::MOORE, Boundary::Torus);
+
+        // then
+        assert_eq!(dead,  Grid::from_text("..O..\n..O..\n.....\n.....", 'O').unwrap());
+        assert_eq!(torus, Grid::from_text("..O..\n..O..\n.....\n..O..", 'O').unwrap());
+        assert_eq!(torus, grid.next(Cell::next));
+    }
+
+    #[test]
+    fn next_with_should_wrap_gliders_only_across_wrapping_axes() {
+        let glider = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+        let modes = [(EdgeMode::Wrap, EdgeMode::Wrap), (EdgeMode::Wrap, EdgeMode::Dead),
+                     (EdgeMode::Dead, EdgeMode::Wrap), (EdgeMode::Dead, EdgeMode::Dead)];
+
+        for &(x_mode, y_mode) in &modes {
+            // Gliders heading off the right edge then off the bottom edge
+            for &(x, y, across_x, wraps) in &[(8, 3, true, x_mode == EdgeMode::Wrap), (3, 8, false, y_mode == EdgeMode::Wrap)] {
+                // given
+                let mut grid = Grid::new(12, 12).unwrap();
+                grid.insert(&glider, x, y, InsertMode::Replace, Overflow::Clip);
+                let start = grid.clone();
+
+                // when
+                for _ in 0..16 {
+                    grid = grid.next_with(Cell::next, Neighbourhood::MOORE, Boundary::PerAxis { x: x_mode, y: y_mode });
+                }
+
+                // then
+                let reappeared = grid.live_cells().any(|(cx, cy)| if across_x { cx < 4 } else { cy < 4 });
+                assert_eq!(reappeared, wraps, "{:?} {:?} glider at ({}, {})", x_mode, y_mode, x, y);
+                if wraps {
+                    assert_eq!(grid, start.translate(4, 4));
+                }
+                else {
+                    assert_eq!(grid.population(), 4, "glider should become a block at the edge");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_with_should_count_cell_across_twisted_edges() {
+        // Cells which count the live cell as a neighbour under each boundary
+        fn influenced(grid: &Grid, boundary: Boundary) -> Vec<(usize, usize)> {
+            let moore = Neighbourhood::MOORE.offsets();
+            (0..grid.height())
+                .flat_map(|y| (0..grid.width()).map(move |x| (x, y)))
+                .filter(|&(x, y)| grid.neighbours_with(x, y, &moore, boundary) > 0)
+                .collect()
+        }
+
+        // given
+        let right_edge = Grid::from_coords(5, 4, vec!((4, 1))).unwrap();
+        let top_edge   = Grid::from_coords(5, 4, vec!((1, 0))).unwrap();
+
+        // then
+        assert_eq!(influenced(&right_edge, Boundary::Klein),
+            vec!((3, 0), (4, 0), (0, 1), (3, 1), (0, 2), (3, 2), (4, 2), (0, 3)));
+        assert_eq!(influenced(&top_edge, Boundary::Klein),
+            vec!((0, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 3), (1, 3), (2, 3)));
+        assert_eq!(influenced(&top_edge, Boundary::CrossSurface),
+            vec!((0, 0), (2, 0), (0, 1), (1, 1), (2, 1), (2, 3), (3, 3), (4, 3)));
+    }
+
+    #[test]
+    fn fmt_should_format_grid_as_string() {
+        // given
+        let grid = grid_of(vec!(vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE),
+                                vec!(CELL_ALIVE, CELL_DEAD,   CELL_ALIVE),
+                                vec!(CELL_ALIVE, CELL_ALIVE,  CELL_ALIVE)));
+
+        // when
+        let formatted = format!("{}", grid); 
+
+        // then
+        assert_eq!(formatted, "OOO\nO O\nOOO");
+    }
+
+    #[test]
+    fn try_from_should_round_trip_bool_rows() {
+        // given
+        let rows = vec!(vec!(false, true, false), vec!(true, true, false));
+
+        // when
+        let grid = Grid::try_from(rows.clone()).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".O.\nOO.", 'O').unwrap());
+        assert_eq!(grid.to_bool_rows(), rows);
+    }
+
+    #[test]
+    fn try_from_should_return_error_given_ragged_rows() {
+        // given
+        let rows = vec!(vec!(false, true, false), vec!(true, true, false), vec!(true));
+
+        // then
+        assert_eq!(Grid::try_from(rows).err(), Some(LifeError::RaggedRows { row: 2, expected: 3, found: 1 }));
+    }
+
+    #[test]
+    fn try_from_should_return_error_given_empty_rows() {
+        assert_eq!(Grid::try_from(vec!()).err(),        Some(LifeError::ZeroDimension { width: 0, height: 0 }));
+        assert_eq!(Grid::try_from(vec!(vec!())).err(),  Some(LifeError::ZeroDimension { width: 0, height: 1 }));
+    }
+
+    #[test]
+    fn from_coords_should_set_listed_cells_alive() {
+        // when
+        let grid = Grid::from_coords(3, 3, vec!((1, 0), (2, 1), (0, 2), (1, 2), (2, 2))).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".O.\n..O\nOOO", 'O').unwrap());
+    }
+
+    #[test]
+    fn from_coords_should_accept_duplicate_coords() {
+        // when
+        let grid = Grid::from_coords(2, 2, vec!((1, 1), (1, 1), (1, 1))).unwrap();
+
+        // then
+        assert_eq!(grid.population(), 1);
+        assert_eq!(grid.get(1, 1), Some(&CELL_ALIVE));
+    }
+
+    #[test]
+    fn from_coords_should_return_error_given_out_of_range_coords() {
+        assert_eq!(Grid::from_coords(2, 2, vec!((0, 0), (2, 1))).err(), Some(LifeError::OutOfBounds { x: 2, y: 1 }));
+        assert_eq!(Grid::from_coords(0, 2, vec!()).err(), Some(LifeError::ZeroDimension { width: 0, height: 2 }));
+    }
+
+    #[test]
+    fn from_coords_clipped_should_ignore_out_of_range_coords() {
+        // when
+        let grid = Grid::from_coords_clipped(2, 2, vec!((0, 0), (2, 1), (1, 5))).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text("O.\n..", 'O').unwrap());
+    }
+
+    #[test]
+    fn live_cells_should_round_trip_with_from_coords() {
+        // given
+        let coords = vec!((1, 0), (2, 1), (0, 2), (1, 2), (2, 2));
+
+        // when
+        let grid = Grid::from_coords(4, 4, coords.clone()).unwrap();
+
+        // then
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), coords);
+    }
+
+    #[test]
+    fn population_should_return_number_of_live_cells() {
+        // given
+        let grid = grid_of(vec!(vec!(CELL_ALIVE, CELL_DEAD,  CELL_DEAD),
+                                vec!(CELL_DEAD,  CELL_ALIVE, CELL_ALIVE)));
+
+        // then
+        assert_eq!(grid.population(), 3);
+        assert_eq!(Grid::new(3, 2).unwrap().population(), 0);
+    }
+
+    #[test]
+    fn is_empty_should_return_true_only_when_no_cells_alive() {
+        // given
+        let mut grid = Grid::new(4, 4).unwrap();
+        assert_eq!(grid.is_empty(), true);
+
+        // when
+        grid.set(3, 3, true).unwrap();
+
+        // then
+        assert_eq!(grid.is_empty(), false);
+    }
+
+    #[test]
+    fn bounding_box_should_return_extents_of_live_cells() {
+        // given
+        let grid = Grid::from_text(".....\n..O..\n.O...\n...O.\n.....", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.bounding_box(), Some((1, 1, 3, 3)));
+    }
+
+    #[test]
+    fn bounding_box_should_return_single_cell_extents() {
+        // given
+        let grid = Grid::from_text("O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.bounding_box(), Some((0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn bounding_box_should_return_none_given_empty_grid() {
+        assert_eq!(Grid::new(3, 3).unwrap().bounding_box(), None);
+    }
+
+    #[test]
+    fn bounding_box_should_return_whole_grid_given_full_grid() {
+        // given
+        let grid = Grid::from_text("OOOO\nOOOO\nOOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.bounding_box(), Some((0, 0, 3, 2)));
+    }
+
+    #[test]
+    fn bounding_box_should_return_literal_extents_across_wrap_seam() {
+        // given
+        let grid = Grid::from_text("O...O\n.....\nO...O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.bounding_box(), Some((0, 0, 4, 2)));
+    }
+
+    #[test]
+    fn get_should_return_cells_at_corners() {
+        // given
+        let grid = grid_of(vec!(vec!(CELL_ALIVE, CELL_DEAD,  CELL_DEAD),
+                                vec!(CELL_DEAD,  CELL_DEAD,  CELL_ALIVE)));
+
+        // then
+        assert_eq!(grid.get(0, 0), Some(&CELL_ALIVE));
+        assert_eq!(grid.get(2, 0), Some(&CELL_DEAD));
+        assert_eq!(grid.get(0, 1), Some(&CELL_DEAD));
+        assert_eq!(grid.get(2, 1), Some(&CELL_ALIVE));
+    }
+
+    #[test]
+    fn get_should_return_none_when_out_of_bounds() {
+        // given
+        let grid = grid_of(vec!(vec!(CELL_ALIVE, CELL_DEAD,  CELL_DEAD),
+                                vec!(CELL_DEAD,  CELL_DEAD,  CELL_ALIVE)));
+
+        // then
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        assert_eq!(grid.get(usize::MAX, usize::MAX), None);
+    }
+
+    #[test]
+    fn set_should_update_cell_returned_by_get() {
+        // given
+        let mut grid = grid_of(vec!(vec!(CELL_DEAD, CELL_DEAD),
+                                    vec!(CELL_DEAD, CELL_DEAD)));
+
+        // when
+        let result = grid.set(1, 1, true);
+
+        // then
+        assert_eq!(result, Ok(()));
+        assert_eq!(grid.get(1, 1), Some(&CELL_ALIVE));
+        assert_eq!(grid.set(1, 1, false), Ok(()));
+        assert_eq!(grid.get(1, 1), Some(&CELL_DEAD));
+    }
+
+    #[test]
+    fn set_should_return_error_when_out_of_bounds() {
+        // given
+        let mut grid = grid_of(vec!(vec!(CELL_DEAD, CELL_DEAD),
+                                    vec!(CELL_DEAD, CELL_DEAD)));
+
+        // when
+        let result = grid.set(2, 0, true);
+
+        // then
+        assert_eq!(result, Err(LifeError::OutOfBounds { x: 2, y: 0 }));
+        assert_eq!(grid.cells.iter().any(Cell::is_alive), false);
+    }
+
+    #[test]
+    fn from_text_should_parse_grid_given_alive_char() {
+        // when
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(rows_of(&grid), vec!(vec!(CELL_DEAD,  CELL_ALIVE, CELL_DEAD),
+                                        vec!(CELL_DEAD,  CELL_DEAD,  CELL_ALIVE),
+                                        vec!(CELL_ALIVE, CELL_ALIVE, CELL_ALIVE)));
+    }
+
+    #[test]
+    fn from_text_should_pad_short_lines_with_dead_cells() {
+        // when
+        let grid = Grid::from_text("#\n.#.#\n\n##", '#').unwrap();
+
+        // then
+        assert_eq!(grid.width(),  4);
+        assert_eq!(grid.height(), 4);
+        assert_eq!(rows_of(&grid), vec!(vec!(CELL_ALIVE, CELL_DEAD,  CELL_DEAD, CELL_DEAD),
+                                        vec!(CELL_DEAD,  CELL_ALIVE, CELL_DEAD, CELL_ALIVE),
+                                        vec!(CELL_DEAD,  CELL_DEAD,  CELL_DEAD, CELL_DEAD),
+                                        vec!(CELL_ALIVE, CELL_ALIVE, CELL_DEAD, CELL_DEAD)));
+    }
+
+    #[test]
+    fn from_text_should_return_error_given_empty_input() {
+        assert_eq!(Grid::from_text("", 'O').err(),     Some(LifeError::EmptyPattern));
+        assert_eq!(Grid::from_text("\n\n", 'O').err(), Some(LifeError::EmptyPattern));
+    }
+
+    #[test]
+    fn from_str_should_accept_lf_and_crlf_line_endings() {
+        // when
+        let lf:   Grid = "OO\nO ".parse().unwrap();
+        let crlf: Grid = "OO\r\nO ".parse().unwrap();
+
+        // then
+        assert_eq!(rows_of(&lf),   vec!(vec!(CELL_ALIVE, CELL_ALIVE), vec!(CELL_ALIVE, CELL_DEAD)));
+        assert_eq!(rows_of(&crlf), rows_of(&lf));
+    }
+
+    #[test]
+    fn from_str_should_round_trip_formatted_glider_and_blinker() {
+        for text in &[" O   \n  O  \nOOO  \n     ", "     \n  O  \n  O  \n  O  \n     "] {
+            // when
+            let grid = Grid::from_str(text).unwrap();
+
+            // then
+            assert_eq!(&format!("{}", grid), text);
+        }
+    }
+
+/*****************************************************************************/
+
+    #[test]
+    fn constructors_should_return_cells_with_given_state() {
+        assert_eq!(Cell::new(true).is_alive(),  true);
+        assert_eq!(Cell::new(false).is_alive(), false);
+        assert_eq!(Cell::alive().is_alive(),    true);
+        assert_eq!(Cell::dead().is_alive(),     false);
+        assert_eq!(Cell::default(),             Cell::dead());
+    }
+
+    #[test]
+    fn is_alive_should_return_cell_state() {
+        assert_eq!(CELL_DEAD.is_alive(),  false);
+        assert_eq!(CELL_ALIVE.is_alive(), true);
+    }
+
+    #[test]
+    fn from_should_convert_between_cell_and_bool() {
+        assert_eq!(Cell::from(true),  CELL_ALIVE);
+        assert_eq!(Cell::from(false), CELL_DEAD);
+        assert_eq!(bool::from(CELL_ALIVE), true);
+        assert_eq!(bool::from(CELL_DEAD),  false);
+    }
 
     #[test]
     fn to_char_should_return_correct_chars() {
-        assert_eq!(Cell { alive: false }.to_char(), ' ');
-        assert_eq!(Cell { alive: true }.to_char(),  'O');
+        assert_eq!(Cell::new(false).to_char(), ' ');
+        assert_eq!(Cell::new(true).to_char(),  'O');
     }
 
     #[test]
     fn next_should_return_correct_next_state() {
         // cell stays alive if 2 or 3 neighbours
-        assert_eq!(Cell::next(&CELL_ALIVE, 0).alive, false);
-        assert_eq!(Cell::next(&CELL_ALIVE, 1).alive, false);
-        assert_eq!(Cell::next(&CELL_ALIVE, 2).alive, true);
-        assert_eq!(Cell::next(&CELL_ALIVE, 3).alive, true);
-        assert_eq!(Cell::next(&CELL_ALIVE, 4).alive, false);
-        assert_eq!(Cell::next(&CELL_ALIVE, 5).alive, false);
-        assert_eq!(Cell::next(&CELL_ALIVE, 6).alive, false);
-        assert_eq!(Cell::next(&CELL_ALIVE, 7).alive, false);
-        assert_eq!(Cell::next(&CELL_ALIVE, 8).alive, false);
+        assert_eq!(Cell::next(&CELL_ALIVE, 0).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_ALIVE, 1).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_ALIVE, 2).is_alive(), true);
+        assert_eq!(Cell::next(&CELL_ALIVE, 3).is_alive(), true);
+        assert_eq!(Cell::next(&CELL_ALIVE, 4).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_ALIVE, 5).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_ALIVE, 6).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_ALIVE, 7).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_ALIVE, 8).is_alive(), false);
 
         // cell stays dead unless 3 neighbours
-        assert_eq!(Cell::next(&CELL_DEAD, 0).alive, false);
-        assert_eq!(Cell::next(&CELL_DEAD, 1).alive, false);
-        assert_eq!(Cell::next(&CELL_DEAD, 2).alive, false);
-        assert_eq!(Cell::next(&CELL_DEAD, 3).alive, true);
-        assert_eq!(Cell::next(&CELL_DEAD, 4).alive, false);
-        assert_eq!(Cell::next(&CELL_DEAD, 5).alive, false);
-        assert_eq!(Cell::next(&CELL_DEAD, 6).alive, false);
-        assert_eq!(Cell::next(&CELL_DEAD, 7).alive, false);
-        assert_eq!(Cell::next(&CELL_DEAD, 8).alive, false);
+        assert_eq!(Cell::next(&CELL_DEAD, 0).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_DEAD, 1).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_DEAD, 2).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_DEAD, 3).is_alive(), true);
+        assert_eq!(Cell::next(&CELL_DEAD, 4).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_DEAD, 5).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_DEAD, 6).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_DEAD, 7).is_alive(), false);
+        assert_eq!(Cell::next(&CELL_DEAD, 8).is_alive(), false);
     }
 }
 
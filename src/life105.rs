@@ -0,0 +1,224 @@
+/* life105.rs - Life 1.05 pattern block format
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::convert::TryFrom;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Grid, LifeError, PatternMeta, Rule};
+
+/*****************************************************************************/
+
+const HEADER: &str = "#Life 1.05";
+
+impl Grid {
+    // Return a Grid of given size with the cells of each #P block of Life 1.05
+    // text alive, block offsets are relative to the centre of the Grid and rows
+    // before the first #P are placed as if after "#P 0 0". Also return the #D
+    // description lines and the rule given by #N (normal i.e. Conway) or #R
+    pub fn from_life105(input: &str, width: usize, height: usize) -> Result<(Grid, PatternMeta), LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        let mut meta = PatternMeta::default();
+        let error = |line: usize, msg: String| LifeError::ParseError { line, msg };
+
+        let mut lines = input.lines().enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        match lines.next() {
+            Some((_, line)) if line == HEADER => {},
+            Some((number, _)) => return Err(error(number, format!("expected '{}' header", HEADER))),
+            None => return Err(error(1, format!("expected '{}' header", HEADER)))
+        }
+
+        // Position of the start of the next row of the current block, relative to the centre,
+        // held as i128 so that offsets near the isize limits cannot overflow
+        let (mut block_x, mut y): (i128, i128) = (0, 0);
+        for (number, line) in lines {
+            if let Some(rest) = line.strip_prefix("#D") {
+                meta.comments.push(rest.trim().to_string());
+            }
+            else if line == "#N" {
+                meta.rule = Some(Rule::CONWAY);
+            }
+            else if let Some(rest) = line.strip_prefix("#R") {
+                meta.rule = Some(Rule::parse(rest).map_err(|err| error(number, err.to_string()))?);
+            }
+            else if let Some(rest) = line.strip_prefix("#P") {
+                let values: Vec<Option<isize>> = rest.split_whitespace().map(|value| value.parse().ok()).collect();
+                match values.as_slice() {
+                    [Some(x), Some(block_y)] => { block_x = *x as i128; y = *block_y as i128 },
+                    _ => return Err(error(number, format!("'{}' should be '#P x y'", line)))
+                }
+            }
+            else if line.starts_with('#') {
+                continue;
+            }
+            else {
+                for (dx, c) in line.chars().enumerate() {
+                    let alive = match c {
+                        '*' => true,
+                        '.' => false,
+                        _   => return Err(error(number, format!("unexpected '{}', expected '.' or '*'", c)))
+                    };
+                    if !alive {
+                        continue;
+                    }
+                    let gx = width as i128 / 2 + block_x + dx as i128;
+                    let gy = height as i128 / 2 + y;
+                    let placed = match (usize::try_from(gx), usize::try_from(gy)) {
+                        (Ok(gx), Ok(gy)) => grid.set(gx, gy, true).is_ok(),
+                        _ => false
+                    };
+                    if !placed {
+                        return Err(error(number, format!("cell at ({}, {}) falls outside the {}x{} grid", gx, gy, width, height)));
+                    }
+                }
+                y += 1;
+            }
+        }
+
+        Ok((grid, meta))
+    }
+
+    // Return Life 1.05 text of the live cells as a single #P block trimmed to
+    // their bounding box, offset relative to the centre of the Grid
+    pub fn to_life105(&self) -> String {
+        let mut text = format!("{}\n", HEADER);
+        if let Some((min_x, min_y, max_x, max_y)) = self.bounding_box() {
+            text.push_str(&format!("#P {} {}\n",
+                min_x as isize - self.width() as isize / 2,
+                min_y as isize - self.height() as isize / 2));
+            for row in self.rows().skip(min_y).take(max_y - min_y + 1) {
+                let line: String = row[min_x..=max_x].iter().map(|cell| if cell.alive { '*' } else { '.' }).collect();
+                // A blank row is kept as "." since empty lines are skipped when parsing
+                let line = line.trim_end_matches('.');
+                text.push_str(if line.is_empty() { "." } else { line });
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_life105_should_place_each_block_relative_to_centre() {
+        // given
+        let text = "\
+#Life 1.05
+#D Two gliders
+#D heading apart
+#N
+#P -4 -3
+.*
+..*
+***
+#P 1 0
+***
+*
+.*
+";
+
+        // when
+        let (grid, meta) = Grid::from_life105(text, 10, 8).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text("\
+..........
+..O.......
+...O......
+.OOO......
+......OOO.
+......O...
+.......O..
+..........", 'O').unwrap());
+        assert_eq!(meta.comments, vec!["Two gliders", "heading apart"]);
+        assert_eq!(meta.rule, Some(Rule::CONWAY));
+    }
+
+    #[test]
+    fn from_life105_should_parse_rule() {
+        // when
+        let (_, meta) = Grid::from_life105("#Life 1.05\n#R 23/36\n#P 0 0\n*\n", 4, 4).unwrap();
+
+        // then
+        assert_eq!(meta.rule, Some(Rule::HIGHLIFE));
+    }
+
+    #[test]
+    fn from_life105_should_return_error_given_invalid_input() {
+        assert_eq!(Grid::from_life105("#P 0 0\n*\n", 4, 4).unwrap_err().to_string(),
+            "line 1: expected '#Life 1.05' header");
+        assert_eq!(Grid::from_life105("#Life 1.05\n#P 0\n*\n", 4, 4).unwrap_err().to_string(),
+            "line 2: '#P 0' should be '#P x y'");
+        assert_eq!(Grid::from_life105("#Life 1.05\n#P 0 0\n*O\n", 4, 4).unwrap_err().to_string(),
+            "line 3: unexpected 'O', expected '.' or '*'");
+        assert_eq!(Grid::from_life105("#Life 1.05\n#P 1 0\n.*\n", 4, 4).unwrap_err().to_string(),
+            "line 3: cell at (4, 2) falls outside the 4x4 grid");
+    }
+
+    #[test]
+    fn from_life105_should_return_error_given_offsets_at_extremes() {
+        // given
+        let max = isize::MAX as i128;
+        let min = isize::MIN as i128;
+
+        // when
+        let far_right = Grid::from_life105("#Life 1.05\n#P 9223372036854775807 0\n*\n", 4, 4);
+        let far_down = Grid::from_life105("#Life 1.05\n#P 0 9223372036854775807\n.\n*\n", 4, 4);
+        let far_left = Grid::from_life105("#Life 1.05\n#P -9223372036854775808 0\n*\n", 4, 4);
+
+        // then
+        assert_eq!(far_right.unwrap_err().to_string(),
+            format!("line 3: cell at ({}, 2) falls outside the 4x4 grid", max + 2));
+        assert_eq!(far_down.unwrap_err().to_string(),
+            format!("line 4: cell at (2, {}) falls outside the 4x4 grid", max + 3));
+        assert_eq!(far_left.unwrap_err().to_string(),
+            format!("line 3: cell at ({}, 2) falls outside the 4x4 grid", min + 2));
+    }
+
+    #[test]
+    fn to_life105_should_write_single_block() {
+        // given
+        let grid = Grid::from_text("......\n...O..\n....O.\n..OOO.", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_life105(), "#Life 1.05\n#P -1 -1\n.*\n..*\n***\n");
+    }
+
+    #[test]
+    fn from_life105_should_round_trip_to_life105() {
+        for seed in 0..4 {
+            // given
+            let grid = Grid::random_seeded_with_density(seed, 13, 9, 0.3);
+
+            // when
+            let (parsed, _) = Grid::from_life105(&grid.to_life105(), 13, 9).unwrap();
+
+            // then
+            assert_eq!(parsed, grid);
+        }
+    }
+
+    #[test]
+    fn from_life105_should_round_trip_blank_interior_row() {
+        // given
+        let grid = Grid::from_text("O..\n...\nO..", 'O').unwrap();
+
+        // when
+        let text = grid.to_life105();
+        let (parsed, _) = Grid::from_life105(&text, 3, 3).unwrap();
+
+        // then
+        assert_eq!(text, "#Life 1.05\n#P -1 -1\n*\n.\n*\n");
+        assert_eq!(parsed, grid);
+    }
+}
+
+/*****************************************************************************/
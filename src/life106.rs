@@ -0,0 +1,180 @@
+/* life106.rs - Life 1.06 coordinate list pattern format
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::convert::TryFrom;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Grid, LifeError, Placement};
+
+/*****************************************************************************/
+
+const HEADER: &str = "#Life 1.06";
+
+impl Grid {
+    // Return a Grid of given size with the cells listed in Life 1.06 text alive,
+    // positioned by given placement, cells falling outside the Grid are an error
+    pub fn from_life106(input: &str, width: usize, height: usize, placement: Placement) -> Result<Grid, LifeError> {
+        Grid::life106(input, width, height, placement, false)
+    }
+
+    // Return a Grid as from_life106, discarding cells falling outside the Grid
+    pub fn from_life106_clipped(input: &str, width: usize, height: usize, placement: Placement) -> Result<Grid, LifeError> {
+        Grid::life106(input, width, height, placement, true)
+    }
+
+    // Return Life 1.06 text listing the live cells
+    pub fn to_life106(&self) -> String {
+        let mut text = format!("{}\n", HEADER);
+        for (x, y) in self.live_cells() {
+            text.push_str(&format!("{} {}\n", x, y));
+        }
+        text
+    }
+
+    // Parse Life 1.06 text into a Grid, optionally discarding cells falling outside it
+    fn life106(input: &str, width: usize, height: usize, placement: Placement, clip: bool) -> Result<Grid, LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        let mut lines = input.lines().enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        match lines.next() {
+            Some((_, line)) if line == HEADER => {},
+            Some((number, _)) => return Err(LifeError::ParseError { line: number, msg: format!("expected '{}' header", HEADER) }),
+            None => return Err(LifeError::ParseError { line: 1, msg: format!("expected '{}' header", HEADER) })
+        }
+
+        let mut coords = Vec::new();
+        for (number, line) in lines.filter(|(_, line)| !line.starts_with('#')) {
+            let values: Vec<Option<isize>> = line.split_whitespace().map(|value| value.parse().ok()).collect();
+            match values.as_slice() {
+                [Some(x), Some(y)] => coords.push((number, *x, *y)),
+                _ => return Err(LifeError::ParseError { line: number, msg: format!("'{}' should be a pair of integer coordinates", line) })
+            }
+        }
+
+        // Offsets and positions are worked out as i128, which holds any difference or
+        // sum of an isize and a usize, so that coordinates far apart cannot overflow
+        let min_x = coords.iter().map(|&(_, x, _)| x).min().unwrap_or(0) as i128;
+        let min_y = coords.iter().map(|&(_, _, y)| y).min().unwrap_or(0) as i128;
+        let (dx, dy) = match placement {
+            Placement::Origin { x, y } => (x as i128 - min_x, y as i128 - min_y),
+            Placement::Centre => {
+                let max_x = coords.iter().map(|&(_, x, _)| x).max().unwrap_or(0) as i128;
+                let max_y = coords.iter().map(|&(_, _, y)| y).max().unwrap_or(0) as i128;
+                ((width as i128 - (max_x - min_x + 1)) / 2 - min_x, (height as i128 - (max_y - min_y + 1)) / 2 - min_y)
+            }
+        };
+
+        for (number, x, y) in coords {
+            let (gx, gy) = (x as i128 + dx, y as i128 + dy);
+            if let (Ok(gx), Ok(gy)) = (usize::try_from(gx), usize::try_from(gy)) {
+                if grid.set(gx, gy, true).is_ok() {
+                    continue;
+                }
+            }
+            if !clip {
+                return Err(LifeError::ParseError {
+                    line:   number,
+                    msg:    format!("cell ({}, {}) falls outside the {}x{} grid at ({}, {})", x, y, width, height, gx, gy)
+                });
+            }
+        }
+        Ok(grid)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_life106_should_list_live_cells() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_life106(), "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n");
+    }
+
+    #[test]
+    fn from_life106_should_round_trip_glider() {
+        // given
+        let grid = Grid::from_text(".O...\n..O..\nOOO..\n.....", 'O').unwrap();
+
+        // when
+        let parsed = Grid::from_life106(&grid.to_life106(), 5, 4, Placement::Origin { x: 0, y: 0 }).unwrap();
+
+        // then
+        assert_eq!(parsed, grid);
+    }
+
+    #[test]
+    fn from_life106_should_translate_negative_coordinates_to_origin() {
+        // given
+        let text = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+
+        // when
+        let grid = Grid::from_life106(text, 5, 5, Placement::Origin { x: 2, y: 1 }).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".....\n...O.\n....O\n..OOO\n.....", 'O').unwrap());
+    }
+
+    #[test]
+    fn from_life106_should_centre_pattern() {
+        // given
+        let text = "#Life 1.06\n  -5 -5\n\n-4   -4  \n-3 -5\n";
+
+        // when
+        let grid = Grid::from_life106(text, 7, 5, Placement::Centre).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".......\n..O.O..\n...O...\n.......\n.......", 'O').unwrap());
+    }
+
+    #[test]
+    fn from_life106_should_return_error_given_invalid_input() {
+        assert_eq!(Grid::from_life106("0 0\n", 4, 4, Placement::Centre),
+            Err(LifeError::ParseError { line: 1, msg: "expected '#Life 1.06' header".to_string() }));
+        assert_eq!(Grid::from_life106("#Life 1.06\n0 0\n1 x\n", 4, 4, Placement::Centre),
+            Err(LifeError::ParseError { line: 3, msg: "'1 x' should be a pair of integer coordinates".to_string() }));
+        assert_eq!(Grid::from_life106("#Life 1.06\n0 0 0\n", 4, 4, Placement::Centre).is_err(), true);
+        assert_eq!(Grid::from_life106("#Life 1.06\n0 0\n", 0, 4, Placement::Centre).is_err(), true);
+    }
+
+    #[test]
+    fn from_life106_should_return_error_given_cells_outside_grid() {
+        // given
+        let text = "#Life 1.06\n0 0\n5 0\n";
+
+        // then
+        assert_eq!(Grid::from_life106(text, 4, 4, Placement::Origin { x: 0, y: 0 }).unwrap_err().to_string(),
+            "line 3: cell (5, 0) falls outside the 4x4 grid at (5, 0)");
+        assert_eq!(Grid::from_life106_clipped(text, 4, 4, Placement::Origin { x: 0, y: 0 }).unwrap(),
+            Grid::from_coords(4, 4, vec![(0, 0)]).unwrap());
+    }
+
+    #[test]
+    fn from_life106_should_place_coordinates_at_extremes_without_overflowing() {
+        // given
+        let lowest = format!("#Life 1.06\n{} 0\n", isize::MIN);
+        let apart = format!("#Life 1.06\n{} 0\n{} 0\n", isize::MAX, isize::MIN);
+
+        // then
+        assert_eq!(Grid::from_life106(&lowest, 4, 4, Placement::Origin { x: 1, y: 2 }).unwrap(),
+            Grid::from_coords(4, 4, vec![(1, 2)]).unwrap());
+        assert_eq!(Grid::from_life106(&apart, 4, 4, Placement::Origin { x: 0, y: 0 }).unwrap_err().to_string(),
+            format!("line 2: cell ({}, 0) falls outside the 4x4 grid at ({}, 0)", isize::MAX, isize::MAX as i128 - isize::MIN as i128));
+        assert!(Grid::from_life106(&apart, 4, 4, Placement::Centre).unwrap_err().to_string().starts_with("line 2: cell ("));
+        assert_eq!(Grid::from_life106_clipped(&apart, 4, 4, Placement::Origin { x: 0, y: 0 }).unwrap(),
+            Grid::from_coords(4, 4, vec![(0, 0)]).unwrap());
+    }
+}
+
+/*****************************************************************************/
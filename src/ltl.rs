@@ -0,0 +1,272 @@
+/* ltl.rs - Larger than Life rules with wide neighbourhoods
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::ops::RangeInclusive;
+use alloc::vec::Vec;
+
+use crate::par::*;
+use crate::{Cell, Grid, LifeError};
+
+/*****************************************************************************/
+
+// Which cells within the radius of a Neighbourhood are counted
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Shape {
+    // Square of cells up to radius away on both axes
+    Moore,
+    // Diamond of cells up to radius away in total across both axes
+    VonNeumann,
+    // Disc of cells less than radius + 1/2 away
+    Circular,
+    // Hexagon of cells emulated on the square grid by skewing it, so that at
+    // radius 1 the NE and SW neighbours are dropped as in Golly
+    Hexagonal
+}
+
+// Region of cells around a centre cell, which itself is never counted
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Neighbourhood {
+    pub radius: u8,
+    pub shape:  Shape
+}
+
+impl Neighbourhood {
+    // Radius 1 neighbourhoods of 8, 4 and 6 cells
+    pub const MOORE:        Neighbourhood = Neighbourhood::new(1, Shape::Moore);
+    pub const VON_NEUMANN:  Neighbourhood = Neighbourhood::new(1, Shape::VonNeumann);
+    pub const HEXAGONAL:    Neighbourhood = Neighbourhood::new(1, Shape::Hexagonal);
+
+    // Return a Neighbourhood of given radius and shape
+    pub const fn new(radius: u8, shape: Shape) -> Neighbourhood {
+        Neighbourhood { radius, shape }
+    }
+
+    // Return number of cells counted, excluding the centre
+    pub fn size(&self) -> u32 {
+        let radius = self.radius as isize;
+        (-radius..=radius).map(|dy| { let (left, right) = self.span(dy); (right - left + 1) as u32 }).sum::<u32>() - 1
+    }
+
+    // Return (dx, dy) offsets of the cells counted, in row major order
+    pub(crate) fn offsets(&self) -> Vec<(isize, isize)> {
+        let radius = self.radius as isize;
+        (-radius..=radius)
+            .flat_map(|dy| { let (left, right) = self.span(dy); (left..=right).map(move |dx| (dx, dy)) })
+            .filter(|&offset| offset != (0, 0))
+            .collect()
+    }
+
+    // Return the leftmost and rightmost dx the Neighbourhood covers, relative to
+    // the centre column, on the row dy away from the centre
+    fn span(&self, dy: isize) -> (isize, isize) {
+        let radius = self.radius as isize;
+        match self.shape {
+            Shape::Moore      => (-radius, radius),
+            Shape::VonNeumann => (dy.abs() - radius, radius - dy.abs()),
+            Shape::Circular   => {
+                // Largest dx with dx² + dy² < (radius + 1/2)², i.e. <= radius² + radius
+                let limit = radius * radius + radius - dy * dy;
+                let reach = (0..=radius).take_while(|dx| dx * dx <= limit).last().unwrap_or(0);
+                (-reach, reach)
+            },
+            Shape::Hexagonal  => (-radius + dy.max(0), radius + dy.min(0))
+        }
+    }
+
+    // Return number of live cells in the Neighbourhood of each cell of a Grid,
+    // wrapping around the edges. Each row of the Neighbourhood is summed in O(1)
+    // from a summed-area table of the Grid padded by radius cells on every side
+    pub(crate) fn counts(&self, grid: &Grid) -> Vec<Vec<u32>> {
+        let width  = grid.width();
+        let height = grid.height();
+        let radius = self.radius as usize;
+        let padded = width + 2 * radius + 1;
+        if width == 0 || height == 0 {
+            return vec![Vec::new(); height];
+        }
+
+        // sums[y * padded + x] is the number of live cells above and left of padded (x, y)
+        let mut sums = vec![0u32; padded * (height + 2 * radius + 1)];
+        for py in 0..height + 2 * radius {
+            let row = &grid[(py + height * radius - radius) % height];
+            let mut row_sum = 0;
+            for px in 0..width + 2 * radius {
+                row_sum += row[(px + width * radius - radius) % width].is_alive() as u32;
+                sums[(py + 1) * padded + px + 1] = sums[py * padded + px + 1] + row_sum;
+            }
+        }
+
+        // Number of live cells in padded rows top..bottom and columns left..right, exclusive
+        let rect = |left: usize, top: usize, right: usize, bottom: usize|
+            sums[bottom * padded + right] + sums[top * padded + left] - sums[top * padded + right] - sums[bottom * padded + left];
+
+        (0..height).into_par_iter()
+            .map(|y| (0..width)
+                .map(|x| {
+                    let centre = grid[y][x].is_alive() as u32;
+                    let total  = if self.shape == Shape::Moore {
+                        rect(x, y, x + 2 * radius + 1, y + 2 * radius + 1)
+                    }
+                    else {
+                        (0..=2 * radius)
+                            .map(|row| {
+                                let (left, right) = self.span(row as isize - radius as isize);
+                                let (left, right) = ((x + radius) as isize + left, (x + radius) as isize + right);
+                                rect(left as usize, y + row, right as usize + 1, y + row + 1)
+                            })
+                            .sum()
+                    };
+                    total - centre
+                })
+                .collect())
+            .collect()
+    }
+}
+
+/*****************************************************************************/
+
+// Birth and survival conditions given as ranges of live cell counts in a Neighbourhood
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LtlRule {
+    neighbourhood:  Neighbourhood,
+    birth:          RangeInclusive<u32>,
+    survival:       RangeInclusive<u32>
+}
+
+impl LtlRule {
+    // R5,C0,M1,S34..58,B34..45,NM i.e. survival counts 33 to 57 excluding the centre
+    pub const BUGS: LtlRule = LtlRule {
+        neighbourhood:  Neighbourhood::new(5, Shape::Moore),
+        birth:          34..=45,
+        survival:       33..=57
+    };
+
+    // Return an LtlRule given the Neighbourhood and count ranges causing birth and survival
+    pub fn new(neighbourhood: Neighbourhood, birth: RangeInclusive<u32>, survival: RangeInclusive<u32>) -> Result<LtlRule, LifeError> {
+        let size = neighbourhood.size();
+        for range in &[&birth, &survival] {
+            if range.end() > &size {
+                return Err(LifeError::InvalidRule(format!("neighbour count {} is out of range 0 to {}", range.end(), size)));
+            }
+        }
+        Ok(LtlRule { neighbourhood, birth, survival })
+    }
+
+    // Return Neighbourhood
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    // Return next cell state
+    pub fn apply(&self, cell: &Cell, neighbours: u32) -> Cell {
+        Cell::new(if cell.is_alive() { self.survival.contains(&neighbours) } else { self.birth.contains(&neighbours) })
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    // Return live cells within the Neighbourhood of (x, y) by checking every cell
+    fn brute_force_count(grid: &Grid, neighbourhood: Neighbourhood, x: usize, y: usize) -> u32 {
+        let radius = neighbourhood.radius as isize;
+        let (width, height) = (grid.width() as isize, grid.height() as isize);
+        let mut count = 0;
+        for dy in -radius..=radius {
+            let (left, right) = neighbourhood.span(dy);
+            for dx in left..=right {
+                if (dx, dy) != (0, 0) {
+                    let nx = (x as isize + dx).rem_euclid(width) as usize;
+                    let ny = (y as isize + dy).rem_euclid(height) as usize;
+                    count += grid[ny][nx].is_alive() as u32;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn size_should_count_cells_excluding_centre() {
+        assert_eq!(Neighbourhood::new(1, Shape::Moore).size(),      8);
+        assert_eq!(Neighbourhood::new(5, Shape::Moore).size(),      120);
+        assert_eq!(Neighbourhood::new(1, Shape::VonNeumann).size(), 4);
+        assert_eq!(Neighbourhood::new(2, Shape::VonNeumann).size(), 12);
+        assert_eq!(Neighbourhood::new(1, Shape::Circular).size(),   8);
+        assert_eq!(Neighbourhood::new(2, Shape::Circular).size(),   20);
+        assert_eq!(Neighbourhood::new(1, Shape::Hexagonal).size(),  6);
+        assert_eq!(Neighbourhood::new(2, Shape::Hexagonal).size(),  18);
+        assert_eq!(Neighbourhood::new(0, Shape::Moore).size(),      0);
+    }
+
+    #[test]
+    fn counts_should_match_neighbours_given_radius_1_moore() {
+        for seed in 0..8 {
+            // given
+            let grid = Grid::random_seeded(seed, 23, 14);
+
+            // when
+            let counts = Neighbourhood::new(1, Shape::Moore).counts(&grid);
+
+            // then
+            for (y, row) in counts.iter().enumerate() {
+                for (x, &count) in row.iter().enumerate() {
+                    assert_eq!(count, grid.neighbours(x, y) as u32);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn counts_should_match_brute_force_given_each_shape() {
+        // given
+        let grid = Grid::random_seeded(42, 19, 13);
+
+        for &shape in &[Shape::Moore, Shape::VonNeumann, Shape::Circular, Shape::Hexagonal] {
+            for radius in 0..=7 {
+                let neighbourhood = Neighbourhood::new(radius, shape);
+
+                // when
+                let counts = neighbourhood.counts(&grid);
+
+                // then
+                for (y, row) in counts.iter().enumerate() {
+                    for (x, &count) in row.iter().enumerate() {
+                        assert_eq!(count, brute_force_count(&grid, neighbourhood, x, y), "{:?} at ({}, {})", neighbourhood, x, y);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offsets_should_drop_ne_and_sw_given_hexagonal() {
+        assert_eq!(Neighbourhood::HEXAGONAL.offsets(), vec![(-1, -1), (0, -1), (-1, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(Neighbourhood::VON_NEUMANN.offsets(), vec![(0, -1), (-1, 0), (1, 0), (0, 1)]);
+        assert_eq!(Neighbourhood::MOORE.offsets().len(), 8);
+    }
+
+    #[test]
+    fn new_should_reject_counts_larger_than_neighbourhood() {
+        assert_eq!(LtlRule::new(Neighbourhood::new(1, Shape::Moore), 3..=3, 2..=9).is_err(), true);
+        assert_eq!(LtlRule::new(Neighbourhood::new(1, Shape::Moore), 3..=3, 2..=8).is_ok(),  true);
+    }
+
+    #[test]
+    fn apply_should_use_count_ranges() {
+        // given
+        let bugs = LtlRule::BUGS;
+
+        // then
+        assert_eq!(bugs.apply(&Cell::dead(), 33),  Cell::dead());
+        assert_eq!(bugs.apply(&Cell::dead(), 34),  Cell::alive());
+        assert_eq!(bugs.apply(&Cell::dead(), 46),  Cell::dead());
+        assert_eq!(bugs.apply(&Cell::alive(), 33), Cell::alive());
+        assert_eq!(bugs.apply(&Cell::alive(), 58), Cell::dead());
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,300 @@
+/* macrocell.rs - Golly Macrocell (.mc) quadtree pattern format
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use crate::{Cell, Grid, LifeError, Rule};
+
+/*****************************************************************************/
+
+const HEADER:       &str = "[M2]";
+const LEAF_LEVEL:   u32 = 3;
+// Largest square read by from_macrocell, 2^14 = 16384 cells wide
+const MAX_LEVEL:    u32 = 14;
+
+// Quadtree node, a leaf of 8 rows of cells packed most significant bit first
+// or an inner node of given level with 1-based indices of nw, ne, sw and se
+// children, 0 for an empty child
+enum Node {
+    Leaf([u8; 8]),
+    Inner(u32, [usize; 4])
+}
+
+impl Node {
+    fn level(&self) -> u32 {
+        match self {
+            Node::Leaf(_) => LEAF_LEVEL,
+            Node::Inner(level, _) => *level
+        }
+    }
+}
+
+// Node lines written so far, numbered from 1 in order, with the index of each
+// distinct node so identical subtrees are written once
+#[derive(Default)]
+struct Writer {
+    lines:  Vec<String>,
+    leaves: HashMap<[u8; 8], usize>,
+    inner:  HashMap<(u32, [usize; 4]), usize>
+}
+
+impl Writer {
+    // Return the index of the node of given level with top left at (x, y),
+    // writing it and its children if not already written, 0 if empty
+    fn node(&mut self, grid: &Grid, x: usize, y: usize, level: u32) -> usize {
+        if x >= grid.width() || y >= grid.height() {
+            return 0;
+        }
+
+        if level == LEAF_LEVEL {
+            let mut rows = [0u8; 8];
+            for (dy, bits) in rows.iter_mut().enumerate() {
+                for dx in 0..8 {
+                    if grid.get(x + dx, y + dy).is_some_and(Cell::is_alive) {
+                        *bits |= 0x80 >> dx;
+                    }
+                }
+            }
+            if rows == [0; 8] {
+                return 0;
+            }
+            let lines = &mut self.lines;
+            return *self.leaves.entry(rows).or_insert_with(|| {
+                lines.push(leaf_line(&rows));
+                lines.len()
+            });
+        }
+
+        let half = 1 << (level - 1);
+        let children = [
+            self.node(grid, x, y, level - 1),
+            self.node(grid, x + half, y, level - 1),
+            self.node(grid, x, y + half, level - 1),
+            self.node(grid, x + half, y + half, level - 1)];
+        if children == [0; 4] {
+            return 0;
+        }
+        let lines = &mut self.lines;
+        *self.inner.entry((level, children)).or_insert_with(|| {
+            lines.push(format!("{} {} {} {} {}", level, children[0], children[1], children[2], children[3]));
+            lines.len()
+        })
+    }
+}
+
+// Return leaf line of rows of '.' and '*' each ending '$', omitting trailing
+// dead cells and trailing empty rows
+fn leaf_line(rows: &[u8; 8]) -> String {
+    let rows: Vec<String> = rows.iter()
+        .map(|bits| (0..8).map(|i| if bits & (0x80 >> i) != 0 { '*' } else { '.' }).collect::<String>())
+        .map(|row| row.trim_end_matches('.').to_string())
+        .collect();
+    let last = rows.iter().rposition(|row| !row.is_empty()).unwrap_or(0);
+    rows[..=last].iter().map(|row| format!("{}$", row)).collect()
+}
+
+// Set the live cells of the node with given index and top left at (x, y)
+fn fill(grid: &mut Grid, nodes: &[Node], index: usize, x: usize, y: usize) {
+    match &nodes[index - 1] {
+        Node::Leaf(rows) => {
+            for (dy, bits) in rows.iter().enumerate() {
+                for dx in (0..8).filter(|dx| bits & (0x80 >> dx) != 0) {
+                    grid[(x + dx, y + dy)].alive = true;
+                }
+            }
+        },
+        Node::Inner(level, children) => {
+            let half = 1 << (level - 1);
+            for (i, &child) in children.iter().enumerate().filter(|(_, &child)| child != 0) {
+                fill(grid, nodes, child, x + (i % 2) * half, y + (i / 2) * half);
+            }
+        }
+    }
+}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return Macrocell text of the Grid with given rule, the Grid placed at the
+    // top left of the next power of two square of at least 8 cells
+    pub fn to_macrocell(&self, rule: &str) -> String {
+        let size = self.width().max(self.height()).next_power_of_two().max(8);
+        let mut writer = Writer::default();
+        writer.node(self, 0, 0, size.trailing_zeros());
+
+        let mut text = format!("{} (rs-life)\n#R {}\n", HEADER, rule);
+        for line in writer.lines {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text
+    }
+
+    // Return a Grid parsed from two state Macrocell text, sized to the square
+    // of the root node, and the rule given by #R if any
+    pub fn from_macrocell(input: &str) -> Result<(Grid, Option<Rule>), LifeError> {
+        let error = |line: usize, msg: String| LifeError::ParseError { line, msg };
+
+        let mut lines = input.lines().enumerate().map(|(index, line)| (index + 1, line.trim()));
+        match lines.next() {
+            Some((_, line)) if line.starts_with(HEADER) => {},
+            _ => return Err(error(1, format!("expected '{}' header", HEADER)))
+        }
+
+        let mut rule = None;
+        let mut nodes: Vec<Node> = Vec::new();
+        for (number, line) in lines {
+            if let Some(rest) = line.strip_prefix("#R") {
+                rule = Some(Rule::parse(rest).map_err(|err| error(number, err.to_string()))?);
+            }
+            else if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            else if line.starts_with(&['.', '*', '$'][..]) {
+                let mut rows = [0u8; 8];
+                let (mut x, mut y) = (0, 0);
+                for c in line.chars() {
+                    match c {
+                        '.' => x += 1,
+                        '*' if x < 8 && y < 8 => { rows[y] |= 0x80 >> x; x += 1 },
+                        '*' => return Err(error(number, format!("leaf cell at ({}, {}) falls outside 8x8", x, y))),
+                        '$' => { x = 0; y += 1 },
+                        _   => return Err(error(number, format!("unexpected '{}', expected '.', '*' or '$'", c)))
+                    }
+                }
+                nodes.push(Node::Leaf(rows));
+            }
+            else {
+                let values: Vec<Option<usize>> = line.split_whitespace().map(|value| value.parse().ok()).collect();
+                let (level, children) = match values.as_slice() {
+                    [Some(level), Some(nw), Some(ne), Some(sw), Some(se)] => (*level, [*nw, *ne, *sw, *se]),
+                    _ => return Err(error(number, format!("'{}' should be a leaf or 'level nw ne sw se'", line)))
+                };
+                let level = match u32::try_from(level) {
+                    Ok(level) if level > LEAF_LEVEL => level,
+                    _ => return Err(error(number, format!("level {} is not supported, expected {} or more", level, LEAF_LEVEL + 1)))
+                };
+                for &child in children.iter().filter(|&&child| child != 0) {
+                    match nodes.get(child.wrapping_sub(1)) {
+                        Some(node) if node.level() == level - 1 => {},
+                        Some(_) => return Err(error(number, format!("node {} is not of level {}", child, level - 1))),
+                        None => return Err(error(number, format!("node {} is not defined before node {}", child, nodes.len() + 1)))
+                    }
+                }
+                nodes.push(Node::Inner(level, children));
+            }
+        }
+
+        let level = nodes.last().ok_or(LifeError::EmptyPattern)?.level();
+        if level > MAX_LEVEL {
+            return Err(error(1, format!("pattern of size 2^{} is too large, at most 2^{} supported", level, MAX_LEVEL)));
+        }
+        let mut grid = Grid::new(1 << level, 1 << level)?;
+        fill(&mut grid, &nodes, nodes.len(), 0, 0);
+        Ok((grid, rule))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOSPER_GLIDER_GUN: &str = "\
+........................O...........
+......................O.O...........
+............OO......OO............OO
+...........O...O....OO............OO
+OO........O.....O...OO..............
+OO........O...O.OO....O.O...........
+..........O.....O.......O...........
+...........O...O....................
+............OO......................";
+
+    #[test]
+    fn to_macrocell_should_write_single_leaf_for_small_grid() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_macrocell("B3/S23"), "[M2] (rs-life)\n#R B3/S23\n.*$..*$***$\n");
+    }
+
+    #[test]
+    fn to_macrocell_should_write_glider_gun_quadtree() {
+        // given
+        let grid = Grid::from_text(GOSPER_GLIDER_GUN, 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_macrocell("B3/S23"), "\
+[M2] (rs-life)
+#R B3/S23
+$$$$**$**$
+$$....**$...*...*$..*$..*...*$..*$...*...*$
+....**$
+4 1 2 0 3
+$......*$....**$....**$*...**$**....*$*$
+*$*$$$$*$*$
+4 5 6 0 0
+5 4 7 0 0
+$$..**$..**$
+4 9 0 0 0
+5 10 0 0 0
+6 8 11 0 0
+");
+    }
+
+    #[test]
+    fn to_macrocell_should_share_identical_nodes() {
+        // given
+        let grid = Grid::from_text("O.......O.......\n\n\n\n\n\n\n\nO.......O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_macrocell("B36/S23"), "[M2] (rs-life)\n#R B36/S23\n*$\n4 1 1 1 1\n");
+    }
+
+    #[test]
+    fn from_macrocell_should_round_trip_to_macrocell() {
+        for &(width, height) in &[(1, 1), (8, 8), (9, 3), (36, 9), (40, 70)] {
+            // given
+            let mut grid = Grid::random_seeded(width as u64, width, height);
+            grid.set(width - 1, height - 1, true).unwrap();
+
+            // when
+            let (parsed, rule) = Grid::from_macrocell(&grid.to_macrocell("B36/S23")).unwrap();
+
+            // then
+            let size = width.max(height).next_power_of_two().max(8);
+            assert_eq!((parsed.width(), parsed.height()), (size, size));
+            assert_eq!(parsed.extract(0, 0, width, height).unwrap(), grid);
+            assert_eq!(parsed.population(), grid.population());
+            assert_eq!(rule, Some(Rule::HIGHLIFE));
+        }
+    }
+
+    #[test]
+    fn from_macrocell_should_return_error_given_invalid_input() {
+        assert_eq!(Grid::from_macrocell("#R B3/S23\n*$\n").unwrap_err().to_string(),
+            "line 1: expected '[M2]' header");
+        assert_eq!(Grid::from_macrocell("[M2]\n.*o$\n").unwrap_err().to_string(),
+            "line 2: unexpected 'o', expected '.', '*' or '$'");
+        assert_eq!(Grid::from_macrocell("[M2]\n.........*$\n").unwrap_err().to_string(),
+            "line 2: leaf cell at (9, 0) falls outside 8x8");
+        assert_eq!(Grid::from_macrocell("[M2]\n*$\n4 1 2 0 0\n").unwrap_err().to_string(),
+            "line 3: node 2 is not defined before node 2");
+        assert_eq!(Grid::from_macrocell("[M2]\n*$\n5 1 0 0 0\n").unwrap_err().to_string(),
+            "line 3: node 1 is not of level 4");
+        assert_eq!(Grid::from_macrocell("[M2]\n*$\n4 1 0\n").unwrap_err().to_string(),
+            "line 3: '4 1 0' should be a leaf or 'level nw ne sw se'");
+        assert_eq!(Grid::from_macrocell("[M2]\n*$\n3 1 0 0 0\n").unwrap_err().to_string(),
+            "line 3: level 3 is not supported, expected 4 or more");
+        assert_eq!(Grid::from_macrocell("[M2]\n*$\n4294967300 1 0 0 0\n").unwrap_err().to_string(),
+            "line 3: level 4294967300 is not supported, expected 4 or more");
+        assert_eq!(Grid::from_macrocell("[M2]\n#R B3/S23\n"), Err(LifeError::EmptyPattern));
+    }
+}
+
+/*****************************************************************************/
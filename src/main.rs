@@ -4,27 +4,55 @@
 
 extern crate termion;
 extern crate rand;
+extern crate unicode_width;
 
-use rand::{thread_rng};
-use std::{thread, time};
-use std::io::{Write, stdout};
+use rand::{thread_rng, Rng};
+use std::{fmt, fs, thread};
+use std::time::{Duration, Instant};
+use std::fmt::Write as _;
+use std::io::{self, Write, stdout};
 use termion::{clear,color,cursor,style};
-use termion::event::Key;
-use termion::input::TermRead;
+use termion::event::{Event, Key, MouseButton, MouseEvent};
+use termion::input::{MouseTerminal, TermRead};
 use termion::raw::IntoRawMode;
+use unicode_width::UnicodeWidthChar;
 
-use rs_life::{Cell, Grid};
+use rs_life::{patterns, Anchor, Boundary, CharSet, Grid, InsertMode, LineEnding, Overflow, Pattern, Rule, Simulation};
 
 /*****************************************************************************/
 
-const SLEEP_MILLIS: u64 = 100;
+// Generations per second run at unless given by --fps, and the range the speed
+// keys and --fps may set
+const DEFAULT_SPEED: f64 = 10.0;
+const MIN_SPEED:     f64 = 0.5;
+const MAX_SPEED:     f64 = 60.0;
+
+// Rules the Shift+U key cycles through: Conway's Life, HighLife, Seeds, Day &
+// Night, Life without Death and Maze
+const PRESETS: [&str; 6] = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B3/S012345678", "B3/S12345"];
+
+// Fraction of cells alive in a random grid unless given by --density
+const DEFAULT_DENSITY: f64 = 0.5;
+
+const USAGE: &str = "usage: rs-life [--render text|halfblocks|braille] [--width CELLS] [--height CELLS]
+    [--density FRACTION] [--seed N] [--rule B3/S23] [--boundary torus|dead|mirror]
+    [--fps N] [--file PATTERN] [--generations N] [--no-mouse]
+    [--color never|auto|always] [--theme classic|matrix|mono|amber|custom:FG,BG]
+    [--chars \"LIVE DEAD\"]";
+
+// Fraction of cells which may change between frames for only their chars to be
+// redrawn, above which redrawing every char is about as quick
+const MAX_CHANGED: f64 = 0.4;
+
+// Time each frame takes at least, input being handled and the screen drawn 60
+// times a second whatever the speed
+const FRAME_TIME: Duration = Duration::from_micros(16_667);
 
 /*****************************************************************************/
 
 struct Terminal <R, W> {
     input:  R,
-    output: W,
-    size: (u16, u16)
+    output: W
 }
 
 impl<R, W: Write> Terminal<R, W> {
@@ -36,7 +64,7 @@ impl<R, W: Write> Terminal<R, W> {
         write!(self.output, "{}{}{}{}", style::Reset, clear::All, cursor::Show, cursor::Goto(1, 1)).unwrap();
     }
 
-    fn write(&mut self, text: &str) {
+    fn write<T: fmt::Display>(&mut self, text: T) {
         write!(self.output, "{}", text).unwrap();
     }
 
@@ -45,64 +73,2758 @@ impl<R, W: Write> Terminal<R, W> {
     }
 }
 
-/*****************************************************************************/
+// How cells are drawn as chars
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Render {
+    // One cell per char
+    Text,
+    // 1x2 cells per half-block char
+    HalfBlocks,
+    // 2x4 cells per Braille char
+    Braille
+}
 
-fn main() {    
-    let sleep_duration = time::Duration::from_millis(SLEEP_MILLIS);
+impl Render {
+    // Return the Render of given name, as passed to --render
+    fn from_name(name: &str) -> Option<Render> {
+        match name {
+            "text"       => Some(Render::Text),
+            "halfblocks" => Some(Render::HalfBlocks),
+            "braille"    => Some(Render::Braille),
+            _ => None
+        }
+    }
 
-    // Initialise terminal
-    let mut terminal = Terminal {
-        input:  termion::async_stdin().keys(),
-        output: stdout().into_raw_mode().unwrap(),
-        size:   termion::terminal_size().unwrap()
+    // Return number of columns and rows of cells each char shows
+    fn cells_per_char(self) -> (usize, usize) {
+        match self {
+            Render::Text       => (1, 1),
+            Render::HalfBlocks => (1, 2),
+            Render::Braille    => (2, 4)
+        }
+    }
+
+    // Return size of grid shown in a terminal of given size
+    fn grid_size(self, size: (u16, u16)) -> (usize, usize) {
+        let (columns, rows) = self.cells_per_char();
+        (size.0 as usize * columns, size.1 as usize * rows)
+    }
+
+    // Render grid into buf as lines separated by "\n", replacing its contents, with
+    // chars of charset if one cell per char
+    fn render_into(self, grid: &Grid, charset: &CharSet, buf: &mut String) {
+        match self {
+            Render::Text       => grid.render_into_with(buf, charset, LineEnding::Lf),
+            Render::HalfBlocks => grid.render_halfblocks_into(buf, LineEnding::Lf),
+            Render::Braille    => grid.render_braille_into(buf, LineEnding::Lf)
+        }
+    }
+}
+
+// Colour a char is drawn in
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Colour {
+    // One of the 8 basic colours, or 8 to 15 for their bright versions
+    Basic(u8),
+    // One of the 256 colours of the xterm palette
+    Ansi(u8),
+    Rgb(u8, u8, u8)
+}
+
+impl fmt::Display for Colour {
+    // Write the escape code changing the foreground to the colour
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Colour::Basic(n) if n < 8 => write!(f, "\x1b[{}m", 30 + n),
+            Colour::Basic(n)  => write!(f, "\x1b[{}m", 90 + n - 8),
+            Colour::Ansi(n)   => write!(f, "{}", color::Fg(color::AnsiValue(n))),
+            Colour::Rgb(r, g, b) => write!(f, "{}", color::Fg(color::Rgb(r, g, b)))
+        }
+    }
+}
+
+impl Colour {
+    // Return the colour shown at depth closest to this, None if the terminal
+    // shows no colours
+    fn at(self, depth: Depth) -> Option<Colour> {
+        match (self, depth) {
+            (_, Depth::None) => None,
+            (Colour::Rgb(r, g, b), Depth::Ansi256) => Some(Colour::Ansi(16 + 36 * cube_level(r) + 6 * cube_level(g) + cube_level(b))),
+            (Colour::Basic(n) | Colour::Ansi(n), Depth::Basic) if n < 16 => Some(Colour::Basic(n % 8)),
+            (Colour::Ansi(n), Depth::Basic16) if n < 16 => Some(Colour::Basic(n)),
+            (Colour::Ansi(_) | Colour::Rgb(..), Depth::Basic) => Some(Colour::Basic(nearest_basic(self.rgb(), 8))),
+            (Colour::Ansi(_) | Colour::Rgb(..), Depth::Basic16) => Some(Colour::Basic(nearest_basic(self.rgb(), 16))),
+            _ => Some(self)
+        }
+    }
+
+    // Return the red, green and blue of the colour as xterm shows it
+    fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Colour::Basic(n) | Colour::Ansi(n) if n < 16 => BASIC_RGB[n as usize],
+            Colour::Ansi(n) if n >= 232 => (8 + 10 * (n - 232), 8 + 10 * (n - 232), 8 + 10 * (n - 232)),
+            Colour::Ansi(n) => {
+                let level = |i: u8| if i == 0 { 0 } else { 55 + 40 * i };
+                (level((n - 16) / 36), level((n - 16) / 6 % 6), level((n - 16) % 6))
+            },
+            Colour::Basic(_) => BASIC_RGB[15],
+            Colour::Rgb(r, g, b) => (r, g, b)
+        }
+    }
+}
+
+// Red, green and blue of the 16 basic colours as xterm shows them
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255)
+];
+
+// Return the level of the 6x6x6 cube of the 256 colour palette, from 16 on,
+// closest to a red, green or blue of value. Its levels are 0, 95, 135, 175, 215
+// and 255
+fn cube_level(value: u8) -> u8 {
+    match value {
+        0..=47   => 0,
+        48..=114 => 1,
+        _ => (value - 35) / 40
+    }
+}
+
+// Return the colour of the first count basic colours closest to that of given
+// red, green and blue
+fn nearest_basic(rgb: (u8, u8, u8), count: u8) -> u8 {
+    let distance = |other: &(u8, u8, u8)| {
+        let difference = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        difference(rgb.0, other.0) + difference(rgb.1, other.1) + difference(rgb.2, other.2)
     };
+    (0..count).min_by_key(|&n| distance(&BASIC_RGB[n as usize])).unwrap()
+}
 
-    terminal.init();
-    terminal.flush();
+// A colour written as the background rather than the foreground
+struct Background(Colour);
 
-    // Create a PRNG
-    let rng = thread_rng();
+impl fmt::Display for Background {
+    // Write the escape code changing the background to the colour
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Colour::Basic(n) if n < 8 => write!(f, "\x1b[{}m", 40 + n),
+            Colour::Basic(n)  => write!(f, "\x1b[{}m", 100 + n - 8),
+            Colour::Ansi(n)   => write!(f, "{}", color::Bg(color::AnsiValue(n))),
+            Colour::Rgb(r, g, b) => write!(f, "{}", color::Bg(color::Rgb(r, g, b)))
+        }
+    }
+}
 
-    // Initialise grid with randomised cell states
-    let mut grid = Grid::random(rng, terminal.size.0 as usize, terminal.size.1 as usize);
+// Return the colour of given name: one of the 8 basic colours, or "bright-"
+// followed by one for its bright version, a number from 0 to 255 of the xterm
+// palette or #rrggbb
+fn parse_colour(name: &str) -> Option<Colour> {
+    const NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+    let basic = |name: &str| NAMES.iter().position(|&basic| basic == name).map(|n| n as u8);
+    if let Some(hex) = name.strip_prefix('#').filter(|hex| hex.len() == 6 && hex.is_ascii()) {
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Colour::Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+    else if let Some(name) = name.strip_prefix("bright-") {
+        basic(name).map(|n| Colour::Basic(n + 8))
+    }
+    else {
+        basic(name).map(Colour::Basic).or_else(|| name.parse().ok().map(Colour::Ansi))
+    }
+}
 
-    // Main loop
-    loop {
+// How many colours the terminal shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Depth {
+    // None, chars being drawn in the terminal's own colour
+    None,
+    // The 8 basic colours, codes 30 to 37 which every colour terminal shows
+    Basic,
+    // The basic colours and their bright versions
+    Basic16,
+    Ansi256,
+    TrueColour
+}
+
+// Whether to colour chars, as given by --color
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColourChoice {
+    Never,
+    // Unless the terminal or NO_COLOR says not to
+    Auto,
+    Always
+}
+
+impl ColourChoice {
+    // Return the ColourChoice of given name, as passed to --color
+    fn from_name(name: &str) -> Option<ColourChoice> {
+        match name {
+            "never"  => Some(ColourChoice::Never),
+            "auto"   => Some(ColourChoice::Auto),
+            "always" => Some(ColourChoice::Always),
+            _ => None
+        }
+    }
+}
+
+// What the colour of each live cell shows, cycled by the T key
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Colouring {
+    // Green for every cell
+    Plain,
+    // Generations each cell has been alive
+    Age,
+    // Generations each cell has been alive in all
+    Heatmap
+}
+
+impl Colouring {
+    // Return the Colouring the T key changes to from this
+    fn next(self) -> Colouring {
+        match self {
+            Colouring::Plain   => Colouring::Age,
+            Colouring::Age     => Colouring::Heatmap,
+            Colouring::Heatmap => Colouring::Plain
+        }
+    }
+}
+
+// Return the colours a terminal shows given choice, reading the environment
+// variables TERM, COLORTERM and NO_COLOR through env. Auto colours unless TERM is
+// unset or dumb or NO_COLOR is set, and Always uses at least the basic colours
+fn colour_depth(choice: ColourChoice, env: &dyn Fn(&str) -> Option<String>) -> Depth {
+    let term = env("TERM").unwrap_or_default();
+    let depth = match env("COLORTERM").as_deref() {
+        Some("truecolor") | Some("24bit") => Depth::TrueColour,
+        _ if term.contains("256color") => Depth::Ansi256,
+        _ if term.contains("16color") => Depth::Basic16,
+        _ => Depth::Basic
+    };
+    match choice {
+        ColourChoice::Never => Depth::None,
+        ColourChoice::Auto if term.is_empty() || term == "dumb" || env("NO_COLOR").is_some_and(|value| !value.is_empty()) =>
+            Depth::None,
+        _ => depth
+    }
+}
+
+// Return the colour shown at depth for that of given red, green and blue, or
+// the basic colour given if the terminal shows no more, which may be closer in
+// look than the basic colour nearest in value
+fn palette_colour(rgb: (u8, u8, u8), basic: u8, depth: Depth) -> Option<Colour> {
+    match depth {
+        Depth::Basic | Depth::Basic16 => Colour::Basic(basic).at(depth),
+        _ => Colour::Rgb(rgb.0, rgb.1, rgb.2).at(depth)
+    }
+}
+
+// Return the colour shown at depth for a cell of given age, the generations it has
+// survived: bright white when newborn, yellow up to 5, green up to 20 and blue after
+fn age_colour(age: u32, depth: Depth) -> Option<Colour> {
+    match age {
+        0      => palette_colour((255, 255, 255), 15, depth),
+        1..=5  => palette_colour((255, 215, 0), 11, depth),
+        6..=20 => palette_colour((0, 175, 0), 2, depth),
+        _ => palette_colour((0, 95, 215), 4, depth)
+    }
+}
+
+// Return the colour shown at depth for a cell alive for given fraction of the
+// generations of the cell alive longest, from blue through yellow to red
+fn heat_colour(fraction: f64, depth: Depth) -> Option<Colour> {
+    let (from, to, basic, t) = if fraction < 0.5 {
+        ((0.0, 95.0, 255.0), (255.0, 215.0, 0.0), if fraction < 0.25 { 4 } else { 11 }, fraction * 2.0)
+    }
+    else {
+        ((255.0, 215.0, 0.0), (255.0, 0.0, 0.0), if fraction < 0.75 { 11 } else { 9 }, fraction * 2.0 - 1.0)
+    };
+    let mix = |from: f64, to: f64| (from + (to - from) * t.clamp(0.0, 1.0)).round() as u8;
+    palette_colour((mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2)), basic, depth)
+}
+
+// Colours and chars cells are drawn in
+#[derive(Clone, Debug, PartialEq)]
+struct Theme {
+    name: String,
+    // Colour of live cells when not coloured by age or heat, and of the
+    // background, the terminal's own where None
+    foreground: Option<Colour>,
+    background: Option<Colour>,
+    charset: CharSet
+}
+
+impl Theme {
+    // Names of the themes given to --theme and cycled by Shift+T key, other than custom
+    const NAMES: [&'static str; 4] = ["classic", "matrix", "mono", "amber"];
 
-        // Check input for key events
-        let input = terminal.input.next();
-        if let Some(Ok(key)) = input {
-            match key {
-                Key::Esc => break,
-                Key::Char('q') => break,
-                _ => {}
+    // Return the theme of given name, one of NAMES or custom:FG,BG with colours as
+    // parse_colour takes them or default for the terminal's own, or an error
+    fn parse(name: &str) -> Result<Theme, String> {
+        let (foreground, background) = match name {
+            "classic" => (Some(Colour::Ansi(2)), None),
+            "matrix"  => (Some(Colour::Rgb(0, 255, 65)), Some(Colour::Basic(0))),
+            "mono"    => (None, None),
+            "amber"   => (Some(Colour::Rgb(255, 176, 0)), Some(Colour::Basic(0))),
+            _ => {
+                let colours = name.strip_prefix("custom:").and_then(|colours| colours.split_once(','))
+                    .ok_or(format!("unknown theme: {}, not one of {} or custom:FG,BG", name, Theme::NAMES.join(", ")))?;
+                let colour = |name: &str| match name {
+                    "default" => Ok(None),
+                    _ => parse_colour(name).map(Some)
+                        .ok_or(format!("unknown colour: {}, not a colour name, number from 0 to 255 or #rrggbb", name))
+                };
+                (colour(colours.0)?, colour(colours.1)?)
             }
+        };
+        Ok(Theme { name: name.to_string(), foreground, background, charset: CharSet::DEFAULT })
+    }
+}
+
+// Return the chars for live and dead cells given to --chars, the two optionally
+// separated by a space, or an error if either is not one column wide
+fn parse_chars(chars: &str) -> Result<CharSet, String> {
+    let list: Vec<char> = chars.chars().collect();
+    let (alive, dead) = match list[..] {
+        [alive, dead] | [alive, ' ', dead] => (alive, dead),
+        _ => return Err(format!("--chars needs a char for live cells then one for dead cells, not '{}'", chars))
+    };
+    for c in [alive, dead] {
+        match char_width(c) {
+            1 => {},
+            0 => return Err(format!("--chars: '{}' takes no columns, so cells drawn with it would not line up", c.escape_debug())),
+            width => return Err(format!("--chars: '{}' is {} columns wide, so cells drawn with it would not line up", c, width))
         }
+    }
+    Ok(CharSet::new(dead, alive))
+}
+
+// Return the columns a terminal draws c across, as given by the East Asian Width
+// and emoji presentation of Unicode: 0 for control and combining chars, 2 for wide
+// chars and 1 for the rest
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+// Escape codes and chars a theme is drawn with in a terminal showing colours of
+// some depth, worked out once for the theme rather than for each char
+struct Pen {
+    charset: CharSet,
+    // Colour of live cells when not coloured by age or heat
+    colour: Option<Colour>,
+    // Codes starting bold and dimmed chars on the background
+    bold: String,
+    faint: String,
+    // Codes filling the screen with the background
+    clear: String
+}
 
-        // Check if terminal size has changed and regenerate grid
-        let term_size = termion::terminal_size().unwrap();
-        if term_size != terminal.size {
-            terminal.size = term_size;
-            grid = Grid::random(rng, terminal.size.0 as usize, terminal.size.1 as usize);
+impl Pen {
+    // Return the Pen drawing theme in a terminal showing colours of depth
+    fn new(theme: &Theme, depth: Depth) -> Pen {
+        let background = theme.background.and_then(|colour| colour.at(depth))
+            .map_or(String::new(), |colour| Background(colour).to_string());
+        Pen {
+            charset: theme.charset,
+            colour: theme.foreground.and_then(|colour| colour.at(depth)),
+            bold: format!("{}{}", style::Bold, background),
+            faint: format!("{}{}", style::Faint, background),
+            clear: format!("{}{}{}", background, clear::All, style::Reset)
         }
+    }
+}
 
-        // Get the next Grid state using the Cell::next function
-        grid = Grid::next(&grid, Cell::next);
+// Assemble the screen showing grid into frame, replacing its contents, using text
+// as scratch space for the rendered lines. Each line is placed by moving the
+// cursor to its start rather than by ending the line before, so that writing the
+// last line of a grid as tall as the terminal does not scroll it. Chars are drawn
+// with pen in colours, as from char_colours, changing colour only between chars of
+// different colours rather than for each char, and not for blanks which look the
+// same in any colour
+fn build_frame(render: Render, grid: &Grid, colours: &[Option<Colour>], pen: &Pen, text: &mut String, frame: &mut String) {
+    render.render_into(grid, &pen.charset, text);
+    frame.clear();
+    frame.reserve(text.len() + 16 * grid.height());
 
-        // Render to terminal
-        terminal.write(&format!("{}{}{}{}{}", 
-            cursor::Goto(1, 1), style::Bold, color::Fg(color::Green), grid, style::Reset));
+    let columns = chars_size(render, grid).0;
+    let mut current = None;
+    frame.push_str(&pen.bold);
+    for (row, line) in text.split('\n').enumerate() {
+        write!(frame, "{}", cursor::Goto(1, row as u16 + 1)).unwrap();
+        for (column, c) in line.chars().enumerate() {
+            if !c.is_whitespace() {
+                set_colour(colours[row * columns + column], &mut current, frame);
+            }
+            frame.push(c);
+        }
+    }
+    write!(frame, "{}", style::Reset).unwrap();
+}
 
-        // Flush terminal output
-        terminal.flush();
+// Assemble into frame only the chars of grid showing cells changed since the grid
+// drawn, or in a colour changed since those drawn, replacing its contents and
+// using text as scratch space for the rendered lines. Each run of changed chars
+// along a row is written after one cursor move. The whole screen is assembled as
+// build_frame does instead if drawn is None or a different size, or more than
+// MAX_CHANGED of the cells changed
+fn build_changes_frame(render: Render, grid: &Grid, colours: &[Option<Colour>], pen: &Pen, drawn: Option<&Drawn>,
+                       text: &mut String, frame: &mut String) {
+    let diff = match drawn.map(|drawn| (drawn.grid.diff(grid), drawn)) {
+        Some((Ok(diff), drawn)) if drawn.colours.len() == colours.len()
+            && (diff.births.len() + diff.deaths.len()) as f64 <= MAX_CHANGED * (grid.width() * grid.height()) as f64 => diff,
+        _ => return build_frame(render, grid, colours, pen, text, frame)
+    };
+
+    // Positions of changed chars as (row, column), in order
+    let (columns, rows) = render.cells_per_char();
+    let line_chars = chars_size(render, grid).0;
+    let mut changed: Vec<(usize, usize)> = diff.births.iter().chain(&diff.deaths).map(|&(x, y)| (y / rows, x / columns))
+        .chain(colours.iter().zip(&drawn.unwrap().colours).enumerate()
+            .filter(|(_, (colour, drawn))| colour != drawn)
+            .map(|(i, _)| (i / line_chars, i % line_chars)))
+        .collect();
+    changed.sort_unstable();
+    changed.dedup();
 
-        // Sleep
-        thread::sleep(sleep_duration);
+    render.render_into(grid, &pen.charset, text);
+    let lines: Vec<&str> = text.split('\n').collect();
+    frame.clear();
+    if changed.is_empty() {
+        return;
     }
 
-    // Restore terminal and flush output
-    terminal.restore();
-    terminal.flush();
+    let mut current = None;
+    frame.push_str(&pen.bold);
+    let mut remaining = &changed[..];
+    while let Some(&(row, column)) = remaining.first() {
+        let run = remaining.iter().enumerate().take_while(|&(i, &position)| position == (row, column + i)).count();
+        write!(frame, "{}", cursor::Goto(column as u16 + 1, row as u16 + 1)).unwrap();
+        for (i, c) in lines[row].chars().skip(column).take(run).enumerate() {
+            if !c.is_whitespace() {
+                set_colour(colours[row * line_chars + column + i], &mut current, frame);
+            }
+            frame.push(c);
+        }
+        remaining = &remaining[run..];
+    }
+    write!(frame, "{}", style::Reset).unwrap();
+}
+
+// Assemble into frame the escape code changing to colour, or back to the default
+// foreground given None, unless the current colour already. Frames start in the
+// default foreground, so chars of no colour look the same however they are drawn
+fn set_colour(colour: Option<Colour>, current: &mut Option<Colour>, frame: &mut String) {
+    if colour != *current {
+        match colour {
+            Some(new) => write!(frame, "{}", new).unwrap(),
+            None => write!(frame, "{}", color::Fg(color::Reset)).unwrap()
+        }
+        *current = colour;
+    }
+}
+
+// Return the number of chars along each line and the number of lines grid is
+// rendered as
+fn chars_size(render: Render, grid: &Grid) -> (usize, usize) {
+    let (columns, rows) = render.cells_per_char();
+    (grid.width().div_ceil(columns), grid.height().div_ceil(rows))
+}
+
+// Return the colour of each char grid is rendered as, line by line, that which
+// colour gives the first live cell it shows or None if it shows none or colour
+// gives none
+fn char_colours(render: Render, grid: &Grid, colour: &dyn Fn(usize, usize) -> Option<Colour>) -> Vec<Option<Colour>> {
+    let (columns, rows) = render.cells_per_char();
+    let (line_chars, lines) = chars_size(render, grid);
+    let mut colours = Vec::with_capacity(line_chars * lines);
+    for row in 0..lines {
+        for column in 0..line_chars {
+            let cells = (row * rows..((row + 1) * rows).min(grid.height()))
+                .flat_map(|y| (column * columns..((column + 1) * columns).min(grid.width())).map(move |x| (x, y)));
+            colours.push(cells.into_iter().find(|&(x, y)| grid[(x, y)].is_alive()).and_then(|(x, y)| colour(x, y)));
+        }
+    }
+    colours
+}
+
+// Assemble into frame the char of the lines rendered in text showing the cell at
+// cursor, in inverse video with pen over the char drawn there
+fn build_cursor(render: Render, text: &str, cursor: (usize, usize), pen: &Pen, frame: &mut String) {
+    let (columns, rows) = render.cells_per_char();
+    let (column, row) = (cursor.0 / columns, cursor.1 / rows);
+    if let Some(c) = text.split('\n').nth(row).and_then(|line| line.chars().nth(column)) {
+        write!(frame, "{}{}", cursor::Goto(column as u16 + 1, row as u16 + 1), pen.bold).unwrap();
+        set_colour(pen.colour, &mut None, frame);
+        write!(frame, "{}{}{}", style::Invert, c, style::Reset).unwrap();
+    }
+}
+
+// Grid last drawn and the colour of each char it was drawn in
+struct Drawn {
+    grid: Grid,
+    colours: Vec<Option<Colour>>
+}
+
+// Return the Boundary of given name, as passed to --boundary, of those the B key
+// cycles through
+fn boundary_from_name(name: &str) -> Option<Boundary> {
+    match name {
+        "torus"  => Some(Boundary::Torus),
+        "dead"   => Some(Boundary::Dead),
+        "mirror" => Some(Boundary::Mirror),
+        _ => None
+    }
+}
+
+// Return the name of boundary for the status line, that taken by --boundary for
+// those it takes
+fn boundary_name(boundary: Boundary) -> &'static str {
+    match boundary {
+        Boundary::Torus        => "torus",
+        Boundary::Dead         => "dead",
+        Boundary::Mirror       => "mirror",
+        Boundary::PerAxis {..} => "per-axis",
+        Boundary::Klein        => "klein",
+        Boundary::CrossSurface => "cross-surface"
+    }
+}
+
+// Return the Boundary the B key changes to from boundary: a torus, dead edges,
+// then mirrored edges
+fn next_boundary(boundary: Boundary) -> Boundary {
+    match boundary {
+        Boundary::Torus => Boundary::Dead,
+        Boundary::Dead  => Boundary::Mirror,
+        _ => Boundary::Torus
+    }
+}
+
+// Whether generations advance each frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Running,
+    Paused
+}
+
+// Settings given by command line arguments
+#[derive(Debug, PartialEq)]
+struct Config {
+    render: Render,
+    // Size of grid in cells, filling the terminal where not given
+    width: Option<usize>,
+    height: Option<usize>,
+    density: Option<f64>,
+    seed: Option<u64>,
+    // Rule to run, in place of any in the pattern file
+    rule: Option<Rule>,
+    boundary: Boundary,
+    speed: f64,
+    // Pattern file to start from in place of random cells
+    file: Option<String>,
+    // Generations to run before exiting, running until quit if not given
+    generations: Option<u64>,
+    // Whether the terminal reports mouse events, which stops some selecting text
+    mouse: bool,
+    colour: ColourChoice,
+    theme: Theme,
+    // Chars for live and dead cells in place of those of each theme
+    chars: Option<CharSet>
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            render: Render::Text,
+            width: None,
+            height: None,
+            density: None,
+            seed: None,
+            rule: None,
+            boundary: Boundary::Torus,
+            speed: DEFAULT_SPEED,
+            file: None,
+            generations: None,
+            mouse: true,
+            colour: ColourChoice::Auto,
+            theme: Theme::parse("classic").unwrap(),
+            chars: None
+        }
+    }
+}
+
+// Cells the grid starts with, and again on restarting
+enum Start {
+    // Cells each alive with probability density, from a stream of random numbers for seed
+    Random { density: f64, seed: u64 },
+    // A pattern in the centre of dead cells
+    Pattern(Pattern)
+}
+
+impl Start {
+    // Return the seed random cells start from, None for a pattern
+    fn seed(&self) -> Option<u64> {
+        match self {
+            Start::Random { seed, .. } => Some(*seed),
+            Start::Pattern(_) => None
+        }
+    }
+
+    // Return a grid of given size with the starting cells
+    fn grid(&self, width: usize, height: usize) -> Grid {
+        match self {
+            Start::Random { density, seed } => Grid::random_seeded_with_density(*seed, width, height, *density),
+            Start::Pattern(pattern) => pattern.cells().resize(width, height, Anchor::Centre)
+        }
+    }
+}
+
+// Choosing a pattern from the library to stamp into the grid, then placing it
+enum Stamp {
+    // Picking the pattern at given index of the list
+    Picking(usize),
+    // Placing given cells with their top left at given cell, wrapping around the
+    // edges of the grid, before the grid is changed
+    Placing(Grid, (usize, usize))
+}
+
+// Everything the app shows and the keys change, kept apart from the Terminal so
+// that handling keys and updating can be tested without one. Each frame the keys
+// pressed are handled, then the app is updated, then drawn
+struct App {
+    simulation: Simulation,
+    start: Start,
+    rule: Rule,
+    boundary: Boundary,
+    render: Render,
+    // Size of the terminal drawn to
+    size: (u16, u16),
+    // Size of grid given by --width and --height, kept while it fits the terminal
+    grid_width: Option<usize>,
+    grid_height: Option<usize>,
+    state: State,
+    // Generations per second while running, and the time run since the last
+    // generation advanced
+    speed: f64,
+    due: Duration,
+    // Generations to advance on the next update while paused
+    steps: u64,
+    // Generations to run before quitting, if limited
+    generations: Option<u64>,
+    // Cell at the cursor while editing, None when not
+    cursor: Option<(usize, usize)>,
+    // Pattern being picked or placed, None when not stamping
+    stamp: Option<Stamp>,
+    // Rule being typed in place of the status line, None when not
+    prompt: Option<String>,
+    // While a mouse button is held, whether it sets cells alive or dead and the
+    // terminal position it was last at
+    painting: Option<(bool, (u16, u16))>,
+    // Whether the last row shows the status line rather than the grid
+    show_status: bool,
+    // Colours the terminal shows and what they show
+    depth: Depth,
+    colouring: Colouring,
+    // Themes cycled through, the one drawn with and the Pen drawing it
+    themes: Vec<Theme>,
+    theme: usize,
+    pen: Pen,
+    // What was last drawn, None when the screen must be drawn in full
+    drawn: Option<Drawn>,
+    quit: bool
+}
+
+impl App {
+    // Return an app running the simulation set by config from given start in a
+    // terminal of given size showing colours of given depth, or an error if the grid set or the pattern started
+    // from is too big for it. A pattern is run under the rule in its file unless
+    // config sets one, and starts paused to be looked at before it changes
+    fn new(config: &Config, start: Start, size: (u16, u16), depth: Depth) -> Result<App, String> {
+        let (rule, state) = match &start {
+            Start::Random { .. }    => (config.rule.unwrap_or(Rule::CONWAY), State::Running),
+            Start::Pattern(pattern) => (config.rule.or(pattern.rule).unwrap_or(Rule::CONWAY), State::Paused)
+        };
+        let mut app = App {
+            // Replaced by restart once the app can give the size of grid to start with
+            simulation: Simulation::new(Grid::random_seeded(0, 0, 0), rule),
+            start,
+            rule,
+            boundary: config.boundary,
+            render: config.render,
+            size,
+            grid_width: config.width,
+            grid_height: config.height,
+            state,
+            speed: config.speed,
+            due: Duration::ZERO,
+            steps: 0,
+            generations: config.generations,
+            cursor: None,
+            stamp: None,
+            prompt: None,
+            painting: None,
+            show_status: true,
+            depth,
+            colouring: Colouring::Plain,
+            themes: Vec::new(),
+            theme: 0,
+            pen: Pen::new(&config.theme, depth),
+            drawn: None,
+            quit: false
+        };
+
+        let (width, height) = app.render.grid_size((size.0, size.1 - 1));
+        if let Some(grid_width) = app.grid_width.filter(|&grid_width| grid_width > width) {
+            return Err(format!("--width {} is more than the {} columns of cells the terminal shows", grid_width, width));
+        }
+        if let Some(grid_height) = app.grid_height.filter(|&grid_height| grid_height > height) {
+            return Err(format!("--height {} is more than the {} rows of cells the terminal shows", grid_height, height));
+        }
+
+        if let Start::Pattern(pattern) = &app.start {
+            let (width, height) = app.grid_size();
+            let (columns, rows) = app.render.cells_per_char();
+            if pattern.width() > width || pattern.height() > height {
+                let needs = if app.grid_width.is_some_and(|grid_width| pattern.width() > grid_width)
+                            || app.grid_height.is_some_and(|grid_height| pattern.height() > grid_height) {
+                    "a larger --width and --height".to_string()
+                }
+                else {
+                    format!("a terminal of at least {}x{} chars", pattern.width().div_ceil(columns), pattern.height().div_ceil(rows) + 1)
+                };
+                return Err(format!("the pattern of {}x{} cells does not fit in the {}x{} shown, needing {}",
+                    pattern.width(), pattern.height(), width, height, needs));
+            }
+        }
+
+        (app.themes, app.theme) = themes(&config.theme, config.chars);
+        app.set_theme(app.theme);
+        app.restart();
+        Ok(app)
+    }
+
+    // Start the simulation again from the starting cells in a grid of the size shown
+    fn restart(&mut self) {
+        let (width, height) = self.grid_size();
+        self.set_grid(self.start.grid(width, height), true);
+    }
+
+    // Start the simulation again from random cells for a new seed, alive with the
+    // density of the last random cells or DEFAULT_DENSITY after a pattern
+    fn reseed(&mut self) {
+        let density = match self.start {
+            Start::Random { density, .. } => density,
+            Start::Pattern(_) => DEFAULT_DENSITY
+        };
+        self.start = Start::Random { density, seed: thread_rng().gen() };
+        self.restart();
+    }
+
+    // Start the simulation again from dead cells, of a density 0 random grid as
+    // Grid::new cannot give one without cells
+    fn clear(&mut self) {
+        let (width, height) = self.grid_size();
+        self.set_grid(Grid::random_seeded_with_density(0, width, height, 0.0), true);
+    }
+
+    // Replace the grid, starting a new simulation of it if restarting, keeping the
+    // cursor within it and drawing it in full. The heatmap starts again if the
+    // grid is resized, and every live cell is newborn
+    fn set_grid(&mut self, grid: Grid, restarting: bool) {
+        let old_size = (self.simulation.grid().width(), self.simulation.grid().height());
+        if restarting {
+            self.simulation = Simulation::new(grid, self.rule).with_boundary(self.boundary).with_heatmap().with_ages();
+        }
+        else {
+            *self.simulation.grid_mut() = grid;
+            if (self.simulation.grid().width(), self.simulation.grid().height()) != old_size {
+                let simulation = std::mem::replace(&mut self.simulation, Simulation::new(Grid::random_seeded(0, 0, 0), self.rule));
+                self.simulation = simulation.with_heatmap().with_ages();
+            }
+        }
+
+        let (width, height) = (self.simulation.grid().width(), self.simulation.grid().height());
+        self.cursor = self.cursor.filter(|_| width > 0 && height > 0).map(|(x, y)| (x.min(width - 1), y.min(height - 1)));
+        self.stamp = match self.stamp.take() {
+            Some(Stamp::Placing(cells, at)) if width > 0 && height > 0 =>
+                Some(Stamp::Placing(cells, (at.0.min(width - 1), at.1.min(height - 1)))),
+            Some(Stamp::Placing(..)) => None,
+            stamp => stamp
+        };
+        self.drawn = None;
+    }
+
+    // Return size of grid shown, as given by --width and --height but no more
+    // than fills the rows of the terminal not showing the status line
+    fn grid_size(&self) -> (usize, usize) {
+        let (width, height) = self.render.grid_size((self.size.0, self.size.1 - self.show_status as u16));
+        (self.grid_width.map_or(width, |grid_width| grid_width.min(width)),
+         self.grid_height.map_or(height, |grid_height| grid_height.min(height)))
+    }
+
+    // Handle a key pressed since the last frame
+    fn handle_key(&mut self, key: Key) {
+        if self.handle_prompt_key(key) || self.handle_stamp_key(key) || self.handle_edit_key(key) {
+            return;
+        }
+
+        match key {
+            Key::Esc | Key::Char('q') => self.quit = true,
+            Key::Char(' ') => {
+                self.state = if self.state == State::Running { State::Paused } else { State::Running };
+                self.steps = 0;
+            },
+            Key::Char('n') | Key::Char('.') if self.state == State::Paused => self.steps += 1,
+            Key::Char('+') | Key::Char(']') => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+            Key::Char('-') | Key::Char('[') => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+            Key::Char('r') => self.reseed(),
+            Key::Char('R') => self.restart(),
+            Key::Char('c') => self.clear(),
+            Key::Char('h') => {
+                self.render = if self.render == Render::HalfBlocks { Render::Text } else { Render::HalfBlocks };
+                self.restart();
+            },
+            Key::Char('i') => {
+                self.show_status = !self.show_status;
+                self.fit_grid();
+            },
+            Key::Char('p') => self.stamp = Some(Stamp::Picking(0)),
+            Key::Char('u') => self.prompt = Some(String::new()),
+            Key::Char('U') => self.set_rule(next_preset(&self.rule)),
+            Key::Char('b') => {
+                self.boundary = next_boundary(self.boundary);
+                self.simulation.set_boundary(self.boundary);
+            },
+            Key::Char('t') => {
+                self.colouring = self.colouring.next();
+                self.drawn = None;
+            },
+            Key::Char('T') => self.set_theme((self.theme + 1) % self.themes.len()),
+            Key::Char('e') => {
+                let grid = self.simulation.grid();
+                if grid.width() > 0 && grid.height() > 0 {
+                    self.cursor = Some((grid.width() / 2, grid.height() / 2));
+                    self.state = State::Paused;
+                    self.steps = 0;
+                }
+            },
+            _ => {}
+        }
+    }
+
+    // Handle a mouse event, the left button setting cells alive and the right
+    // button setting them dead along the line it is dragged
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        match event {
+            MouseEvent::Press(button, x, y) => {
+                self.painting = match button {
+                    MouseButton::Left  => Some((true, (x, y))),
+                    MouseButton::Right => Some((false, (x, y))),
+                    _ => None
+                };
+                if let Some((alive, _)) = self.painting {
+                    self.paint((x, y), alive);
+                }
+            },
+            MouseEvent::Hold(x, y) => {
+                if let Some((alive, last)) = self.painting {
+                    for position in line(last, (x, y)).into_iter().skip(1) {
+                        self.paint(position, alive);
+                    }
+                    self.painting = Some((alive, (x, y)));
+                }
+            },
+            MouseEvent::Release(..) => self.painting = None
+        }
+    }
+
+    // Set the cells shown by the char at given terminal position alive or dead
+    fn paint(&mut self, position: (u16, u16), alive: bool) {
+        let grid = self.simulation.grid();
+        let (width, height) = (grid.width(), grid.height());
+        if let Some((x, y)) = cell_at(self.render, (width, height), position) {
+            let (columns, rows) = self.render.cells_per_char();
+            let grid = self.simulation.grid_mut();
+            for y in y..(y + rows).min(height) {
+                for x in x..(x + columns).min(width) {
+                    grid.set(x, y, alive).unwrap();
+                }
+            }
+        }
+    }
+
+    // Handle a key pressed while typing a rule, running under it once accepted if
+    // valid, and keeping on typing if not. Returns false if not typing a rule
+    fn handle_prompt_key(&mut self, key: Key) -> bool {
+        let line = match &mut self.prompt {
+            Some(line) => line,
+            None => return false
+        };
+
+        match prompt_line(line, &mut std::iter::once(key)) {
+            Prompted::Editing => return true,
+            Prompted::Accepted => match Rule::parse(line) {
+                Ok(rule) => self.set_rule(rule),
+                Err(_) => return true
+            },
+            Prompted::Cancelled => {}
+        }
+
+        // Draw in full in case the grid shows where the prompt was
+        self.prompt = None;
+        self.drawn = None;
+        true
+    }
+
+    // Run the simulation under rule from the current generation on, and after
+    // starting again
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.simulation.set_rule(rule);
+    }
+
+    // Handle a key pressed while stamping: moving through the list of patterns and
+    // picking one, then moving it around the edges of the grid as on a torus,
+    // rotating it and stamping it, or cancelling either. Returns false if not
+    // stamping or for a key acting as it otherwise would
+    fn handle_stamp_key(&mut self, key: Key) -> bool {
+        let grid = self.simulation.grid();
+        let (width, height) = (grid.width(), grid.height());
+        let stamp = match (self.stamp.take(), key) {
+            (Some(_), Key::Esc) => None,
+            (Some(Stamp::Picking(index)), Key::Up | Key::Char('k')) =>
+                Some(Stamp::Picking((index + patterns::all().len() - 1) % patterns::all().len())),
+            (Some(Stamp::Picking(index)), Key::Down | Key::Char('j')) => Some(Stamp::Picking((index + 1) % patterns::all().len())),
+            (Some(Stamp::Picking(index)), Key::Char('\n')) if width > 0 && height > 0 => {
+                let cells = patterns::all()[index].cells().clone();
+                let at = ((width / 2).saturating_sub(cells.width() / 2), (height / 2).saturating_sub(cells.height() / 2));
+                Some(Stamp::Placing(cells, at))
+            },
+            (Some(Stamp::Placing(cells, (x, y))), key) => match key {
+                Key::Left  | Key::Char('h') => Some(Stamp::Placing(cells, ((x + width - 1) % width, y))),
+                Key::Right | Key::Char('l') => Some(Stamp::Placing(cells, ((x + 1) % width, y))),
+                Key::Up    | Key::Char('k') => Some(Stamp::Placing(cells, (x, (y + height - 1) % height))),
+                Key::Down  | Key::Char('j') => Some(Stamp::Placing(cells, (x, (y + 1) % height))),
+                Key::Char('[') => Some(Stamp::Placing(cells.rotate_ccw(), (x, y))),
+                Key::Char(']') => Some(Stamp::Placing(cells.rotate_cw(), (x, y))),
+                Key::Char('\n') => {
+                    self.simulation.grid_mut().insert(&cells, x, y, InsertMode::Replace, Overflow::Wrap);
+                    None
+                },
+                _ => {
+                    self.stamp = Some(Stamp::Placing(cells, (x, y)));
+                    return false;
+                }
+            },
+            (stamp, _) => {
+                self.stamp = stamp;
+                return false;
+            }
+        };
+
+        // Draw in full to remove the list or the pattern from where it was
+        self.stamp = stamp;
+        self.drawn = None;
+        true
+    }
+
+    // Handle a key pressed while editing, moving the cursor around the edges of the
+    // grid as on a torus, toggling the cell at it or leaving it to run again.
+    // Returns false if not editing or for a key acting as when running, apart from
+    // Space which is ignored to keep the simulation paused
+    fn handle_edit_key(&mut self, key: Key) -> bool {
+        let (x, y) = match self.cursor {
+            Some(cursor) => cursor,
+            None => return false
+        };
+        let (width, height) = (self.simulation.grid().width(), self.simulation.grid().height());
+
+        let cursor = match key {
+            Key::Left  | Key::Char('h') => ((x + width - 1) % width, y),
+            Key::Right | Key::Char('l') => ((x + 1) % width, y),
+            Key::Up    | Key::Char('k') => (x, (y + height - 1) % height),
+            Key::Down  | Key::Char('j') => (x, (y + 1) % height),
+            Key::Char('\n') | Key::Char('x') => {
+                self.simulation.grid_mut().toggle(x, y).unwrap();
+                return true;
+            },
+            Key::Esc | Key::Char('e') => {
+                self.cursor = None;
+                self.state = State::Running;
+                self.drawn = None;
+                return true;
+            },
+            Key::Char(' ') => return true,
+            _ => return false
+        };
+
+        // Draw in full to remove the cursor from where it was
+        self.cursor = Some(cursor);
+        self.drawn = None;
+        true
+    }
+
+    // Resize the grid to fit a terminal of given size, if it differs
+    fn resize(&mut self, size: (u16, u16)) {
+        if size != self.size {
+            self.size = size;
+            self.fit_grid();
+        }
+    }
+
+    // Resize the grid about its centre to the size shown
+    fn fit_grid(&mut self) {
+        let (width, height) = self.grid_size();
+        self.set_grid(self.simulation.grid().resize(width, height, Anchor::Centre), false);
+    }
+
+    // Advance one generation if running and elapsed, the time since the last
+    // update, brings the time run to that of a generation, or those stepped
+    // through by keys if paused, then quit if the generations to run have been.
+    // At most one generation's time is carried over, so a slow step only slows
+    // the speed rather than being caught up on
+    fn update(&mut self, elapsed: Duration) {
+        let steps = match self.state {
+            State::Running => {
+                self.due += elapsed;
+                if self.due >= self.generation_time() {
+                    self.due = (self.due - self.generation_time()).min(self.generation_time());
+                    1
+                }
+                else {
+                    0
+                }
+            },
+            State::Paused => {
+                self.due = Duration::ZERO;
+                self.steps
+            }
+        };
+        self.steps = 0;
+
+        let generation = self.simulation.generation();
+        for _ in 0..self.generations.map_or(steps, |generations| steps.min(generations.saturating_sub(generation))) {
+            self.simulation.step();
+        }
+        if self.generations.is_some_and(|generations| self.simulation.generation() >= generations) {
+            self.quit = true;
+        }
+    }
+
+    // Draw with the theme at given index of those cycled through, in full
+    fn set_theme(&mut self, index: usize) {
+        self.theme = index;
+        self.pen = Pen::new(&self.themes[index], self.depth);
+        self.drawn = None;
+    }
+
+    // Return the colour of each char showing the grid, as coloured by age or heat
+    // or plainly
+    fn colours(&self) -> Vec<Option<Colour>> {
+        let grid = self.simulation.grid();
+        let heatmap = self.simulation.heatmap();
+        let max = heatmap.max().max(1) as f64;
+        let colour = |x: usize, y: usize| match self.colouring {
+            Colouring::Plain   => self.pen.colour,
+            Colouring::Age     => age_colour(self.simulation.age(x, y).unwrap_or(0), self.depth),
+            Colouring::Heatmap => heat_colour(heatmap.get(x, y).unwrap_or(0) as f64 / max, self.depth)
+        };
+        char_colours(self.render, grid, &colour)
+    }
+
+    // Return what the app is doing for the status line
+    fn activity(&self) -> &'static str {
+        match (&self.stamp, self.cursor, self.state) {
+            (Some(_), ..)             => "Stamping",
+            (None, Some(_), _)        => "Editing",
+            (None, None, State::Paused)  => "Paused",
+            (None, None, State::Running) => "Running"
+        }
+    }
+
+    // Return how long each generation should take while running
+    fn generation_time(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.speed)
+    }
+}
+
+/*****************************************************************************/
+
+// Return the cell at the top left of the char at given terminal position, which
+// is 1-based, or None if the char shows no cell of a grid of given size, as
+// along the status line below it
+fn cell_at(render: Render, grid_size: (usize, usize), position: (u16, u16)) -> Option<(usize, usize)> {
+    let (columns, rows) = render.cells_per_char();
+    let x = (position.0 as usize).checked_sub(1)? * columns;
+    let y = (position.1 as usize).checked_sub(1)? * rows;
+    if x < grid_size.0 && y < grid_size.1 { Some((x, y)) } else { None }
+}
+
+// Return the terminal positions along a line from one to another, including
+// both, by Bresenham's algorithm
+fn line(from: (u16, u16), to: (u16, u16)) -> Vec<(u16, u16)> {
+    let (mut x, mut y) = (from.0 as i32, from.1 as i32);
+    let (dx, dy) = ((to.0 as i32 - x).abs(), -(to.1 as i32 - y).abs());
+    let (sx, sy) = ((to.0 as i32 - x).signum(), (to.1 as i32 - y).signum());
+    let mut error = dx + dy;
+
+    let mut positions = vec![(x as u16, y as u16)];
+    while (x, y) != (to.0 as i32, to.1 as i32) {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+        positions.push((x as u16, y as u16));
+    }
+    positions
+}
+
+// Return the rule of PRESETS after rule, or the first if rule is not one of them
+fn next_preset(rule: &Rule) -> Rule {
+    let presets: Vec<Rule> = PRESETS.iter().map(|preset| Rule::parse(preset).unwrap()).collect();
+    let index = presets.iter().position(|preset| preset == rule).map_or(0, |index| (index + 1) % presets.len());
+    presets[index]
+}
+
+// How prompt_line finished with the keys given it
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Prompted {
+    // The keys ran out first, the line to be edited by more
+    Editing,
+    // Enter key was pressed
+    Accepted,
+    // Escape key was pressed
+    Cancelled
+}
+
+// Edit line with keys until Enter key accepts it or Escape key cancels it, or
+// they run out, leaving the rest. Printable chars are added to the end of the
+// line and Backspace removes the last char, other keys being ignored
+fn prompt_line(line: &mut String, keys: &mut impl Iterator<Item = Key>) -> Prompted {
+    for key in keys {
+        match key {
+            Key::Char('\n') => return Prompted::Accepted,
+            Key::Esc => return Prompted::Cancelled,
+            Key::Backspace => {
+                line.pop();
+            },
+            Key::Char(c) if !c.is_control() => line.push(c),
+            _ => {}
+        }
+    }
+    Prompted::Editing
+}
+
+// Return a line of exactly width chars asking for a rule, showing line typed so
+// far then why it is not a rule if it is not
+fn format_prompt(line: &str, width: usize) -> String {
+    let mut prompt = format!(" Rule: {}_", line);
+    if let (false, Err(err)) = (line.is_empty(), Rule::parse(line)) {
+        write!(prompt, "  {}", err).unwrap();
+    }
+    format!("{:<width$.width$}", prompt, width = width)
+}
+
+// Return a copy of grid with cells stamped with their top left at given cell,
+// wrapping around its edges, showing how the grid would look once stamped
+fn composite(grid: &Grid, cells: &Grid, at: (usize, usize)) -> Grid {
+    let mut composite = grid.clone();
+    composite.insert(cells, at.0, at.1, InsertMode::Replace, Overflow::Wrap);
+    composite
+}
+
+// Assemble into frame the chars showing cells stamped into grid at given cell
+// dimmed with pen over those drawn, using text as scratch space for the rendered
+// lines
+fn build_ghost(render: Render, grid: &Grid, cells: &Grid, at: (usize, usize), pen: &Pen, text: &mut String, frame: &mut String) {
+    let (width, height) = (grid.width(), grid.height());
+    let (columns, rows) = render.cells_per_char();
+    let mut chars: Vec<(usize, usize)> = (0..cells.height().min(height))
+        .flat_map(|y| (0..cells.width().min(width)).map(move |x| (((at.1 + y) % height) / rows, ((at.0 + x) % width) / columns)))
+        .collect();
+    chars.sort_unstable();
+    chars.dedup();
+
+    render.render_into(&composite(grid, cells, at), &pen.charset, text);
+    let lines: Vec<Vec<char>> = text.split('\n').map(|line| line.chars().collect()).collect();
+    frame.push_str(&pen.faint);
+    set_colour(pen.colour, &mut None, frame);
+    for (row, column) in chars {
+        write!(frame, "{}{}", cursor::Goto(column as u16 + 1, row as u16 + 1), lines[row][column]).unwrap();
+    }
+    write!(frame, "{}", style::Reset).unwrap();
+}
+
+// Assemble into frame the list of library patterns to pick from, in a box at the
+// top left of a terminal of given size with the one at index in inverse video.
+// The list scrolls to keep it in view if the terminal is too short to show all
+fn build_picker(index: usize, size: (u16, u16), frame: &mut String) {
+    let names: Vec<&str> = patterns::all().iter().map(|pattern| pattern.name.as_deref().unwrap_or("")).collect();
+    let width = (names.iter().map(|name| name.chars().count()).max().unwrap_or(0) + 2).min(size.0.saturating_sub(2) as usize);
+    let shown = (size.1.saturating_sub(2) as usize).min(names.len());
+    let first = (index + 1).saturating_sub(shown);
+
+    for (row, (i, name)) in names.iter().enumerate().skip(first).take(shown).enumerate() {
+        write!(frame, "{}", cursor::Goto(2, row as u16 + 2)).unwrap();
+        if i == index {
+            write!(frame, "{}", style::Invert).unwrap();
+        }
+        write!(frame, " {:<width$.width$}{}", name, style::Reset, width = width.saturating_sub(1)).unwrap();
+    }
+}
+
+// Return the themes cycled through starting from theme, those of Theme::NAMES
+// with theme in place of that of its name or after them if custom, each with
+// chars in place of its own if given, and the index of theme among them
+fn themes(theme: &Theme, chars: Option<CharSet>) -> (Vec<Theme>, usize) {
+    let mut themes: Vec<Theme> = Theme::NAMES.iter().map(|name| Theme::parse(name).unwrap()).collect();
+    let index = match themes.iter().position(|other| other.name == theme.name) {
+        Some(index) => index,
+        None => {
+            themes.push(theme.clone());
+            themes.len() - 1
+        }
+    };
+    for theme in &mut themes {
+        theme.charset = chars.unwrap_or(theme.charset);
+    }
+    (themes, index)
+}
+
+// Return the value following an option in args, parsed and checked by valid,
+// or an error saying what the option needs
+fn value<T: std::str::FromStr>(args: &mut impl Iterator<Item = String>, option: &str, needs: &str,
+                               valid: impl Fn(&T) -> bool) -> Result<T, String> {
+    let arg = args.next().ok_or(format!("{} needs {}", option, needs))?;
+    arg.parse().ok().filter(valid).ok_or(format!("{} needs {}, not '{}'", option, needs, arg))
+}
+
+// Return the Config given by command line arguments, defaults for those not given
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+    let mut config = Config::default();
+    while let Some(arg) = args.next() {
+        let args = &mut args;
+        match arg.as_str() {
+            "--render" => {
+                let name = args.next().ok_or("--render needs one of text, halfblocks or braille")?;
+                config.render = Render::from_name(&name).ok_or(format!("unknown render mode: {}", name))?;
+            },
+            "--width"  => config.width  = Some(value(args, "--width", "a number of cells above 0", |&width| width > 0)?),
+            "--height" => config.height = Some(value(args, "--height", "a number of cells above 0", |&height| height > 0)?),
+            "--density" => {
+                config.density = Some(value(args, "--density", "a fraction from 0 to 1", |density| (0.0..=1.0).contains(density))?);
+            },
+            "--seed" => config.seed = Some(value(args, "--seed", "a whole number", |_| true)?),
+            "--rule" => {
+                let rule: String = value(args, "--rule", "a rule such as B3/S23", |_| true)?;
+                config.rule = Some(Rule::parse(&rule).map_err(|err| format!("--rule {}: {}", rule, err))?);
+            },
+            "--boundary" => {
+                let name = args.next().ok_or("--boundary needs one of torus, dead or mirror")?;
+                config.boundary = boundary_from_name(&name).ok_or(format!("unknown boundary: {}", name))?;
+            },
+            "--fps" => {
+                let needs = format!("a number of generations per second from {} to {}", MIN_SPEED, MAX_SPEED);
+                config.speed = value(args, "--fps", &needs, |fps| (MIN_SPEED..=MAX_SPEED).contains(fps))?;
+            },
+            "--file" => config.file = Some(value(args, "--file", "a pattern file", |_| true)?),
+            "--generations" => config.generations = Some(value(args, "--generations", "a number of generations", |_| true)?),
+            "--no-mouse" => config.mouse = false,
+            "--theme" => config.theme = Theme::parse(&value::<String>(args, "--theme", "a theme", |_| true)?)?,
+            "--chars" => config.chars = Some(parse_chars(&value::<String>(args, "--chars", "chars such as \"# .\"", |_| true)?)?),
+            "--color" => {
+                let name = args.next().ok_or("--color needs one of never, auto or always")?;
+                config.colour = ColourChoice::from_name(&name).ok_or(format!("unknown color choice: {}", name))?;
+            },
+            _ => return Err(format!("unknown argument: {}\n{}", arg, USAGE))
+        }
+    }
+
+    if config.file.is_some() && (config.density.is_some() || config.seed.is_some()) {
+        return Err("--density and --seed set random cells, so cannot be given with --file".to_string());
+    }
+    Ok(config)
+}
+
+// Return the cells a Config starts with, reading the pattern file if given
+fn start(config: &Config) -> Result<Start, String> {
+    match &config.file {
+        Some(path) => Ok(Start::Pattern(load_pattern(path)?)),
+        None => Ok(Start::Random {
+            density: config.density.unwrap_or(DEFAULT_DENSITY),
+            seed: config.seed.unwrap_or_else(|| thread_rng().gen())
+        })
+    }
+}
+
+// Return the pattern in the file at path, read as plaintext if its name ends
+// .cells and as RLE otherwise
+fn load_pattern(path: &str) -> Result<Pattern, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path, err))?;
+    let pattern = if path.ends_with(".cells") {
+        Pattern::from_cells(&text).map_err(|err| err.to_string())
+    }
+    else {
+        Pattern::from_rle(&text).map_err(|err| err.to_string())
+    };
+    pattern.map_err(|err| format!("{}: {}", path, err))
+}
+
+// What the status line shows
+struct Status<'a> {
+    generation: u64,
+    population: usize,
+    size: (usize, usize),
+    rule: &'a Rule,
+    boundary: Boundary,
+    // Seed of random cells started from, None for a pattern
+    seed: Option<u64>,
+    speed: f64,
+    // What the app is doing, as from App::activity
+    activity: &'static str
+}
+
+impl<'a> Status<'a> {
+    // Return the status of given app
+    fn of(app: &'a App) -> Status<'a> {
+        let grid = app.simulation.grid();
+        Status {
+            generation: app.simulation.generation(),
+            population: grid.population(),
+            size: (grid.width(), grid.height()),
+            rule: app.simulation.rule(),
+            boundary: app.simulation.boundary(),
+            seed: app.start.seed(),
+            speed: app.speed,
+            activity: app.activity()
+        }
+    }
+}
+
+// Return status as a line of exactly width chars, cut short or padded with spaces
+fn format_status(status: &Status, width: usize) -> String {
+    let mut line = format!(" Gen {} | Pop {} | {}x{} | {} | {} | ", status.generation, status.population,
+        status.size.0, status.size.1, status.rule, boundary_name(status.boundary));
+    if let Some(seed) = status.seed {
+        write!(line, "Seed {} | ", seed).unwrap();
+    }
+    write!(line, "{} gen/s | {}", status.speed, status.activity).unwrap();
+    format!("{:<width$.width$}", line, width = width)
+}
+
+// Assemble into frame the status line of app along the last row of the terminal
+// in inverse video, if it is shown, or the prompt for a rule in its place
+fn build_status(app: &App, frame: &mut String) {
+    let line = match &app.prompt {
+        Some(line) => format_prompt(line, app.size.0 as usize),
+        None if app.show_status => format_status(&Status::of(app), app.size.0 as usize),
+        None => return
+    };
+    write!(frame, "{}{}{}{}", cursor::Goto(1, app.size.1), style::Invert, line, style::Reset).unwrap();
+}
+
+// Return how long to sleep after a frame which took elapsed so that it takes
+// frame_time in all, none if it took longer
+fn time_to_next_frame(frame_time: Duration, elapsed: Duration) -> Duration {
+    frame_time.saturating_sub(elapsed)
+}
+
+// Return a terminal size read by termion if it has both rows and columns, which
+// a terminal in some environments may not
+fn usable_size(size: io::Result<(u16, u16)>) -> Option<(u16, u16)> {
+    size.ok().filter(|&(columns, rows)| columns > 0 && rows > 0)
+}
+
+/*****************************************************************************/
+
+fn main() {    
+    // Parse arguments before the terminal enters raw mode so errors print plainly
+    let config = match parse_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("rs-life: {}", message);
+            std::process::exit(2);
+        }
+    };
+
+    // Read the size before raw mode too, so that the shell is left as it was if there is none
+    let size = match usable_size(termion::terminal_size()) {
+        Some(size) => size,
+        None => {
+            eprintln!("rs-life: the terminal has no rows or columns to draw in");
+            std::process::exit(1);
+        }
+    };
+
+    // Initialise app running the grid which config starts with
+    let depth = colour_depth(config.colour, &|name| std::env::var(name).ok());
+    let mut app = match start(&config).and_then(|start| App::new(&config, start, size, depth)) {
+        Ok(app) => app,
+        Err(message) => {
+            eprintln!("rs-life: {}", message);
+            std::process::exit(1);
+        }
+    };
+
+    // Initialise terminal, reporting mouse events unless turned off
+    let output = stdout().into_raw_mode().unwrap();
+    let mut terminal = Terminal {
+        input:  termion::async_stdin().events(),
+        output: if config.mouse { Box::new(MouseTerminal::from(output)) as Box<dyn Write> } else { Box::new(output) }
+    };
+
+    terminal.init();
+    terminal.flush();
+
+    // Buffers each frame is rendered and assembled into, kept across frames to reuse their allocations
+    let (mut text, mut frame) = (String::new(), String::new());
+
+    // Bytes written drawing each frame, and drawing it in full, for the stats shown on exit by debug builds
+    let (mut frames, mut bytes, mut full_bytes) = (0, 0, 0);
+
+    // Main loop, advancing generations by the time since the last update
+    let mut updated = Instant::now();
+    loop {
+        let start = Instant::now();
+
+        // Handle every key pressed and mouse event since the last frame, so that held keys do not queue up
+        for event in terminal.input.by_ref().flatten() {
+            match event {
+                Event::Key(key) => app.handle_key(key),
+                Event::Mouse(event) => app.handle_mouse(event),
+                Event::Unsupported(_) => {}
+            }
+        }
+        if app.quit {
+            break;
+        }
+
+        // Check if terminal size has changed, keeping the last size while the size cannot
+        // be read or has no rows or columns
+        if let Some(size) = usable_size(termion::terminal_size()) {
+            app.resize(size);
+        }
+
+        // Advance to the next Grid state if its time has come, unless paused
+        app.update(start - updated);
+        updated = start;
+
+        // Render the changes since the last frame to terminal, then the status line,
+        // filling the screen with the background of the theme first when drawing in full
+        let grid = app.simulation.grid();
+        let colours = app.colours();
+        if app.drawn.is_none() {
+            terminal.write(&app.pen.clear);
+        }
+        build_changes_frame(app.render, grid, &colours, &app.pen, app.drawn.as_ref(), &mut text, &mut frame);
+        match &app.stamp {
+            Some(Stamp::Picking(index)) => build_picker(*index, app.size, &mut frame),
+            Some(Stamp::Placing(cells, at)) => build_ghost(app.render, grid, cells, *at, &app.pen, &mut text, &mut frame),
+            None => if let Some(cursor) = app.cursor {
+                build_cursor(app.render, &text, cursor, &app.pen, &mut frame);
+            }
+        }
+        build_status(&app, &mut frame);
+        terminal.write(&frame);
+
+        if cfg!(debug_assertions) {
+            frames += 1;
+            bytes += frame.len();
+            build_frame(app.render, grid, &colours, &app.pen, &mut text, &mut frame);
+            build_status(&app, &mut frame);
+            full_bytes += frame.len();
+        }
+        app.drawn = Some(Drawn { grid: grid.clone(), colours });
+
+        // Flush terminal output
+        terminal.flush();
+
+        if app.quit {
+            break;
+        }
+
+        // Sleep for the rest of the frame
+        thread::sleep(time_to_next_frame(FRAME_TIME, start.elapsed()));
+    }
+
+    // Restore terminal and flush output
+    terminal.restore();
+    terminal.flush();
+
+    if cfg!(debug_assertions) && frames > 0 {
+        eprintln!("rs-life: wrote {} bytes per frame on average, {} drawing each frame in full", bytes / frames, full_bytes / frames);
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usable_size_should_reject_sizes_without_rows_or_columns() {
+        assert_eq!(usable_size(Ok((80, 24))), Some((80, 24)));
+        assert_eq!(usable_size(Ok((0, 0))), None);
+        assert_eq!(usable_size(Ok((0, 24))), None);
+        assert_eq!(usable_size(Ok((80, 0))), None);
+        assert_eq!(usable_size(Err(io::Error::other("not a terminal"))), None);
+    }
+
+    // Return the Pen drawing the classic theme in the 256 colours
+    fn classic() -> Pen {
+        Pen::new(&Theme::parse("classic").unwrap(), Depth::Ansi256)
+    }
+
+    // Return the colour of each char showing grid plainly in the classic theme
+    fn green(render: Render, grid: &Grid) -> Vec<Option<Colour>> {
+        char_colours(render, grid, &|_, _| classic().colour)
+    }
+
+    // Return what was drawn showing grid plainly
+    fn drawn(render: Render, grid: &Grid) -> Drawn {
+        Drawn { grid: grid.clone(), colours: green(render, grid) }
+    }
+
+    #[test]
+    fn build_frame_should_place_each_line_without_line_terminators() {
+        // given
+        let grid = Grid::from_text("O.\n.O\nOO", 'O').unwrap();
+        let (mut text, mut frame) = (String::new(), String::from("stale"));
+
+        // when
+        build_frame(Render::Text, &grid, &green(Render::Text, &grid), &classic(), &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{}{}O {} O{}OO{}", style::Bold, cursor::Goto(1, 1), color::Fg(color::Green),
+            cursor::Goto(1, 2), cursor::Goto(1, 3), style::Reset));
+        assert!(!frame.contains('\n') && !frame.contains('\r'));
+    }
+
+    #[test]
+    fn build_frame_should_place_a_line_per_row_of_chars_given_half_blocks() {
+        // given
+        let grid = Grid::random_seeded(5, 8, 7);
+        let (mut text, mut frame) = (String::new(), String::new());
+
+        // when
+        build_frame(Render::HalfBlocks, &grid, &green(Render::HalfBlocks, &grid), &classic(), &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame.matches("\x1b[").count(), 2 + 4 + 1);
+        assert!(frame.contains(&cursor::Goto(1, 4).to_string()) && !frame.contains(&cursor::Goto(1, 5).to_string()));
+        assert!(frame.ends_with(&style::Reset.to_string()));
+        assert!(!frame.contains('\n') && !frame.contains('\r'));
+    }
+
+    #[test]
+    fn build_changes_frame_should_write_runs_of_changed_chars_after_cursor_moves() {
+        // given
+        let drawn = drawn(Render::Text, &Grid::from_text("O....\n.....\n....O", 'O').unwrap());
+        let grid = Grid::from_text(".OO.O\n.....\nO...O", 'O').unwrap();
+        let (mut text, mut frame) = (String::new(), String::new());
+
+        // when
+        build_changes_frame(Render::Text, &grid, &green(Render::Text, &grid), &classic(), Some(&drawn), &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{} {}OO{}O{}O{}", style::Bold, cursor::Goto(1, 1), color::Fg(color::Green),
+            cursor::Goto(5, 1), cursor::Goto(1, 3), style::Reset));
+    }
+
+    #[test]
+    fn build_changes_frame_should_write_each_changed_char_once_given_braille() {
+        // given
+        let drawn = drawn(Render::Braille, &Grid::new(4, 8).unwrap());
+        let grid = Grid::from_coords(4, 8, vec![(0, 0), (1, 3), (2, 4), (3, 7)]).unwrap();
+        let (mut text, mut frame) = (String::new(), String::new());
+
+        // when
+        build_changes_frame(Render::Braille, &grid, &green(Render::Braille, &grid), &classic(), Some(&drawn), &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{}{}\u{2881}{}\u{2881}{}", style::Bold, cursor::Goto(1, 1), color::Fg(color::Green),
+            cursor::Goto(2, 2), style::Reset));
+    }
+
+    #[test]
+    fn build_changes_frame_should_write_nothing_given_no_changes() {
+        let grid = Grid::random_seeded(2, 10, 4);
+        let (mut text, mut frame) = (String::new(), String::from("stale"));
+        build_changes_frame(Render::Text, &grid, &green(Render::Text, &grid), &classic(), Some(&drawn(Render::Text, &grid)), &mut text,
+            &mut frame);
+        assert_eq!(frame, "");
+    }
+
+    #[test]
+    fn build_changes_frame_should_build_whole_frame_given_many_changes_or_new_size() {
+        // given
+        let grid = Grid::from_text("OO...\nOO...", 'O').unwrap();
+        let colours = green(Render::Text, &grid);
+        let (mut text, mut frame, mut full) = (String::new(), String::new(), String::new());
+        build_frame(Render::Text, &grid, &colours, &classic(), &mut text, &mut full);
+
+        // then
+        build_changes_frame(Render::Text, &grid, &colours, &classic(), None, &mut text, &mut frame);
+        assert_eq!(frame, full);
+        let many = drawn(Render::Text, &Grid::from_text("....O\n.....", 'O').unwrap());
+        build_changes_frame(Render::Text, &grid, &colours, &classic(), Some(&many), &mut text, &mut frame);
+        assert_eq!(frame, full);
+        let bigger = drawn(Render::Text, &Grid::new(5, 3).unwrap());
+        build_changes_frame(Render::Text, &grid, &colours, &classic(), Some(&bigger), &mut text, &mut frame);
+        assert_eq!(frame, full);
+        let few = drawn(Render::Text, &Grid::from_text("O....\nOO...", 'O').unwrap());
+        build_changes_frame(Render::Text, &grid, &colours, &classic(), Some(&few), &mut text, &mut frame);
+        assert_ne!(frame, full);
+    }
+
+    #[test]
+    fn build_frame_should_change_colour_only_between_runs_of_different_colours() {
+        // given
+        let grid = Grid::from_text("OOO.O\n....O", 'O').unwrap();
+        let red = Some(Colour::Basic(1));
+        let yellow = Some(Colour::Basic(11));
+        let colours = [red, red, yellow, None, yellow, None, None, None, None, yellow];
+        let (mut text, mut frame) = (String::new(), String::new());
+
+        // when
+        build_frame(Render::Text, &grid, &colours, &classic(), &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{}\x1b[31mOO\x1b[93mO O{}    O{}", style::Bold, cursor::Goto(1, 1), cursor::Goto(1, 2),
+            style::Reset));
+    }
+
+    #[test]
+    fn build_changes_frame_should_write_chars_changed_colour_given_same_cells() {
+        // given
+        let grid = Grid::from_text("OO.O", 'O').unwrap();
+        let drawn = drawn(Render::Text, &grid);
+        let mut colours = green(Render::Text, &grid);
+        colours[1] = Some(Colour::Rgb(255, 0, 0));
+        let (mut text, mut frame) = (String::new(), String::new());
+
+        // when
+        build_changes_frame(Render::Text, &grid, &colours, &classic(), Some(&drawn), &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{}{}O{}", style::Bold, cursor::Goto(2, 1), color::Fg(color::Rgb(255, 0, 0)), style::Reset));
+    }
+
+    #[test]
+    fn char_colours_should_give_colour_of_first_live_cell_of_each_char() {
+        // given
+        let grid = Grid::from_text("O..\nOO.\n..O", 'O').unwrap();
+
+        // when
+        let colours = char_colours(Render::HalfBlocks, &grid, &|x, y| Some(Colour::Ansi((10 * y + x) as u8)));
+
+        // then
+        assert_eq!(colours, vec![Some(Colour::Ansi(0)), Some(Colour::Ansi(11)), None, None, None, Some(Colour::Ansi(22))]);
+        assert_eq!(char_colours(Render::Text, &grid, &|_, _| None), vec![None; 9]);
+    }
+
+    #[test]
+    fn colour_should_write_escape_code_for_depth() {
+        assert_eq!(Colour::Basic(2).to_string(), "\x1b[32m");
+        assert_eq!(Colour::Basic(9).to_string(), "\x1b[91m");
+        assert_eq!(Colour::Ansi(2).to_string(), color::Fg(color::Green).to_string());
+        assert_eq!(Colour::Rgb(1, 2, 3).to_string(), "\x1b[38;2;1;2;3m");
+    }
+
+    #[test]
+    fn age_colour_should_map_ages_to_palette_of_depth() {
+        assert_eq!(age_colour(0, Depth::Ansi256), Some(Colour::Ansi(231)));
+        assert_eq!(age_colour(0, Depth::TrueColour), Some(Colour::Rgb(255, 255, 255)));
+        assert_eq!(age_colour(1, Depth::Ansi256), Some(Colour::Ansi(220)));
+        assert_eq!(age_colour(5, Depth::Basic), Some(Colour::Basic(3)));
+        assert_eq!(age_colour(5, Depth::Basic16), Some(Colour::Basic(11)));
+        assert_eq!(age_colour(0, Depth::Basic), Some(Colour::Basic(7)));
+        assert_eq!(age_colour(6, Depth::Ansi256), Some(Colour::Ansi(34)));
+        assert_eq!(age_colour(21, Depth::Ansi256), Some(Colour::Ansi(26)));
+        assert_eq!(age_colour(1000, Depth::Basic), Some(Colour::Basic(4)));
+        assert_eq!(age_colour(3, Depth::None), None);
+    }
+
+    #[test]
+    fn heat_colour_should_map_fractions_from_blue_to_red() {
+        assert_eq!(heat_colour(0.0, Depth::TrueColour), Some(Colour::Rgb(0, 95, 255)));
+        assert_eq!(heat_colour(0.5, Depth::TrueColour), Some(Colour::Rgb(255, 215, 0)));
+        assert_eq!(heat_colour(1.0, Depth::Ansi256), Some(Colour::Ansi(196)));
+        assert_eq!(heat_colour(0.1, Depth::Basic), Some(Colour::Basic(4)));
+        assert_eq!(heat_colour(0.9, Depth::Basic), Some(Colour::Basic(1)));
+        assert_eq!(heat_colour(0.9, Depth::Basic16), Some(Colour::Basic(9)));
+        assert_eq!(heat_colour(0.9, Depth::None), None);
+    }
+
+    #[test]
+    fn colour_depth_should_follow_choice_and_environment() {
+        // given
+        let env = |vars: &'static [(&'static str, &'static str)]| move |name: &str|
+            vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string());
+
+        // then
+        assert_eq!(colour_depth(ColourChoice::Auto, &env(&[("TERM", "xterm")])), Depth::Basic);
+        assert_eq!(colour_depth(ColourChoice::Auto, &env(&[("TERM", "xterm-16color")])), Depth::Basic16);
+        assert_eq!(colour_depth(ColourChoice::Auto, &env(&[("TERM", "xterm-256color")])), Depth::Ansi256);
+        assert_eq!(colour_depth(ColourChoice::Auto, &env(&[("TERM", "xterm"), ("COLORTERM", "truecolor")])), Depth::TrueColour);
+        assert_eq!(colour_depth(ColourChoice::Auto, &env(&[("TERM", "dumb")])), Depth::None);
+        assert_eq!(colour_depth(ColourChoice::Auto, &env(&[])), Depth::None);
+        assert_eq!(colour_depth(ColourChoice::Auto, &env(&[("TERM", "xterm"), ("NO_COLOR", "1")])), Depth::None);
+        assert_eq!(colour_depth(ColourChoice::Always, &env(&[("TERM", "dumb")])), Depth::Basic);
+        assert_eq!(colour_depth(ColourChoice::Never, &env(&[("TERM", "xterm-256color")])), Depth::None);
+    }
+
+    #[test]
+    fn colour_at_should_give_closest_colour_terminal_shows() {
+        assert_eq!(Colour::Rgb(255, 176, 0).at(Depth::TrueColour), Some(Colour::Rgb(255, 176, 0)));
+        assert_eq!(Colour::Rgb(255, 176, 0).at(Depth::Ansi256), Some(Colour::Ansi(214)));
+        assert_eq!(Colour::Rgb(0, 255, 65).at(Depth::Basic16), Some(Colour::Basic(10)));
+        assert_eq!(Colour::Rgb(0, 255, 65).at(Depth::Basic), Some(Colour::Basic(2)));
+        assert_eq!(Colour::Ansi(2).at(Depth::Basic), Some(Colour::Basic(2)));
+        assert_eq!(Colour::Ansi(196).at(Depth::Basic16), Some(Colour::Basic(9)));
+        assert_eq!(Colour::Ansi(196).at(Depth::Basic), Some(Colour::Basic(1)));
+        assert_eq!(Colour::Ansi(244).at(Depth::Basic16), Some(Colour::Basic(8)));
+        assert_eq!(Colour::Basic(15).at(Depth::Basic), Some(Colour::Basic(7)));
+        assert_eq!(Colour::Basic(4).at(Depth::None), None);
+    }
+
+    #[test]
+    fn parse_colour_should_give_colour_of_name_number_or_hex() {
+        assert_eq!(parse_colour("red"), Some(Colour::Basic(1)));
+        assert_eq!(parse_colour("bright-white"), Some(Colour::Basic(15)));
+        assert_eq!(parse_colour("208"), Some(Colour::Ansi(208)));
+        assert_eq!(parse_colour("#0080ff"), Some(Colour::Rgb(0, 128, 255)));
+        assert_eq!(parse_colour("256"), None);
+        assert_eq!(parse_colour("#08f"), None);
+        assert_eq!(parse_colour("#00€ff"), None);
+        assert_eq!(parse_colour("bright-orange"), None);
+    }
+
+    #[test]
+    fn theme_parse_should_give_built_in_and_custom_themes() {
+        assert_eq!(Theme::parse("classic").unwrap().foreground, Some(Colour::Ansi(2)));
+        assert_eq!(Theme::parse("mono").unwrap().foreground, None);
+        assert_eq!(Theme::parse("amber").unwrap().background, Some(Colour::Basic(0)));
+
+        let custom = Theme::parse("custom:yellow,#000020").unwrap();
+        assert_eq!((custom.foreground, custom.background), (Some(Colour::Basic(3)), Some(Colour::Rgb(0, 0, 32))));
+        assert_eq!(custom.charset, CharSet::DEFAULT);
+
+        assert_eq!(Theme::parse("neon"), Err("unknown theme: neon, not one of classic, matrix, mono, amber or custom:FG,BG".to_string()));
+        assert!(Theme::parse("custom:yellow").is_err());
+        assert!(Theme::parse("custom:yellow,mauve").unwrap_err().starts_with("unknown colour: mauve"));
+    }
+
+    #[test]
+    fn parse_chars_should_take_one_column_chars_with_optional_space_between() {
+        assert_eq!(parse_chars("\u{2588} "), Ok(CharSet::new(' ', '\u{2588}')));
+        assert_eq!(parse_chars("# ."), Ok(CharSet::new('.', '#')));
+        assert_eq!(parse_chars("#."), Ok(CharSet::new('.', '#')));
+        assert!(parse_chars("#").is_err());
+        assert!(parse_chars("# . ").is_err());
+        assert_eq!(parse_chars("\u{4e16}."), Err("--chars: '\u{4e16}' is 2 columns wide, so cells drawn with it would not line up".to_string()));
+        assert!(parse_chars("\u{1f600} ").is_err());
+        assert_eq!(parse_chars("\u{1f680} "), Err("--chars: '\u{1f680}' is 2 columns wide, so cells drawn with it would not line up".to_string()));
+        assert!(parse_chars("\u{1fa70} ").is_err());
+        assert!(parse_chars("\u{2614} ").is_err());
+        assert!(parse_chars("\u{301}.").unwrap_err().contains("takes no columns"));
+        assert!(parse_chars("\t.").is_err());
+    }
+
+    #[test]
+    fn pen_should_work_out_codes_of_theme_for_depth() {
+        // when
+        let pen = Pen::new(&Theme::parse("amber").unwrap(), Depth::Ansi256);
+
+        // then
+        assert_eq!(pen.colour, Some(Colour::Ansi(214)));
+        assert_eq!(pen.bold, format!("{}\x1b[40m", style::Bold));
+        assert_eq!(pen.faint, format!("{}\x1b[40m", style::Faint));
+        assert_eq!(pen.clear, format!("\x1b[40m{}{}", clear::All, style::Reset));
+        assert_eq!(Pen::new(&Theme::parse("amber").unwrap(), Depth::None).bold, style::Bold.to_string());
+    }
+
+    #[test]
+    fn build_frame_should_draw_chars_of_theme_on_its_background() {
+        // given
+        let mut theme = Theme::parse("custom:bright-green,blue").unwrap();
+        theme.charset = CharSet::new('.', '#');
+        let pen = Pen::new(&theme, Depth::Basic);
+        let grid = Grid::from_text("O.O", 'O').unwrap();
+        let (mut text, mut frame) = (String::new(), String::new());
+
+        // when
+        build_frame(Render::Text, &grid, &char_colours(Render::Text, &grid, &|_, _| pen.colour), &pen, &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}\x1b[44m{}\x1b[32m#\x1b[39m.\x1b[32m#{}", style::Bold, cursor::Goto(1, 1), style::Reset));
+    }
+
+    #[test]
+    fn build_changes_frame_should_draw_dead_chars_in_default_colour_as_build_frame_does() {
+        // given
+        let mut theme = Theme::parse("classic").unwrap();
+        theme.charset = CharSet::new('.', '#');
+        let pen = Pen::new(&theme, Depth::Ansi256);
+        let grid = Grid::from_text("OO.O......", 'O').unwrap();
+        let colours = char_colours(Render::Text, &grid, &|_, _| pen.colour);
+        let drawn = Drawn { grid: Grid::from_text("O.OO......", 'O').unwrap(), colours: colours.clone() };
+        let (mut text, mut frame, mut changes) = (String::new(), String::new(), String::new());
+
+        // when
+        build_frame(Render::Text, &grid, &colours, &pen, &mut text, &mut frame);
+        build_changes_frame(Render::Text, &grid, &colours, &pen, Some(&drawn), &mut text, &mut changes);
+
+        // then
+        assert_eq!(frame, format!("{}{}\x1b[38;5;2m##\x1b[39m.\x1b[38;5;2m#\x1b[39m......{}", style::Bold, cursor::Goto(1, 1),
+            style::Reset));
+        assert_eq!(changes, format!("{}{}\x1b[38;5;2m#\x1b[39m.{}", style::Bold, cursor::Goto(2, 1), style::Reset));
+    }
+
+    #[test]
+    fn themes_should_cycle_from_theme_given_with_chars_given() {
+        // when
+        let (built_in, index) = themes(&Theme::parse("mono").unwrap(), None);
+        let (custom, custom_index) = themes(&Theme::parse("custom:1,2").unwrap(), Some(CharSet::new('.', '#')));
+
+        // then
+        assert_eq!(built_in.iter().map(|theme| theme.name.as_str()).collect::<Vec<&str>>(), Theme::NAMES);
+        assert_eq!((index, built_in[0].charset), (2, CharSet::DEFAULT));
+        assert_eq!((custom.len(), custom_index), (5, 4));
+        assert_eq!(custom[4].foreground, Some(Colour::Ansi(1)));
+        assert!(custom.iter().all(|theme| theme.charset == CharSet::new('.', '#')));
+    }
+
+    fn args(args: &[&str]) -> Result<Config, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    // Return an app started by given arguments in a terminal of given size
+    fn started_app(arguments: &[&str], size: (u16, u16)) -> App {
+        let config = args(arguments).unwrap();
+        App::new(&config, start(&config).unwrap(), size, Depth::Ansi256).unwrap()
+    }
+
+    // Return an app paused on a blinker in a 5x6 terminal
+    fn paused_blinker_app() -> App {
+        let mut app = started_app(&[], (5, 6));
+        app.simulation = Simulation::new(Grid::from_text(".....\n.....\n.OOO.\n.....\n.....", 'O').unwrap(), Rule::CONWAY)
+            .with_heatmap().with_ages();
+        app.handle_key(Key::Char(' '));
+        app
+    }
+
+    #[test]
+    fn update_should_not_advance_given_paused() {
+        // given
+        let mut app = paused_blinker_app();
+
+        // when
+        app.update(app.generation_time());
+        app.update(app.generation_time());
+
+        // then
+        assert_eq!(app.state, State::Paused);
+        assert_eq!(app.simulation.generation(), 0);
+    }
+
+    #[test]
+    fn update_should_advance_one_generation_per_step_key_given_paused() {
+        // given
+        let mut app = paused_blinker_app();
+        let grid = app.simulation.grid().clone();
+
+        // when
+        app.handle_key(Key::Char('n'));
+        app.update(app.generation_time());
+
+        // then
+        assert_eq!(app.simulation.generation(), 1);
+        assert_eq!(app.simulation.grid(), &grid.next_with_rule(&Rule::CONWAY));
+        app.update(app.generation_time());
+        assert_eq!(app.simulation.generation(), 1);
+
+        app.handle_key(Key::Char('.'));
+        app.handle_key(Key::Char('n'));
+        app.update(app.generation_time());
+        assert_eq!(app.simulation.generation(), 3);
+    }
+
+    #[test]
+    fn handle_key_should_resume_and_ignore_step_keys_given_running() {
+        // given
+        let mut app = paused_blinker_app();
+
+        // when
+        app.handle_key(Key::Char(' '));
+        app.handle_key(Key::Char('n'));
+        app.update(app.generation_time());
+
+        // then
+        assert_eq!(app.state, State::Running);
+        assert_eq!(app.simulation.generation(), 1);
+        assert!(!app.quit);
+        app.handle_key(Key::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn handle_key_should_double_and_halve_speed_within_range() {
+        // given
+        let mut app = started_app(&[], (5, 6));
+
+        // when
+        app.handle_key(Key::Char('+'));
+        app.handle_key(Key::Char(']'));
+
+        // then
+        assert_eq!(app.speed, 40.0);
+        app.handle_key(Key::Char('+'));
+        assert_eq!(app.speed, MAX_SPEED);
+        for _ in 0..8 {
+            app.handle_key(Key::Char('-'));
+        }
+        assert_eq!(app.speed, MIN_SPEED);
+        app.handle_key(Key::Char('['));
+        assert_eq!(app.speed, MIN_SPEED);
+        assert_eq!(app.generation_time(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn update_should_advance_once_time_of_generation_has_run_given_running() {
+        // given
+        let mut app = started_app(&["--fps", "0.5"], (10, 10));
+
+        // when
+        for _ in 0..119 {
+            app.update(FRAME_TIME);
+        }
+
+        // then
+        assert_eq!(app.simulation.generation(), 0);
+        app.update(FRAME_TIME);
+        assert_eq!(app.simulation.generation(), 1);
+        app.update(Duration::from_secs(60));
+        assert_eq!(app.simulation.generation(), 2);
+        app.update(FRAME_TIME);
+        app.update(FRAME_TIME);
+        assert_eq!(app.simulation.generation(), 3);
+    }
+
+    #[test]
+    fn time_to_next_frame_should_give_rest_of_frame_time() {
+        let frame_time = Duration::from_millis(100);
+        assert_eq!(time_to_next_frame(frame_time, Duration::from_millis(30)), Duration::from_millis(70));
+        assert_eq!(time_to_next_frame(frame_time, Duration::ZERO), frame_time);
+    }
+
+    #[test]
+    fn time_to_next_frame_should_give_zero_given_overrun_frame() {
+        let frame_time = Duration::from_millis(100);
+        assert_eq!(time_to_next_frame(frame_time, frame_time), Duration::ZERO);
+        assert_eq!(time_to_next_frame(frame_time, Duration::from_millis(250)), Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_args_should_give_defaults_given_no_arguments() {
+        // when
+        let config = args(&[]).unwrap();
+
+        // then
+        assert_eq!(config, Config::default());
+        assert_eq!((config.render, config.rule, config.speed), (Render::Text, None, DEFAULT_SPEED));
+        assert_eq!((config.width, config.height, config.density, config.seed), (None, None, None, None));
+        assert_eq!((config.file, config.generations), (None, None));
+    }
+
+    #[test]
+    fn parse_args_should_override_defaults_given_arguments() {
+        // when
+        let config = args(&["--render", "braille", "--width", "40", "--height", "30", "--density", "0.3",
+            "--seed", "12345", "--rule", "B36/S23", "--boundary", "mirror", "--fps", "2.5", "--generations", "100", "--color", "always",
+            "--theme", "custom:#ff8000,default", "--chars", "# ."]).unwrap();
+
+        // then
+        assert_eq!(config, Config {
+            render: Render::Braille,
+            width: Some(40),
+            height: Some(30),
+            density: Some(0.3),
+            seed: Some(12345),
+            rule: Some(Rule::parse("B36/S23").unwrap()),
+            boundary: Boundary::Mirror,
+            speed: 2.5,
+            file: None,
+            generations: Some(100),
+            mouse: true,
+            colour: ColourChoice::Always,
+            theme: Theme { name: "custom:#ff8000,default".to_string(), foreground: Some(Colour::Rgb(255, 128, 0)), background: None,
+                           charset: CharSet::DEFAULT },
+            chars: Some(CharSet::new('.', '#'))
+        });
+        assert!(!args(&["--no-mouse"]).unwrap().mouse);
+        assert_eq!(args(&["--file", "gosper.rle"]).unwrap().file.as_deref(), Some("gosper.rle"));
+    }
+
+    #[test]
+    fn parse_args_should_give_error_given_bad_values() {
+        assert_eq!(args(&["--fps", "61"]), Err("--fps needs a number of generations per second from 0.5 to 60, not '61'".to_string()));
+        assert_eq!(args(&["--fps"]), Err("--fps needs a number of generations per second from 0.5 to 60".to_string()));
+        assert!(args(&["--fps", "0.25"]).is_err());
+        assert!(args(&["--width", "0"]).is_err());
+        assert!(args(&["--height", "tall"]).is_err());
+        assert!(args(&["--density", "1.5"]).is_err());
+        assert!(args(&["--seed", "-1"]).is_err());
+        assert!(args(&["--rule", "B3/S9"]).unwrap_err().starts_with("--rule B3/S9: "));
+        assert!(args(&["--generations", "many"]).is_err());
+        assert!(args(&["--render", "ascii"]).is_err());
+        assert_eq!(args(&["--boundary", "klein"]), Err("unknown boundary: klein".to_string()));
+        assert_eq!(args(&["--color", "sometimes"]), Err("unknown color choice: sometimes".to_string()));
+        assert!(args(&["--colour"]).unwrap_err().starts_with("unknown argument: --colour\nusage: "));
+    }
+
+    #[test]
+    fn parse_args_should_give_error_given_file_with_random_cells() {
+        assert!(args(&["--file", "gosper.rle", "--density", "0.3"]).is_err());
+        assert!(args(&["--seed", "1", "--file", "gosper.rle"]).is_err());
+    }
+
+    #[test]
+    fn app_should_start_from_seed_with_density_in_grid_of_given_size() {
+        // when
+        let app = started_app(&["--seed", "7", "--density", "0.25", "--width", "30", "--height", "10"], (80, 24));
+
+        // then
+        assert_eq!(app.simulation.grid(), &Grid::random_seeded_with_density(7, 30, 10, 0.25));
+        assert_eq!(app.grid_size(), (30, 10));
+    }
+
+    #[test]
+    fn app_should_give_error_given_grid_bigger_than_terminal() {
+        // given
+        let config = args(&["--render", "halfblocks", "--width", "80", "--height", "47"]).unwrap();
+        let seed = || Start::Random { density: 0.5, seed: 1 };
+
+        // then
+        assert!(App::new(&config, seed(), (80, 24), Depth::Ansi256).is_err());
+        assert!(App::new(&config, seed(), (80, 25), Depth::Ansi256).is_ok());
+        assert!(App::new(&config, seed(), (79, 25), Depth::Ansi256).is_err());
+    }
+
+    // Return the path of a pattern in the library's catalogue
+    fn catalogue_path(name: &str) -> String {
+        format!("{}/src/patterns/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn app_should_start_paused_with_pattern_file_in_centre() {
+        // given
+        let path = catalogue_path("glider.rle");
+
+        // when
+        let app = started_app(&["--file", &path], (20, 11));
+
+        // then
+        let glider = load_pattern(&path).unwrap();
+        let grid = app.simulation.grid();
+        assert_eq!((grid.width(), grid.height(), grid.population()), (20, 10, 5));
+        assert_eq!(&grid.extract(8, 3, 3, 3).unwrap(), glider.cells());
+        assert_eq!(app.state, State::Paused);
+        assert_eq!(app.simulation.rule(), &Rule::CONWAY);
+    }
+
+    #[test]
+    fn app_should_run_rule_given_in_place_of_pattern_file_rule() {
+        let app = started_app(&["--file", &catalogue_path("glider.rle"), "--rule", "B36/S23"], (20, 11));
+        assert_eq!(app.simulation.rule(), &Rule::parse("B36/S23").unwrap());
+    }
+
+    #[test]
+    fn app_should_give_error_with_terminal_size_needed_given_too_big_pattern() {
+        // given
+        let config = args(&["--file", &catalogue_path("gosper-glider-gun.rle")]).unwrap();
+        let gun = || start(&config).unwrap();
+
+        // then
+        assert_eq!(App::new(&config, gun(), (30, 10), Depth::Ansi256).err(),
+            Some("the pattern of 36x9 cells does not fit in the 30x9 shown, needing a terminal of at least 36x10 chars".to_string()));
+        assert!(App::new(&config, gun(), (36, 10), Depth::Ansi256).is_ok());
+
+        let config = args(&["--file", &catalogue_path("gosper-glider-gun.rle"), "--render", "braille", "--width", "20"]).unwrap();
+        assert_eq!(App::new(&config, start(&config).unwrap(), (30, 10), Depth::Ansi256).err(),
+            Some("the pattern of 36x9 cells does not fit in the 20x36 shown, needing a larger --width and --height".to_string()));
+    }
+
+    #[test]
+    fn load_pattern_should_give_error_with_path_and_line_number() {
+        // given
+        let path = std::env::temp_dir().join(format!("rs-life-{}-bad.rle", std::process::id()));
+        fs::write(&path, "#N Bad\nx = 3, y = 1\nbzo!\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        // when
+        let error = load_pattern(path);
+        fs::remove_file(path).unwrap();
+
+        // then
+        assert_eq!(error.err(), Some(format!("{}: line 3: unexpected 'z'", path)));
+        assert!(load_pattern("missing.rle").unwrap_err().starts_with("cannot read missing.rle: "));
+    }
+
+    #[test]
+    fn handle_key_should_pause_with_cursor_in_centre_given_e() {
+        // given
+        let mut app = started_app(&[], (9, 6));
+
+        // when
+        app.handle_key(Key::Char('e'));
+        app.update(app.generation_time());
+
+        // then
+        assert_eq!(app.cursor, Some((4, 2)));
+        assert_eq!(app.state, State::Paused);
+        assert_eq!(app.simulation.generation(), 0);
+        assert!(format_status(&Status::of(&app), 100).trim_end().ends_with(" | Editing"));
+
+        app.handle_key(Key::Char(' '));
+        assert_eq!(app.state, State::Paused);
+    }
+
+    #[test]
+    fn handle_edit_key_should_move_cursor_around_edges() {
+        // given
+        let mut app = started_app(&[], (4, 4));
+        app.handle_key(Key::Char('e'));
+        let moves = |app: &mut App, keys: &[Key]| {
+            for &key in keys {
+                app.handle_key(key);
+            }
+            app.cursor.unwrap()
+        };
+
+        // then
+        assert_eq!(app.cursor, Some((2, 1)));
+        assert_eq!(moves(&mut app, &[Key::Right, Key::Char('l')]), (0, 1));
+        assert_eq!(moves(&mut app, &[Key::Left]), (3, 1));
+        assert_eq!(moves(&mut app, &[Key::Char('h'), Key::Char('h'), Key::Char('h')]), (0, 1));
+        assert_eq!(moves(&mut app, &[Key::Up, Key::Char('k')]), (0, 2));
+        assert_eq!(moves(&mut app, &[Key::Down, Key::Char('j')]), (0, 1));
+        assert!(app.drawn.is_none());
+    }
+
+    #[test]
+    fn handle_edit_key_should_toggle_cell_at_cursor() {
+        // given
+        let mut app = started_app(&[], (4, 4));
+        app.handle_key(Key::Char('c'));
+        app.handle_key(Key::Char('e'));
+
+        // when
+        app.handle_key(Key::Char('x'));
+        app.handle_key(Key::Right);
+        app.handle_key(Key::Char('\n'));
+        app.handle_key(Key::Char('\n'));
+
+        // then
+        assert_eq!(app.simulation.grid(), &Grid::from_coords(4, 3, vec![(2, 1)]).unwrap());
+    }
+
+    #[test]
+    fn handle_edit_key_should_run_again_given_esc_or_e() {
+        for key in [Key::Esc, Key::Char('e')] {
+            // given
+            let mut app = started_app(&[], (4, 4));
+            app.handle_key(Key::Char('e'));
+
+            // when
+            app.handle_key(key);
+
+            // then
+            assert_eq!((app.cursor, app.state, app.quit), (None, State::Running, false));
+        }
+    }
+
+    #[test]
+    fn set_grid_should_keep_cursor_in_grid() {
+        // given
+        let mut app = started_app(&[], (9, 6));
+        app.handle_key(Key::Char('e'));
+
+        // when
+        app.resize((3, 3));
+
+        // then
+        assert_eq!(app.cursor, Some((2, 1)));
+        app.resize((3, 1));
+        assert_eq!(app.cursor, None);
+    }
+
+    #[test]
+    fn cell_at_should_give_cell_at_top_left_of_char() {
+        assert_eq!(cell_at(Render::Text, (80, 23), (1, 1)), Some((0, 0)));
+        assert_eq!(cell_at(Render::Text, (80, 23), (80, 23)), Some((79, 22)));
+        assert_eq!(cell_at(Render::HalfBlocks, (80, 46), (3, 2)), Some((2, 2)));
+        assert_eq!(cell_at(Render::Braille, (160, 92), (2, 3)), Some((2, 8)));
+    }
+
+    #[test]
+    fn cell_at_should_give_none_outside_grid() {
+        assert_eq!(cell_at(Render::Text, (80, 23), (0, 1)), None);
+        assert_eq!(cell_at(Render::Text, (80, 23), (1, 0)), None);
+        assert_eq!(cell_at(Render::Text, (80, 23), (81, 1)), None);
+        // Along the status line of a terminal 24 rows high
+        assert_eq!(cell_at(Render::Text, (80, 23), (1, 24)), None);
+        assert_eq!(cell_at(Render::HalfBlocks, (80, 46), (1, 24)), None);
+        assert_eq!(cell_at(Render::Braille, (60, 20), (31, 1)), None);
+    }
+
+    #[test]
+    fn line_should_give_positions_between_ends_without_gaps() {
+        assert_eq!(line((3, 4), (3, 4)), vec![(3, 4)]);
+        assert_eq!(line((1, 1), (4, 1)), vec![(1, 1), (2, 1), (3, 1), (4, 1)]);
+        assert_eq!(line((2, 2), (4, 4)), vec![(2, 2), (3, 3), (4, 4)]);
+        assert_eq!(line((5, 1), (1, 3)), vec![(5, 1), (4, 2), (3, 2), (2, 3), (1, 3)]);
+        assert_eq!(line((1, 6), (2, 1)), vec![(1, 6), (1, 5), (1, 4), (2, 3), (2, 2), (2, 1)]);
+
+        let positions = line((40, 3), (2, 17));
+        assert_eq!((positions[0], positions[positions.len() - 1], positions.len()), ((40, 3), (2, 17), 39));
+        assert!(positions.windows(2).all(|pair| pair[0].0.abs_diff(pair[1].0) <= 1 && pair[0].1.abs_diff(pair[1].1) <= 1));
+    }
+
+    #[test]
+    fn handle_mouse_should_paint_cells_along_drag() {
+        // given
+        let mut app = started_app(&[], (10, 8));
+        app.handle_key(Key::Char('c'));
+
+        // when
+        app.handle_mouse(MouseEvent::Press(MouseButton::Left, 1, 1));
+        app.handle_mouse(MouseEvent::Hold(4, 4));
+        app.handle_mouse(MouseEvent::Release(4, 4));
+        app.handle_mouse(MouseEvent::Hold(6, 4));
+
+        // then
+        assert_eq!(app.simulation.grid(), &Grid::from_coords(10, 7, vec![(0, 0), (1, 1), (2, 2), (3, 3)]).unwrap());
+
+        app.handle_mouse(MouseEvent::Press(MouseButton::Right, 2, 2));
+        app.handle_mouse(MouseEvent::Hold(3, 3));
+        assert_eq!(app.simulation.grid(), &Grid::from_coords(10, 7, vec![(0, 0), (3, 3)]).unwrap());
+    }
+
+    #[test]
+    fn handle_mouse_should_paint_cells_of_char_and_ignore_status_line() {
+        // given
+        let mut app = started_app(&["--render", "braille"], (3, 3));
+        app.handle_key(Key::Char('c'));
+
+        // when
+        app.handle_mouse(MouseEvent::Press(MouseButton::Left, 2, 2));
+        app.handle_mouse(MouseEvent::Press(MouseButton::Left, 2, 3));
+        app.handle_mouse(MouseEvent::Press(MouseButton::WheelUp, 1, 1));
+        app.handle_mouse(MouseEvent::Hold(1, 1));
+
+        // then
+        let grid = app.simulation.grid();
+        assert_eq!((grid.width(), grid.height(), grid.population()), (6, 8, 8));
+        assert_eq!(grid.extract(2, 4, 2, 4).unwrap().population(), 8);
+    }
+
+    #[test]
+    fn build_cursor_should_write_char_at_cursor_in_inverse_video() {
+        // given
+        let grid = Grid::from_coords(4, 8, vec![(1, 7)]).unwrap();
+        let (mut text, mut frame) = (String::new(), String::new());
+        Render::Braille.render_into(&grid, &CharSet::DEFAULT, &mut text);
+
+        // when
+        build_cursor(Render::Braille, &text, (0, 6), &classic(), &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{}{}{}\u{2880}{}", cursor::Goto(1, 2), style::Bold, color::Fg(color::Green),
+            style::Invert, style::Reset));
+    }
+
+    #[test]
+    fn handle_stamp_key_should_pick_pattern_from_library() {
+        // given
+        let mut app = started_app(&[], (20, 11));
+        let last = patterns::all().len() - 1;
+
+        // when
+        app.handle_key(Key::Char('p'));
+
+        // then
+        assert!(matches!(app.stamp, Some(Stamp::Picking(0))));
+        assert_eq!(app.activity(), "Stamping");
+        app.handle_key(Key::Up);
+        assert!(matches!(app.stamp, Some(Stamp::Picking(index)) if index == last));
+        app.handle_key(Key::Char('j'));
+        app.handle_key(Key::Down);
+        assert!(matches!(app.stamp, Some(Stamp::Picking(1))));
+
+        app.handle_key(Key::Char('\n'));
+        let lwss = patterns::by_name("lwss").unwrap().cells();
+        assert!(matches!(&app.stamp, Some(Stamp::Placing(cells, (8, 3))) if cells == lwss));
+    }
+
+    #[test]
+    fn handle_stamp_key_should_cancel_given_esc() {
+        // given
+        let mut app = started_app(&[], (20, 11));
+        let grid = app.simulation.grid().clone();
+
+        // when
+        app.handle_key(Key::Char('p'));
+        app.handle_key(Key::Esc);
+
+        // then
+        assert!(app.stamp.is_none() && !app.quit);
+        app.handle_key(Key::Char('p'));
+        app.handle_key(Key::Char('\n'));
+        app.handle_key(Key::Right);
+        app.handle_key(Key::Esc);
+        assert!(app.stamp.is_none() && !app.quit);
+        assert_eq!(app.simulation.grid(), &grid);
+    }
+
+    #[test]
+    fn handle_stamp_key_should_pass_on_other_keys() {
+        // given
+        let mut app = started_app(&[], (20, 11));
+        app.handle_key(Key::Char('p'));
+
+        // when
+        app.handle_key(Key::Char(' '));
+        app.handle_key(Key::Char('\n'));
+        app.handle_key(Key::Char('+'));
+
+        // then
+        assert_eq!(app.state, State::Paused);
+        assert_eq!(app.speed, 2.0 * DEFAULT_SPEED);
+        assert!(matches!(app.stamp, Some(Stamp::Placing(..))));
+    }
+
+    #[test]
+    fn handle_stamp_key_should_rotate_move_and_stamp_pattern_across_edges() {
+        // given
+        let mut app = started_app(&[], (20, 11));
+        app.handle_key(Key::Char('c'));
+        app.handle_key(Key::Char('p'));
+        app.handle_key(Key::Char('\n'));
+        let glider = patterns::by_name("glider").unwrap().cells();
+
+        // when
+        app.handle_key(Key::Char(']'));
+        for _ in 0..11 {
+            app.handle_key(Key::Char('l'));
+        }
+        for _ in 0..5 {
+            app.handle_key(Key::Up);
+        }
+
+        // then
+        assert!(matches!(&app.stamp, Some(Stamp::Placing(cells, (0, 9))) if cells == &glider.rotate_cw()));
+        app.handle_key(Key::Left);
+        assert_eq!(app.simulation.grid().population(), 0);
+        app.handle_key(Key::Char('\n'));
+        assert!(app.stamp.is_none());
+
+        let mut grid = Grid::new(20, 10).unwrap();
+        grid.insert(&glider.rotate_cw(), 19, 9, InsertMode::Replace, Overflow::Wrap);
+        assert_eq!(app.simulation.grid(), &grid);
+        assert_eq!(grid.population(), 5);
+    }
+
+    #[test]
+    fn composite_should_stamp_copy_across_edges() {
+        // given
+        let grid = Grid::from_text("....\n.O..\n....", 'O').unwrap();
+        let cells = Grid::from_text("OO\n.O", 'O').unwrap();
+
+        // when
+        let composite = composite(&grid, &cells, (3, 2));
+
+        // then
+        assert_eq!(composite, Grid::from_text("O...\n.O..\nO..O", 'O').unwrap());
+        assert_eq!(grid, Grid::from_text("....\n.O..\n....", 'O').unwrap());
+    }
+
+    #[test]
+    fn build_ghost_should_write_dim_chars_covered_by_pattern() {
+        // given
+        let grid = Grid::from_text("....\nO...\n....", 'O').unwrap();
+        let cells = Grid::from_text("O.\n.O", 'O').unwrap();
+        let (mut text, mut frame) = (String::new(), String::new());
+
+        // when
+        build_ghost(Render::Text, &grid, &cells, (3, 2), &classic(), &mut text, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{}{}O{} {} {}O{}", style::Faint, color::Fg(color::Green),
+            cursor::Goto(1, 1), cursor::Goto(4, 1), cursor::Goto(1, 3), cursor::Goto(4, 3), style::Reset));
+    }
+
+    #[test]
+    fn build_picker_should_scroll_to_show_pattern_picked() {
+        // given
+        let mut frame = String::new();
+        let names: Vec<&str> = patterns::all().iter().map(|pattern| pattern.name.as_deref().unwrap()).collect();
+
+        // when
+        build_picker(4, (14, 5), &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{} {:<11}{}{} {:<11}{}{}{} {:<11.11}{}", cursor::Goto(2, 2), names[2], style::Reset,
+            cursor::Goto(2, 3), names[3], style::Reset, cursor::Goto(2, 4), style::Invert, names[4], style::Reset));
+    }
+
+    #[test]
+    fn handle_key_should_replay_run_from_seed_given_capital_r() {
+        // given
+        let mut app = started_app(&["--seed", "9", "--density", "0.3"], (20, 11));
+        app.update(app.generation_time());
+        app.update(app.generation_time());
+
+        // when
+        app.handle_key(Key::Char('R'));
+
+        // then
+        assert_eq!(app.simulation.grid(), &Grid::random_seeded_with_density(9, 20, 10, 0.3));
+        assert_eq!(app.simulation.generation(), 0);
+        assert_eq!(app.start.seed(), Some(9));
+        assert!(app.drawn.is_none());
+    }
+
+    #[test]
+    fn handle_key_should_start_from_new_seed_at_density_given_r() {
+        // given
+        let mut app = started_app(&["--seed", "9", "--density", "0.3"], (20, 11));
+        app.update(app.generation_time());
+
+        // when
+        app.handle_key(Key::Char('r'));
+
+        // then
+        let seed = app.start.seed().unwrap();
+        assert_ne!(seed, 9);
+        assert_eq!(app.simulation.grid(), &Grid::random_seeded_with_density(seed, 20, 10, 0.3));
+        assert_eq!(app.simulation.generation(), 0);
+    }
+
+    #[test]
+    fn handle_key_should_start_from_random_cells_given_r_after_pattern() {
+        // given
+        let mut app = started_app(&["--file", &catalogue_path("glider.rle")], (20, 11));
+
+        // when
+        app.handle_key(Key::Char('r'));
+
+        // then
+        let seed = app.start.seed().unwrap();
+        assert_eq!(app.simulation.grid(), &Grid::random_seeded_with_density(seed, 20, 10, DEFAULT_DENSITY));
+        assert_eq!(app.state, State::Paused);
+    }
+
+    #[test]
+    fn handle_key_should_clear_grid_given_c() {
+        // given
+        let mut app = started_app(&[], (20, 11));
+        app.update(app.generation_time());
+
+        // when
+        app.handle_key(Key::Char('c'));
+
+        // then
+        let grid = app.simulation.grid();
+        assert_eq!((grid.width(), grid.height(), grid.population()), (20, 10, 0));
+        assert_eq!(app.simulation.generation(), 0);
+
+        app.handle_key(Key::Char('R'));
+        assert_eq!(app.simulation.grid(), &app.start.grid(20, 10));
+    }
+
+    #[test]
+    fn update_should_quit_after_generations_given() {
+        // given
+        let mut app = started_app(&["--generations", "2"], (10, 10));
+
+        // when
+        app.update(app.generation_time());
+        assert!(!app.quit);
+        app.update(app.generation_time());
+
+        // then
+        assert!(app.quit);
+        assert_eq!(app.simulation.generation(), 2);
+
+        let mut unstepped = started_app(&["--generations", "0"], (10, 10));
+        unstepped.update(unstepped.generation_time());
+        assert!(unstepped.quit);
+        assert_eq!(unstepped.simulation.generation(), 0);
+    }
+
+    #[test]
+    fn update_should_count_generations_each_cell_has_been_alive() {
+        // given
+        let mut app = paused_blinker_app();
+        app.colouring = Colouring::Age;
+
+        // when
+        app.handle_key(Key::Char('n'));
+        app.handle_key(Key::Char('n'));
+        app.update(app.generation_time());
+
+        // then
+        let ages: Vec<Option<u32>> = (0..5).map(|x| app.simulation.age(x, 2)).collect();
+        assert_eq!((ages, app.simulation.age(2, 1)), (vec![None, Some(0), Some(2), Some(0), None], None));
+        let colours = app.colours();
+        assert_eq!((colours[11], colours[12], colours[2]), (Some(Colour::Ansi(231)), Some(Colour::Ansi(220)), None));
+    }
+
+    #[test]
+    fn handle_key_should_cycle_colouring_and_draw_in_full_given_t() {
+        // given
+        let mut app = started_app(&["--seed", "4"], (10, 10));
+        app.drawn = Some(drawn(app.render, app.simulation.grid()));
+
+        // when
+        app.handle_key(Key::Char('t'));
+
+        // then
+        assert_eq!(app.colouring, Colouring::Age);
+        assert!(app.drawn.is_none());
+        app.handle_key(Key::Char('t'));
+        assert_eq!(app.colouring, Colouring::Heatmap);
+        app.handle_key(Key::Char('t'));
+        assert_eq!(app.colouring, Colouring::Plain);
+    }
+
+    #[test]
+    fn handle_key_should_cycle_themes_and_draw_in_full_given_capital_t() {
+        // given
+        let mut app = started_app(&["--theme", "amber", "--chars", "# ."], (10, 10));
+        app.drawn = Some(drawn(app.render, app.simulation.grid()));
+        assert_eq!((app.theme, app.pen.colour), (3, Some(Colour::Ansi(214))));
+
+        // when
+        app.handle_key(Key::Char('T'));
+
+        // then
+        assert_eq!((app.theme, app.pen.colour, app.pen.charset), (0, Some(Colour::Ansi(2)), CharSet::new('.', '#')));
+        assert!(app.drawn.is_none());
+    }
+
+    #[test]
+    fn prompt_line_should_edit_line_until_enter_leaving_later_keys() {
+        // given
+        let mut line = String::from("B3");
+        let mut keys = vec![Key::Char('6'), Key::Char('x'), Key::Backspace, Key::Left, Key::Char('/'), Key::Char('\n'), Key::Char('q')]
+            .into_iter();
+
+        // when
+        let prompted = prompt_line(&mut line, &mut keys);
+
+        // then
+        assert_eq!((prompted, line.as_str()), (Prompted::Accepted, "B36/"));
+        assert_eq!(keys.collect::<Vec<Key>>(), vec![Key::Char('q')]);
+    }
+
+    #[test]
+    fn prompt_line_should_cancel_given_esc_or_keep_editing_given_no_more_keys() {
+        // given
+        let mut line = String::new();
+
+        // then
+        assert_eq!(prompt_line(&mut line, &mut vec![Key::Backspace, Key::Char('B'), Key::Char('\t')].into_iter()), Prompted::Editing);
+        assert_eq!(line, "B");
+        assert_eq!(prompt_line(&mut line, &mut vec![Key::Char('2'), Key::Esc, Key::Char('3')].into_iter()), Prompted::Cancelled);
+        assert_eq!(line, "B2");
+        assert_eq!(prompt_line(&mut line, &mut std::iter::empty()), Prompted::Editing);
+    }
+
+    #[test]
+    fn handle_key_should_run_rule_typed_without_resetting_grid_given_u() {
+        // given
+        let mut app = started_app(&["--seed", "6"], (20, 10));
+        app.update(app.generation_time());
+        let grid = app.simulation.grid().clone();
+
+        // when
+        let keys = "uB36/S2q\n".chars().map(Key::Char).chain([Key::Backspace, Key::Char('3'), Key::Char('\n')]);
+        for key in keys {
+            app.handle_key(key);
+        }
+
+        // then
+        assert!(app.prompt.is_none() && !app.quit);
+        assert_eq!((app.simulation.rule(), app.simulation.generation()), (&Rule::HIGHLIFE, 1));
+        assert_eq!(app.simulation.grid(), &grid);
+        app.update(app.generation_time());
+        assert_eq!(app.simulation.grid(), &grid.next_with_rule(&Rule::HIGHLIFE));
+        app.handle_key(Key::Char('R'));
+        assert_eq!(app.simulation.rule(), &Rule::HIGHLIFE);
+    }
+
+    #[test]
+    fn handle_key_should_keep_rule_given_prompt_cancelled() {
+        // given
+        let mut app = started_app(&[], (20, 10));
+
+        // when
+        for key in [Key::Char('u'), Key::Char('B'), Key::Char('2'), Key::Esc] {
+            app.handle_key(key);
+        }
+
+        // then
+        assert!(app.prompt.is_none() && !app.quit);
+        assert_eq!(app.simulation.rule(), &Rule::CONWAY);
+    }
+
+    #[test]
+    fn handle_key_should_cycle_preset_rules_given_capital_u() {
+        // given
+        let mut app = started_app(&["--rule", "B3/S12345"], (20, 10));
+
+        // when
+        app.handle_key(Key::Char('U'));
+        assert_eq!(app.simulation.rule(), &Rule::CONWAY);
+        app.handle_key(Key::Char('U'));
+        assert_eq!(app.simulation.rule(), &Rule::HIGHLIFE);
+
+        // then
+        assert_eq!(next_preset(&Rule::SEEDS), Rule::DAY_AND_NIGHT);
+        assert_eq!(next_preset(&Rule::parse("B1/S1").unwrap()), Rule::CONWAY);
+    }
+
+    #[test]
+    fn format_prompt_should_show_why_line_is_not_a_rule() {
+        assert_eq!(format_prompt("", 12), " Rule: _    ");
+        assert_eq!(format_prompt("B36/S23", 20), " Rule: B36/S23_     ");
+        assert_eq!(format_prompt("B3", 80).trim_end(), " Rule: B3_  invalid rule: 'B3' should have two parts separated by '/'");
+    }
+
+    #[test]
+    fn build_status_should_show_prompt_given_status_hidden() {
+        // given
+        let mut app = started_app(&[], (12, 6));
+        app.handle_key(Key::Char('i'));
+        let mut frame = String::new();
+        build_status(&app, &mut frame);
+        assert!(frame.is_empty());
+
+        // when
+        app.handle_key(Key::Char('u'));
+        build_status(&app, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{} Rule: _    {}", cursor::Goto(1, 6), style::Invert, style::Reset));
+    }
+
+    #[test]
+    fn boundary_name_should_give_name_boundary_from_name_takes() {
+        for boundary in [Boundary::Torus, Boundary::Dead, Boundary::Mirror] {
+            assert_eq!(boundary_from_name(boundary_name(boundary)), Some(boundary));
+        }
+        assert_eq!(boundary_name(Boundary::Klein), "klein");
+        assert_eq!(boundary_from_name("klein"), None);
+        assert_eq!(boundary_from_name("Torus"), None);
+    }
+
+    #[test]
+    fn handle_key_should_cycle_boundary_without_resetting_grid_given_b() {
+        // given
+        let mut app = started_app(&["--seed", "8"], (20, 10));
+        app.update(app.generation_time());
+        let grid = app.simulation.grid().clone();
+
+        // when
+        app.handle_key(Key::Char('b'));
+
+        // then
+        assert_eq!((app.simulation.boundary(), app.simulation.generation()), (Boundary::Dead, 1));
+        assert_eq!(app.simulation.grid(), &grid);
+        assert!(format_status(&Status::of(&app), 80).contains(" | B3/S23 | dead | "));
+        app.handle_key(Key::Char('b'));
+        assert_eq!(app.simulation.boundary(), Boundary::Mirror);
+        app.handle_key(Key::Char('b'));
+        assert_eq!(app.simulation.boundary(), Boundary::Torus);
+    }
+
+    #[test]
+    fn app_should_keep_boundary_given_across_resizes_and_restarts() {
+        // given
+        let mut app = started_app(&["--boundary", "dead"], (20, 10));
+        assert_eq!(app.simulation.boundary(), Boundary::Dead);
+        app.handle_key(Key::Char('b'));
+
+        // when
+        app.resize((30, 12));
+        assert_eq!(app.simulation.boundary(), Boundary::Mirror);
+        app.handle_key(Key::Char('R'));
+
+        // then
+        assert_eq!(app.simulation.boundary(), Boundary::Mirror);
+        assert_eq!(app.simulation.grid().width(), 30);
+    }
+
+    fn status(rule: &Rule) -> Status<'_> {
+        Status { generation: 42, population: 7, size: (80, 23), rule, boundary: Boundary::Dead, seed: None, speed: 2.5, activity: "Paused" }
+    }
+
+    #[test]
+    fn format_status_should_pad_line_to_width() {
+        // when
+        let line = format_status(&status(&Rule::CONWAY), 70);
+
+        // then
+        assert_eq!(line, format!("{:<70}", " Gen 42 | Pop 7 | 80x23 | B3/S23 | dead | 2.5 gen/s | Paused"));
+        assert_eq!(line.chars().count(), 70);
+        assert_eq!(format_status(&Status { seed: Some(12345), ..status(&Rule::CONWAY) }, 80).trim_end(),
+            " Gen 42 | Pop 7 | 80x23 | B3/S23 | dead | Seed 12345 | 2.5 gen/s | Paused");
+    }
+
+    #[test]
+    fn format_status_should_cut_line_to_width_given_narrow_terminal() {
+        let rule = Rule::CONWAY;
+        assert_eq!(format_status(&status(&rule), 12), " Gen 42 | Po");
+        assert_eq!(format_status(&status(&rule), 1), " ");
+        assert_eq!(format_status(&status(&rule), 0), "");
+    }
+
+    #[test]
+    fn build_status_should_fill_last_row_given_status_shown() {
+        // given
+        let mut app = started_app(&["--fps", "2.5"], (12, 6));
+        let mut frame = String::new();
+
+        // when
+        build_status(&app, &mut frame);
+
+        // then
+        assert_eq!(frame, format!("{}{} Gen 0 | Pop{}", cursor::Goto(1, 6), style::Invert, style::Reset));
+        assert!(format_status(&Status::of(&app), 100).contains(&format!(" | Seed {} | ", app.start.seed().unwrap())));
+        assert_eq!(app.simulation.grid().height(), 5);
+
+        frame.clear();
+        app.handle_key(Key::Char('i'));
+        build_status(&app, &mut frame);
+        assert_eq!(frame, "");
+        assert_eq!(app.simulation.grid().height(), 6);
+        assert!(app.drawn.is_none());
+    }
+
+    #[test]
+    fn grid_size_should_give_grid_with_cells_given_usable_size() {
+        for render in &[Render::Text, Render::HalfBlocks, Render::Braille] {
+            let (width, height) = render.grid_size((1, 1));
+            assert!(width > 0 && height > 0);
+        }
+        assert_eq!(Render::Braille.grid_size((80, 24)), (160, 96));
+    }
 }
 
 /*****************************************************************************/
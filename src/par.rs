@@ -0,0 +1,62 @@
+/* par.rs - Rayon's parallel iterators given the parallel feature, serial ones otherwise
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+// Callers import par::* in place of rayon::prelude::* and call par_iter, par_chunks,
+// par_chunks_mut and into_par_iter as they would with rayon. Without the parallel
+// feature these return the matching std iterators, so the same iterator chains
+// compile to plain serial loops
+
+#[cfg(feature = "parallel")]
+pub(crate) use rayon::prelude::*;
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) use serial::*;
+
+// Rows of cells as returned by par_chunks
+#[cfg(feature = "parallel")]
+pub(crate) type ParChunks<'a, T> = rayon::slice::Chunks<'a, T>;
+#[cfg(not(feature = "parallel"))]
+pub(crate) type ParChunks<'a, T> = core::slice::Chunks<'a, T>;
+
+/*****************************************************************************/
+
+#[cfg(not(feature = "parallel"))]
+mod serial {
+    use core::slice::{Chunks, ChunksMut, Iter};
+
+    pub(crate) trait ParallelSlice<T> {
+        fn par_iter(&self) -> Iter<'_, T>;
+        fn par_chunks(&self, size: usize) -> Chunks<'_, T>;
+    }
+
+    impl<T> ParallelSlice<T> for [T] {
+        fn par_iter(&self) -> Iter<'_, T> {
+            self.iter()
+        }
+
+        fn par_chunks(&self, size: usize) -> Chunks<'_, T> {
+            self.chunks(size)
+        }
+    }
+
+    pub(crate) trait ParallelSliceMut<T> {
+        fn par_chunks_mut(&mut self, size: usize) -> ChunksMut<'_, T>;
+    }
+
+    impl<T> ParallelSliceMut<T> for [T] {
+        fn par_chunks_mut(&mut self, size: usize) -> ChunksMut<'_, T> {
+            self.chunks_mut(size)
+        }
+    }
+
+    pub(crate) trait IntoParallelIterator: IntoIterator + Sized {
+        fn into_par_iter(self) -> Self::IntoIter {
+            self.into_iter()
+        }
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {}
+}
+
+/*****************************************************************************/
@@ -0,0 +1,78 @@
+/* parallelism.rs - How Grid steps are spread across threads
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+#[cfg(feature = "parallel")]
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+use crate::Cell;
+use crate::par::*;
+
+/*****************************************************************************/
+
+// Grids with fewer cells are stepped on the calling thread given Parallelism::Auto
+const SERIAL_CELLS: usize = 32 * 1024;
+
+// Each rayon task steps a band of whole rows holding at least this many cells
+const BAND_CELLS:   usize = 8 * 1024;
+
+// Bound on functions called from several threads at once: Sync given the parallel
+// feature, and met by every type without it
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Sync {}
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+
+#[cfg(feature = "parallel")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+// Threads used to step a Grid. Without the parallel feature every grid is stepped
+// on the calling thread
+#[derive(Clone, Debug, Default)]
+pub enum Parallelism {
+    // Small grids on the calling thread, larger grids in bands of rows on the global rayon pool
+    #[default]
+    Auto,
+    // Every grid on the calling thread
+    Serial,
+    // Every grid in bands of rows on the global rayon pool
+    Parallel,
+    // Every grid in bands of rows on a dedicated rayon pool
+    #[cfg(feature = "parallel")]
+    Pool(Arc<ThreadPool>)
+}
+
+impl Parallelism {
+    // Return Parallelism using a dedicated pool of given number of threads
+    #[cfg(feature = "parallel")]
+    pub fn threads(threads: usize) -> Result<Parallelism, ThreadPoolBuildError> {
+        Ok(Parallelism::Pool(Arc::new(ThreadPoolBuilder::new().num_threads(threads).build()?)))
+    }
+
+    // Call band_func with the first row of each band of rows of given width and its cells
+    pub(crate) fn for_each_band<F: Fn(usize, &mut [Cell]) + MaybeSync>(&self, cells: &mut [Cell], width: usize, band_func: F) {
+        match self {
+            Parallelism::Auto if cells.len() < SERIAL_CELLS => band_func(0, cells),
+            Parallelism::Serial => band_func(0, cells),
+            // Threads cannot be spawned on plain wasm32, so every grid is stepped on the calling thread
+            _ if cfg!(any(target_arch = "wasm32", not(feature = "parallel"))) => band_func(0, cells),
+            Parallelism::Auto | Parallelism::Parallel => for_each_band(cells, width, &band_func),
+            #[cfg(feature = "parallel")]
+            Parallelism::Pool(pool) => pool.install(|| for_each_band(cells, width, &band_func))
+        }
+    }
+}
+
+// Call band_func in parallel with the first row of each band of rows and its cells
+fn for_each_band<F: Fn(usize, &mut [Cell]) + MaybeSync>(cells: &mut [Cell], width: usize, band_func: &F) {
+    let width = width.max(1);
+    let rows = (BAND_CELLS / width).max(1);
+    cells.par_chunks_mut(rows * width).enumerate()
+        .for_each(|(band, cells)| band_func(band * rows, cells));
+}
+
+/*****************************************************************************/
@@ -0,0 +1,126 @@
+/* pattern.rs - Pattern cells with the metadata read from pattern files
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Grid, InsertMode, LifeError, Overflow, Rule};
+
+/*****************************************************************************/
+
+// Cells of a pattern with the name, author, comments and rule read with them
+// from a pattern file, written again when exported
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pattern {
+    pub name:       Option<String>,
+    pub author:     Option<String>,
+    pub comments:   Vec<String>,
+    pub rule:       Option<Rule>,
+    cells:          Grid
+}
+
+impl Pattern {
+    // Return a Pattern of given cells without metadata
+    pub fn new(cells: Grid) -> Pattern {
+        Pattern { name: None, author: None, comments: Vec::new(), rule: None, cells }
+    }
+
+    // Return cells of the pattern
+    pub fn cells(&self) -> &Grid {
+        &self.cells
+    }
+
+    // Return number of cells in a row
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    // Return number of rows
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    // Return a copy of the Pattern rotated 90 degrees clockwise
+    pub fn rotate_cw(&self) -> Pattern {
+        self.with_cells(self.cells.rotate_cw())
+    }
+
+    // Return a copy of the Pattern mirrored left to right
+    pub fn flip_h(&self) -> Pattern {
+        self.with_cells(self.cells.flip_horizontal())
+    }
+
+    // Return a copy of the Pattern mirrored top to bottom
+    pub fn flip_v(&self) -> Pattern {
+        self.with_cells(self.cells.flip_vertical())
+    }
+
+    // Return a copy of the Pattern with cells shifted by given offsets, wrapping
+    // around the edges of its cells
+    pub fn translate(&self, dx: isize, dy: isize) -> Pattern {
+        self.with_cells(self.cells.translate(dx, dy))
+    }
+
+    // Copy the cells into grid with the top left corner at given position, cells
+    // beyond its edges being clipped
+    pub fn stamp_into(&self, grid: &mut Grid, x: usize, y: usize) {
+        grid.insert(&self.cells, x, y, InsertMode::Replace, Overflow::Clip);
+    }
+
+    // Return a Grid of given size with the pattern stamped at given position
+    pub fn to_grid(&self, width: usize, height: usize, x: usize, y: usize) -> Result<Grid, LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        self.stamp_into(&mut grid, x, y);
+        Ok(grid)
+    }
+
+    // Return a copy of the metadata with given cells
+    fn with_cells(&self, cells: Grid) -> Pattern {
+        Pattern { cells, ..self.clone() }
+    }
+}
+
+impl From<Grid> for Pattern {
+    fn from(cells: Grid) -> Pattern {
+        Pattern::new(cells)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transforms_should_keep_metadata() {
+        // given
+        let mut glider = Pattern::new(Grid::from_text(".O.\n..O\nOOO", 'O').unwrap());
+        glider.name = Some("Glider".to_string());
+
+        // when
+        let rotated = glider.rotate_cw().flip_h().flip_v().translate(1, 0);
+
+        // then
+        assert_eq!(rotated.name, glider.name);
+        assert_eq!(rotated.cells(), &glider.cells().rotate_cw().flip_horizontal().flip_vertical().translate(1, 0));
+        assert_eq!((rotated.width(), rotated.height()), (3, 3));
+    }
+
+    #[test]
+    fn stamp_into_should_replace_cells_and_clip_at_edges() {
+        // given
+        let block = Pattern::new(Grid::from_text("OO\nOO", 'O').unwrap());
+        let mut grid = Grid::from_text("O...\n....\n....", 'O').unwrap();
+
+        // when
+        block.stamp_into(&mut grid, 3, 2);
+
+        // then
+        assert_eq!(grid, Grid::from_text("O...\n....\n...O", 'O').unwrap());
+        assert_eq!(block.to_grid(3, 3, 1, 1).unwrap(), Grid::from_text("...\n.OO\n.OO", 'O').unwrap());
+    }
+}
+
+/*****************************************************************************/
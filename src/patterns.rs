@@ -0,0 +1,136 @@
+/* patterns.rs - Library of classic patterns embedded as RLE
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use std::sync::OnceLock;
+
+use crate::Pattern;
+
+/*****************************************************************************/
+
+// RLE text of each pattern in the library, named by its #N line
+const SOURCES: [&str; 14] = [
+    include_str!("patterns/glider.rle"),
+    include_str!("patterns/lwss.rle"),
+    include_str!("patterns/mwss.rle"),
+    include_str!("patterns/hwss.rle"),
+    include_str!("patterns/blinker.rle"),
+    include_str!("patterns/toad.rle"),
+    include_str!("patterns/beacon.rle"),
+    include_str!("patterns/pulsar.rle"),
+    include_str!("patterns/pentadecathlon.rle"),
+    include_str!("patterns/r-pentomino.rle"),
+    include_str!("patterns/diehard.rle"),
+    include_str!("patterns/acorn.rle"),
+    include_str!("patterns/gosper-glider-gun.rle"),
+    include_str!("patterns/block-laying-switch-engine.rle")
+];
+
+// Return every pattern in the library
+pub fn all() -> &'static [Pattern] {
+    static PATTERNS: OnceLock<Vec<Pattern>> = OnceLock::new();
+    PATTERNS.get_or_init(|| SOURCES.iter()
+        .map(|source| Pattern::from_rle(source).unwrap_or_else(|err| unreachable!("library pattern is invalid: {}", err)))
+        .collect())
+}
+
+// Return the library pattern of given name, ignoring case
+pub fn by_name(name: &str) -> Option<&'static Pattern> {
+    all().iter().find(|pattern| pattern.name.as_deref().is_some_and(|pattern| pattern.eq_ignore_ascii_case(name)))
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Boundary, Grid, Rule, Simulation};
+
+    #[test]
+    fn all_should_name_every_pattern_with_conway_rule() {
+        for pattern in all() {
+            assert!(pattern.name.is_some());
+            assert!(!pattern.comments.is_empty());
+            assert_eq!(pattern.rule, Some(Rule::CONWAY));
+            assert!(!pattern.cells().is_empty());
+        }
+        assert_eq!(all().len(), SOURCES.len());
+    }
+
+    #[test]
+    fn by_name_should_ignore_case() {
+        assert_eq!(by_name("gosper glider gun").and_then(|pattern| pattern.name.as_deref()), Some("Gosper glider gun"));
+        assert_eq!(by_name("LWSS").map(|pattern| pattern.cells().population()), Some(9));
+        assert_eq!(by_name("acorn").and_then(|pattern| pattern.author.as_deref()), Some("Charles Corderman"));
+        assert_eq!(by_name("queen bee"), None);
+    }
+
+    #[test]
+    fn spaceships_and_oscillators_should_match_published_apgcodes() {
+        for &(name, code) in &[
+            ("glider", "xq4_153"), ("lwss", "xq4_6frc"), ("mwss", "xq4_27dee6"), ("hwss", "xq4_27deee6"),
+            ("blinker", "xp2_7"), ("toad", "xp2_7e"), ("beacon", "xp2_318c"), ("pentadecathlon", "xp15_4r4z4r4")] {
+            assert_eq!(by_name(name).unwrap().cells().to_apgcode(), Some(code.to_string()), "{}", name);
+        }
+        assert_eq!(by_name("pulsar").unwrap().cells().to_apgcode().unwrap().get(..4), Some("xp3_"));
+    }
+
+    #[test]
+    fn gosper_glider_gun_should_emit_a_glider_every_30_generations() {
+        // given
+        let gun = by_name("gosper glider gun").unwrap();
+        let grid = gun.to_grid(60, 40, 1, 1).unwrap();
+        let step = |n| grid.step_n(n, |cell, neighbours| Rule::CONWAY.apply(cell, neighbours));
+        // Rows of the gun and those below it, where the gliders fly
+        let gun_rows = |grid: &Grid| grid.extract(0, 0, 60, 10).unwrap();
+        let below = |grid: &Grid| {
+            let mut below = grid.clone();
+            below.fill_rect(0, 0, 60, 10, false).unwrap();
+            below
+        };
+
+        // when
+        let first = (0..=30).find(|&n| !below(&step(n)).is_empty());
+
+        // then
+        assert!(first.is_some_and(|n| (15..=30).contains(&n)), "first glider at {:?}", first);
+        assert_eq!(below(&step(30)).to_apgcode(), Some("xq4_153".to_string()));
+        assert_eq!(below(&step(60)).census().count("glider"), 2);
+        assert_eq!(gun_rows(&step(30)), gun_rows(&grid));
+        assert_eq!(gun_rows(&step(60)), gun_rows(&grid));
+    }
+
+    #[test]
+    fn diehard_should_vanish_after_130_generations() {
+        // given
+        let grid = by_name("diehard").unwrap().to_grid(40, 30, 15, 12).unwrap();
+        let mut simulation = Simulation::new(grid, Rule::CONWAY);
+
+        // when
+        simulation.step_n(129);
+
+        // then
+        assert!(simulation.grid().population() > 0);
+        simulation.step();
+        assert!(simulation.grid().is_empty());
+    }
+
+    #[test]
+    fn block_laying_switch_engine_should_keep_laying_blocks() {
+        // given
+        let grid = by_name("block-laying switch engine").unwrap().to_grid(400, 400, 200, 200).unwrap();
+        let mut simulation = Simulation::new(grid, Rule::CONWAY).with_boundary(Boundary::Dead);
+        let mut blocks = Vec::new();
+
+        // when
+        for _ in 0..3 {
+            simulation.step_n(400);
+            blocks.push(simulation.grid().census().count("block"));
+        }
+
+        // then
+        assert!(blocks[0] > 0 && blocks[0] < blocks[1] && blocks[1] < blocks[2], "{:?}", blocks);
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,198 @@
+/* raster.rs - Rendering Grids as PBM text and, given the image feature, to and from images
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+#[cfg(feature = "image")]
+use core::convert::TryFrom;
+#[cfg(feature = "image")]
+use ::image::{DynamicImage, Rgb, RgbImage};
+use alloc::string::String;
+
+use crate::Grid;
+#[cfg(feature = "image")]
+use crate::{Cell, LifeError};
+
+/*****************************************************************************/
+
+// Longest line of a PBM file the format allows
+const PBM_LINE_LENGTH: usize = 70;
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return the cells as a plain PBM image, one pixel per cell with live cells
+    // black as "1" and dead cells white as "0". Rows of pixels start on new lines,
+    // wrapped to keep to the 70 chars per line the format allows
+    pub fn to_pbm(&self) -> String {
+        let mut text = format!("P1\n{} {}\n", self.width, self.height);
+        text.reserve((self.width + self.width / PBM_LINE_LENGTH + 1) * self.height);
+
+        for row in self.rows() {
+            for chunk in row.chunks(PBM_LINE_LENGTH) {
+                text.extend(chunk.iter().map(|cell| if cell.alive { '1' } else { '0' }));
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    // Return the cells as an image of squares cell_px pixels across in given
+    // colours, scaled without interpolation so edges stay crisp. Returns an error
+    // if the image would be too large for u32 dimensions or its buffer
+    #[cfg(feature = "image")]
+    pub fn to_image(&self, cell_px: u32, live: Rgb<u8>, dead: Rgb<u8>) -> Result<RgbImage, LifeError> {
+        let scale = |cells: usize| u32::try_from(cells).ok().and_then(|cells| cells.checked_mul(cell_px));
+        let size = scale(self.width).zip(scale(self.height)).filter(|&(width, height)| {
+            (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(3))
+                .is_some_and(|bytes| bytes <= isize::MAX as usize)
+        });
+        let (width, height) = size.ok_or(LifeError::TooLarge { width: self.width, height: self.height })?;
+
+        Ok(RgbImage::from_fn(width, height, |x, y| {
+            let index = (y / cell_px) as usize * self.width + (x / cell_px) as usize;
+            if self.cells[index].alive { live } else { dead }
+        }))
+    }
+
+    // Return a Grid of a cell per pixel of img, alive if its luma is above the
+    // threshold or, if inverted, dark cells alive instead. Returns an error given
+    // an empty image
+    #[cfg(feature = "image")]
+    pub fn from_image(img: &DynamicImage, threshold: u8, invert: bool) -> Result<Grid, LifeError> {
+        Grid::from_image_downsampled(img, threshold, invert, 1)
+    }
+
+    // Return a Grid of a cell per block of downsample x downsample pixels of img,
+    // thresholded as from_image does on the average luma of the block. Blocks at
+    // the right and bottom edges average the pixels the image has
+    #[cfg(feature = "image")]
+    pub fn from_image_downsampled(img: &DynamicImage, threshold: u8, invert: bool, downsample: u32) -> Result<Grid, LifeError> {
+        let luma = img.to_luma8();
+        let block = downsample.max(1);
+        let width = luma.width().div_ceil(block) as usize;
+        let height = luma.height().div_ceil(block) as usize;
+        let mut grid = Grid::new(width, height)?;
+
+        // Sum and count of the pixels in each block
+        let mut sums = vec![(0u32, 0u32); width * height];
+        for (x, y, pixel) in luma.enumerate_pixels() {
+            let sum = &mut sums[(y / block) as usize * width + (x / block) as usize];
+            sum.0 += pixel.0[0] as u32;
+            sum.1 += 1;
+        }
+
+        for (cell, (sum, count)) in grid.cells.iter_mut().zip(sums) {
+            *cell = Cell::from((sum / count > threshold as u32) != invert);
+        }
+        Ok(grid)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_pbm_should_write_plain_bitmap() {
+        // given
+        let grid = Grid::from_text(".O..\n..O.\nOOO.", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_pbm(), "P1\n4 3\n0100\n0010\n1110\n");
+    }
+
+    #[test]
+    fn to_pbm_should_wrap_rows_longer_than_70_pixels() {
+        // given
+        let mut grid = Grid::new(75, 2).unwrap();
+        grid.set(74, 0, true).unwrap();
+
+        // when
+        let pbm = grid.to_pbm();
+        let lines: Vec<&str> = pbm.lines().collect();
+
+        // then
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[1], "75 2");
+        assert_eq!((lines[2].len(), lines[3]), (70, "00001"));
+        assert_eq!((lines[4].len(), lines[5]), (70, "00000"));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn to_image_should_draw_cells_as_squares() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO\n...", 'O').unwrap();
+        let (live, dead) = (Rgb([0, 255, 0]), Rgb([0, 0, 0]));
+
+        // when
+        let image = grid.to_image(4, live, dead).unwrap();
+
+        // then
+        assert_eq!(image.dimensions(), (12, 16));
+        assert_eq!(image.get_pixel(0, 0), &dead);
+        assert_eq!(image.get_pixel(4, 0), &live);
+        assert_eq!(image.get_pixel(7, 3), &live);
+        assert_eq!(image.get_pixel(8, 3), &dead);
+        assert_eq!(image.get_pixel(11, 11), &live);
+        assert_eq!(image.get_pixel(11, 12), &dead);
+        assert_eq!(image.pixels().filter(|&&pixel| pixel == live).count(), 5 * 16);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn to_image_should_give_error_given_image_too_large() {
+        // given
+        let grid = Grid::new(3, 2).unwrap();
+        let (live, dead) = (Rgb([0, 255, 0]), Rgb([0, 0, 0]));
+
+        // when
+        let result = grid.to_image(u32::MAX, live, dead);
+
+        // then
+        assert_eq!(result.unwrap_err(), LifeError::TooLarge { width: 3, height: 2 });
+        assert_eq!(grid.to_image(u32::MAX / 3, live, dead).unwrap_err(), LifeError::TooLarge { width: 3, height: 2 });
+    }
+
+    // Return a checkerboard of squares square_px pixels across, white at the top left
+    #[cfg(feature = "image")]
+    fn checkerboard(width: u32, height: u32, square_px: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+            if (x / square_px + y / square_px).is_multiple_of(2) { Rgb([255, 255, 255]) } else { Rgb([0, 0, 0]) }
+        }))
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn from_image_should_threshold_luma_of_each_pixel() {
+        // given
+        let image = checkerboard(4, 3, 1);
+
+        // then
+        assert_eq!(Grid::from_image(&image, 127, false).unwrap(), Grid::from_text("O.O.\n.O.O\nO.O.", 'O').unwrap());
+        assert_eq!(Grid::from_image(&image, 127, true).unwrap(), Grid::from_text(".O.O\nO.O.\n.O.O", 'O').unwrap());
+        assert!(Grid::from_image(&image, 255, false).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn from_image_downsampled_should_average_luma_over_blocks() {
+        // given
+        let image = checkerboard(9, 7, 3);
+        let grid = Grid::from_text("O.O\n.O.\nO.O", 'O').unwrap();
+
+        // then
+        assert_eq!(Grid::from_image_downsampled(&image, 127, false, 3).unwrap(), grid);
+        assert_eq!(Grid::from_image_downsampled(&image, 127, true, 3).unwrap(), grid.invert());
+        // Blocks of 2x2 pixels of a finer checkerboard average to a luma of 127
+        let grey = checkerboard(4, 4, 1);
+        assert_eq!(Grid::from_image_downsampled(&grey, 127, false, 2).unwrap().population(), 0);
+        assert_eq!(Grid::from_image_downsampled(&grey, 126, false, 2).unwrap().population(), 4);
+        assert_eq!(Grid::from_image(&DynamicImage::new_rgb8(0, 5), 127, false).err(),
+            Some(LifeError::ZeroDimension { width: 0, height: 5 }));
+    }
+}
+
+/*****************************************************************************/
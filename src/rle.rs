@@ -0,0 +1,444 @@
+/* rle.rs - Run Length Encoded pattern format
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::{error, fmt};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Grid, LifeError, Pattern, Rule};
+
+/*****************************************************************************/
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum RleError {
+    MissingHeader,
+    InvalidHeader { line: usize, msg: String },
+    InvalidRule(LifeError),
+    UnexpectedChar { line: usize, found: char },
+    RowTooWide { row: usize, width: usize, declared: usize },
+    TooManyRows { declared: usize }
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RleError::MissingHeader => write!(f, "missing 'x = ..., y = ...' header line"),
+            RleError::InvalidHeader { line, msg } => write!(f, "line {}: invalid header, {}", line, msg),
+            RleError::InvalidRule(err) => write!(f, "{}", err),
+            RleError::UnexpectedChar { line, found } => write!(f, "line {}: unexpected '{}'", line, found),
+            RleError::RowTooWide { row, width, declared } =>
+                write!(f, "row {} has {} cells, exceeding declared width {}", row, width, declared),
+            RleError::TooManyRows { declared } => write!(f, "pattern has more rows than declared height {}", declared)
+        }
+    }
+}
+
+impl error::Error for RleError {}
+
+// Maximum length of pattern lines written by to_rle, as recommended by the format
+const LINE_LENGTH: usize = 70;
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return a Grid parsed from RLE text, of the dimensions given in its header,
+    // and the Rule from the header if present
+    pub fn from_rle(input: &str) -> Result<(Grid, Option<Rule>), RleError> {
+        let mut lines = input.lines().enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (number, line) = match lines.next() {
+            Some((number, line)) if line.starts_with('x') => (number, line),
+            _ => return Err(RleError::MissingHeader)
+        };
+        let (width, height, rule) = header(number, line)?;
+        let mut grid = Grid::new(width, height)
+            .map_err(|err| RleError::InvalidHeader { line: number, msg: err.to_string() })?;
+
+        // Runs may be split across lines, so the count carries over line breaks.
+        // Counts and positions saturate rather than overflow, any run that long
+        // being wider or taller than the declared size
+        let (mut x, mut y, mut count): (usize, usize, usize) = (0, 0, 0);
+        'body: for (number, line) in lines {
+            for c in line.chars() {
+                let run = count.max(1);
+                match c {
+                    '0'..='9' => {
+                        count = count.saturating_mul(10).saturating_add(c.to_digit(10).unwrap_or(0) as usize);
+                        continue
+                    },
+                    'b' | 'o' => {
+                        if x.saturating_add(run) > width {
+                            return Err(RleError::RowTooWide { row: y, width: x.saturating_add(run), declared: width });
+                        }
+                        if y >= height {
+                            return Err(RleError::TooManyRows { declared: height });
+                        }
+                        if c == 'o' {
+                            grid.cells[y * width + x..y * width + x + run].iter_mut().for_each(|cell| cell.alive = true);
+                        }
+                        x += run;
+                    },
+                    '$' => { x = 0; y = y.saturating_add(run) },
+                    '!' => break 'body,
+                    c if c.is_whitespace() => continue,
+                    _ => return Err(RleError::UnexpectedChar { line: number, found: c })
+                }
+                count = 0;
+            }
+        }
+
+        Ok((grid, rule))
+    }
+
+    // Return RLE text of the live cells trimmed to their bounding box, with
+    // given rule in the header if present
+    pub fn to_rle(&self, rule: Option<&str>) -> String {
+        let (min_x, min_y, width, height) = match self.bounding_box() {
+            Some((min_x, min_y, max_x, max_y)) => (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1),
+            None => (0, 0, 0, 0)
+        };
+
+        // With no live cells the header is of a single dead cell, as from_rle
+        // rejects zero dimensions
+        let mut rle = format!("x = {}, y = {}", width.max(1), height.max(1));
+        if let Some(rule) = rule {
+            rle.push_str(&format!(", rule = {}", rule));
+        }
+        rle.push('\n');
+
+        // Tokens of run count and tag, rows ending in dead cells drop the final run
+        // and blank rows are folded into the run count of the next $
+        let mut tokens = Vec::new();
+        let mut rows_ended = 0;
+        for row in self.rows().skip(min_y).take(height) {
+            let mut runs: Vec<(usize, bool)> = Vec::new();
+            for cell in &row[min_x..min_x + width] {
+                match runs.last_mut() {
+                    Some((count, alive)) if *alive == cell.alive => *count += 1,
+                    _ => runs.push((1, cell.alive))
+                }
+            }
+            if let Some((_, false)) = runs.last() {
+                runs.pop();
+            }
+
+            if !runs.is_empty() {
+                if rows_ended > 0 {
+                    tokens.push(token(rows_ended, '$'));
+                    rows_ended = 0;
+                }
+                tokens.extend(runs.into_iter().map(|(count, alive)| token(count, if alive { 'o' } else { 'b' })));
+            }
+            rows_ended += 1;
+        }
+        tokens.push("!".to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length + token.len() > LINE_LENGTH {
+                rle.push('\n');
+                line_length = 0;
+            }
+            line_length += token.len();
+            rle.push_str(&token);
+        }
+        rle.push('\n');
+        rle
+    }
+}
+
+impl Pattern {
+    // Return a Pattern parsed from RLE text, with the name from a #N line, the
+    // author from a #O line and comments from #C or #c lines
+    pub fn from_rle(input: &str) -> Result<Pattern, RleError> {
+        let (cells, rule) = Grid::from_rle(input)?;
+        let mut pattern = Pattern::new(cells);
+        pattern.rule = rule;
+
+        for line in input.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("#N") {
+                pattern.name = Some(name.trim().to_string());
+            }
+            else if let Some(author) = line.strip_prefix("#O") {
+                pattern.author = Some(author.trim().to_string());
+            }
+            else if let Some(comment) = line.strip_prefix("#C").or_else(|| line.strip_prefix("#c")) {
+                pattern.comments.push(comment.trim().to_string());
+            }
+        }
+        Ok(pattern)
+    }
+
+    // Return RLE text of the cells with #N, #O and #C lines for the metadata and
+    // the rule in the header if present
+    pub fn to_rle(&self) -> String {
+        let mut rle = String::new();
+        if let Some(name) = &self.name {
+            rle.push_str(&format!("#N {}\n", name));
+        }
+        if let Some(author) = &self.author {
+            rle.push_str(&format!("#O {}\n", author));
+        }
+        for comment in &self.comments {
+            rle.push_str(&format!("#C {}\n", comment));
+        }
+        rle.push_str(&self.cells().to_rle(self.rule.map(|rule| rule.to_string()).as_deref()));
+        rle
+    }
+}
+
+// Return RLE token for a run of count tags, counts of 1 are left out
+fn token(count: usize, tag: char) -> String {
+    if count == 1 { tag.to_string() } else { format!("{}{}", count, tag) }
+}
+
+// Return width, height and optional Rule parsed from an "x = m, y = n, rule = abc" header
+fn header(number: usize, line: &str) -> Result<(usize, usize, Option<Rule>), RleError> {
+    let invalid = |msg: String| RleError::InvalidHeader { line: number, msg };
+    let (mut width, mut height, mut rule) = (None, None, None);
+
+    for field in line.split(',') {
+        let mut parts = field.splitn(2, '=');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key.trim(), value.trim()),
+            _ => return Err(invalid(format!("'{}' should be 'key = value'", field.trim())))
+        };
+        let dimension = || value.parse::<usize>().map_err(|_| invalid(format!("'{}' is not a valid {}", value, key)));
+        match key {
+            "x"    => width  = Some(dimension()?),
+            "y"    => height = Some(dimension()?),
+            "rule" => rule   = Some(Rule::parse(value).map_err(RleError::InvalidRule)?),
+            _      => return Err(invalid(format!("unknown key '{}'", key)))
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err(invalid("both x and y are required".to_string()))
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    const GOSPER_GLIDER_GUN: &str = "\
+#N Gosper glider gun
+#C This was the first gun discovered.
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+obo$10bo5bo7bo$11bo3bo$12b2o!";
+
+    #[test]
+    fn from_rle_should_parse_glider() {
+        // when
+        let (grid, rule) = Grid::from_rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!").unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".O.\n..O\nOOO", 'O').unwrap());
+        assert_eq!(rule, Some(Rule::CONWAY));
+    }
+
+    #[test]
+    fn from_rle_should_parse_gosper_glider_gun() {
+        // when
+        let (grid, _) = Grid::from_rle(GOSPER_GLIDER_GUN).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text("\
+........................O...........
+......................O.O...........
+............OO......OO............OO
+...........O...O....OO............OO
+OO........O.....O...OO..............
+OO........O...O.OO....O.O...........
+..........O.....O.......O...........
+...........O...O....................
+............OO......................", 'O').unwrap());
+    }
+
+    #[test]
+    fn from_rle_should_accept_missing_rule_and_terminator() {
+        // when
+        let (grid, rule) = Grid::from_rle("x = 3, y = 2\n3o$obo").unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text("OOO\nO.O", 'O').unwrap());
+        assert_eq!(rule, None);
+    }
+
+    #[test]
+    fn from_rle_should_continue_runs_across_line_breaks() {
+        // when
+        let (grid, _) = Grid::from_rle("x = 14, y = 3\n1\n2o$\n$o!").unwrap();
+
+        // then
+        assert_eq!(grid.population(), 13);
+        assert_eq!(grid.get(0, 2).unwrap().is_alive(), true);
+        assert_eq!(grid.get(12, 0).unwrap().is_alive(), false);
+    }
+
+    #[test]
+    fn from_rle_should_skip_multiple_rows_given_run_count_before_dollar() {
+        // when
+        let (grid, _) = Grid::from_rle("x = 2, y = 4\no3$bo!").unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text("O.\n..\n..\n.O", 'O').unwrap());
+    }
+
+    #[test]
+    fn from_rle_should_ignore_cells_after_terminator() {
+        // when
+        let (grid, _) = Grid::from_rle("x = 2, y = 1\nbo!\n2o$2o").unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".O", 'O').unwrap());
+    }
+
+    #[test]
+    fn to_rle_should_encode_glider_with_rule() {
+        // given
+        let grid = Grid::from_text(".....\n..O..\n...O.\n.OOO.\n.....", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_rle(Some("B3/S23")), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+        assert_eq!(grid.to_rle(None),           "x = 3, y = 3\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn to_rle_should_fold_blank_rows_into_row_terminator() {
+        // given
+        let grid = Grid::from_text("O.\n..\n..\n.O", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_rle(None), "x = 2, y = 4\no3$bo!\n");
+    }
+
+    #[test]
+    fn to_rle_should_encode_empty_grid() {
+        assert_eq!(Grid::new(4, 4).unwrap().to_rle(None), "x = 1, y = 1\n!\n");
+        assert_eq!(Grid::random_seeded(0, 0, 0).to_rle(None), "x = 1, y = 1\n!\n");
+    }
+
+    #[test]
+    fn to_rle_should_wrap_lines_at_70_characters() {
+        // given
+        let (gun, _) = Grid::from_rle(GOSPER_GLIDER_GUN).unwrap();
+        let soup = Grid::random_seeded(4, 80, 60);
+
+        // then
+        assert_eq!(gun.to_rle(Some("B3/S23")), "\
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+obo$10bo5bo7bo$11bo3bo$12b2o!
+");
+        assert_eq!(soup.to_rle(None).lines().all(|line| line.len() <= 70), true);
+    }
+
+    #[test]
+    fn from_rle_should_round_trip_to_rle_given_bounding_box_crop() {
+        // given
+        let (gun, _) = Grid::from_rle(GOSPER_GLIDER_GUN).unwrap();
+        let glider = Grid::from_text("......\n...O..\n....O.\n..OOO.\n......", 'O').unwrap();
+        let mut grids = vec![gun, glider];
+        grids.extend((0..8).map(|seed| Grid::random_seeded_with_density(seed, 50, 40, 0.1 * seed as f64)));
+
+        for grid in grids {
+            // when
+            let (parsed, rule) = Grid::from_rle(&grid.to_rle(Some("B3/S23"))).unwrap();
+
+            // then
+            let expected = match grid.bounding_box() {
+                Some((min_x, min_y, max_x, max_y)) => grid.extract(min_x, min_y, max_x - min_x + 1, max_y - min_y + 1).unwrap(),
+                None => Grid::new(1, 1).unwrap()
+            };
+            assert_eq!(parsed, expected);
+            assert_eq!(rule, Some(Rule::CONWAY));
+        }
+    }
+
+    #[test]
+    fn from_rle_should_return_error_given_missing_header() {
+        assert_eq!(Grid::from_rle("bob$2bo$3o!"), Err(RleError::MissingHeader));
+        assert_eq!(Grid::from_rle("#N Empty\n"),  Err(RleError::MissingHeader));
+    }
+
+    #[test]
+    fn from_rle_should_return_error_given_invalid_header() {
+        assert_eq!(Grid::from_rle("x = 3\nbob!").unwrap_err().to_string(),
+            "line 1: invalid header, both x and y are required");
+        assert_eq!(Grid::from_rle("#C\nx = three, y = 3\nbob!").unwrap_err().to_string(),
+            "line 2: invalid header, 'three' is not a valid x");
+        assert_eq!(Grid::from_rle("x = 0, y = 3\n!").unwrap_err().to_string(),
+            "line 1: invalid header, grid dimensions 0x3 must be non-zero");
+        assert_eq!(Grid::from_rle(&format!("x = {}, y = 2\n!", usize::MAX)).unwrap_err().to_string(),
+            format!("line 1: invalid header, grid dimensions {}x2 hold too many cells", usize::MAX));
+        assert_eq!(Grid::from_rle(&format!("x = {}, y = {}\n!", 1usize << (usize::BITS / 2), 1usize << (usize::BITS / 2 - 1)))
+            .unwrap_err().to_string(),
+            format!("line 1: invalid header, grid dimensions {}x{} hold too many cells",
+                1usize << (usize::BITS / 2), 1usize << (usize::BITS / 2 - 1)));
+        assert!(matches!(Grid::from_rle("x = 3, y = 3, rule = B9/S23\nbob!"), Err(RleError::InvalidRule(_))));
+    }
+
+    #[test]
+    fn from_rle_should_return_error_given_runs_exceeding_width() {
+        assert_eq!(Grid::from_rle("x = 3, y = 3\nbob$4o!"), Err(RleError::RowTooWide { row: 1, width: 4, declared: 3 }));
+        assert_eq!(Grid::from_rle("x = 3, y = 3\nbob$4o!").unwrap_err().to_string(),
+            "row 1 has 4 cells, exceeding declared width 3");
+    }
+
+    #[test]
+    fn from_rle_should_return_error_given_runs_overflowing() {
+        assert_eq!(Grid::from_rle("x = 3, y = 3\n99999999999999999999999o!"),
+            Err(RleError::RowTooWide { row: 0, width: usize::MAX, declared: 3 }));
+        assert_eq!(Grid::from_rle(&format!("x = 3, y = 3\no{}o!", usize::MAX)),
+            Err(RleError::RowTooWide { row: 0, width: usize::MAX, declared: 3 }));
+        assert_eq!(Grid::from_rle(&format!("x = 3, y = 3\no{}$2$o!", usize::MAX)), Err(RleError::TooManyRows { declared: 3 }));
+    }
+
+    #[test]
+    fn from_rle_should_return_error_given_rows_exceeding_height() {
+        assert_eq!(Grid::from_rle("x = 3, y = 2\nbob$2bo$3o!"), Err(RleError::TooManyRows { declared: 2 }));
+    }
+
+    #[test]
+    fn from_rle_should_return_error_given_unexpected_char() {
+        assert_eq!(Grid::from_rle("x = 3, y = 3\nbob$\n2bx$3o!"), Err(RleError::UnexpectedChar { line: 3, found: 'x' }));
+    }
+
+    #[test]
+    fn pattern_from_rle_should_parse_metadata_lines() {
+        // when
+        let pattern = Pattern::from_rle("#N Glider\n#O Richard K. Guy\n#C The smallest spaceship.\n#c Found in 1969.\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!").unwrap();
+
+        // then
+        assert_eq!(pattern.name, Some("Glider".to_string()));
+        assert_eq!(pattern.author, Some("Richard K. Guy".to_string()));
+        assert_eq!(pattern.comments, vec!["The smallest spaceship.", "Found in 1969."]);
+        assert_eq!(pattern.rule, Some(Rule::CONWAY));
+        assert_eq!(pattern.cells(), &Grid::from_text(".O.\n..O\nOOO", 'O').unwrap());
+    }
+
+    #[test]
+    fn pattern_to_rle_should_write_metadata_lines() {
+        // given
+        let pattern = Pattern::from_rle(GOSPER_GLIDER_GUN).unwrap();
+
+        // when
+        let rle = pattern.to_rle();
+
+        // then
+        assert_eq!(rle, format!("{}\n", GOSPER_GLIDER_GUN));
+        assert_eq!(Pattern::from_rle(&rle).unwrap(), pattern);
+        assert_eq!(Pattern::new(Grid::from_text("OOO", 'O').unwrap()).to_rle(), "x = 3, y = 1\n3o!\n");
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,286 @@
+/* rule.rs - Outer totalistic birth/survival rules
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use core::str::FromStr;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Cell, LifeError, Neighbourhood, Shape};
+
+/*****************************************************************************/
+
+// Birth and survival conditions indexed by number of live neighbours in a
+// Moore, von Neumann or hexagonal radius 1 Neighbourhood
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rule {
+    birth:          [bool; 9],
+    survival:       [bool; 9],
+    neighbourhood:  Neighbourhood
+}
+
+impl Rule {
+    // B3/S23
+    pub const CONWAY: Rule = Rule {
+        birth:      [false, false, false, true,  false, false, false, false, false],
+        survival:   [false, false, true,  true,  false, false, false, false, false],
+        neighbourhood:  Neighbourhood::MOORE
+    };
+
+    // B36/S23
+    pub const HIGHLIFE: Rule = Rule {
+        birth:      [false, false, false, true,  false, false, true,  false, false],
+        survival:   [false, false, true,  true,  false, false, false, false, false],
+        neighbourhood:  Neighbourhood::MOORE
+    };
+
+    // B2/S
+    pub const SEEDS: Rule = Rule {
+        birth:      [false, false, true,  false, false, false, false, false, false],
+        survival:   [false, false, false, false, false, false, false, false, false],
+        neighbourhood:  Neighbourhood::MOORE
+    };
+
+    // B3678/S34678
+    pub const DAY_AND_NIGHT: Rule = Rule {
+        birth:      [false, false, false, true,  false, false, true,  true,  true],
+        survival:   [false, false, false, true,  true,  false, true,  true,  true],
+        neighbourhood:  Neighbourhood::MOORE
+    };
+
+    // Return a Rule given the neighbour counts causing birth and survival
+    pub fn new(birth: &[u8], survival: &[u8]) -> Result<Rule, LifeError> {
+        Ok(Rule { birth: Rule::counts(birth)?, survival: Rule::counts(survival)?, neighbourhood: Neighbourhood::MOORE })
+    }
+
+    // Return Rule counting neighbours in given radius 1 Neighbourhood
+    pub fn with_neighbourhood(mut self, neighbourhood: Neighbourhood) -> Result<Rule, LifeError> {
+        if neighbourhood.radius != 1 || neighbourhood.shape == Shape::Circular {
+            return Err(LifeError::InvalidRule(format!("{:?} is not a Moore, von Neumann or hexagonal neighbourhood", neighbourhood)));
+        }
+        let size = neighbourhood.size() as usize;
+        if let Some(count) = (size + 1..9).find(|&count| self.birth[count] || self.survival[count]) {
+            return Err(LifeError::InvalidRule(format!("neighbour count {} is out of range 0 to {}", count, size)));
+        }
+        self.neighbourhood = neighbourhood;
+        Ok(self)
+    }
+
+    // Return a Rule parsed from B/S ("B36/S23") or S/B ("23/36") notation, with an
+    // optional V or H suffix for the von Neumann or hexagonal Neighbourhood ("B2/S34H")
+    pub fn parse(text: &str) -> Result<Rule, LifeError> {
+        let text  = text.trim();
+        let (rule, neighbourhood) = match text.chars().last().map(|c| c.to_ascii_lowercase()) {
+            Some('v') => (&text[..text.len() - 1], Neighbourhood::VON_NEUMANN),
+            Some('h') => (&text[..text.len() - 1], Neighbourhood::HEXAGONAL),
+            _         => (text, Neighbourhood::MOORE)
+        };
+        let max   = neighbourhood.size();
+        let parts: Vec<&str> = rule.split('/').collect();
+        if parts.len() != 2 {
+            return Err(LifeError::InvalidRule(format!("'{}' should have two parts separated by '/'", text)));
+        }
+
+        let (birth, survival) = match (Rule::strip_prefix(parts[0]), Rule::strip_prefix(parts[1])) {
+            ((Some('b'), birth), (Some('s'), survival)) => (birth, survival),
+            ((Some('s'), survival), (Some('b'), birth)) => (birth, survival),
+            ((None, survival), (None, birth))           => (birth, survival),
+            _ => return Err(LifeError::InvalidRule(format!("'{}' should be in B/S or S/B notation", text)))
+        };
+
+        Ok(Rule { birth: Rule::digits(text, birth, max)?, survival: Rule::digits(text, survival, max)?, neighbourhood })
+    }
+
+    // Return true if a dead cell with given neighbours is born
+    pub fn birth(&self, neighbours: u8) -> bool {
+        self.birth.get(neighbours as usize).copied().unwrap_or(false)
+    }
+
+    // Return true if a live cell with given neighbours survives
+    pub fn survival(&self, neighbours: u8) -> bool {
+        self.survival.get(neighbours as usize).copied().unwrap_or(false)
+    }
+
+    // Return Neighbourhood in which neighbours are counted
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    // Return next cell state
+    pub fn apply(&self, cell: &Cell, neighbours: u8) -> Cell {
+        Cell::new(if cell.is_alive() { self.survival(neighbours) } else { self.birth(neighbours) })
+    }
+
+    // Return true if a cell with given centre state and neighbourhood mask is alive next,
+    // only the number of live neighbours in the mask matters
+    pub fn apply_neighbourhood(&self, mask: u8, centre: bool) -> bool {
+        let neighbours = mask.count_ones() as u8;
+        if centre { self.survival(neighbours) } else { self.birth(neighbours) }
+    }
+
+    // Split a leading B or S from part of a rulestring
+    fn strip_prefix(part: &str) -> (Option<char>, &str) {
+        match part.chars().next().map(|c| c.to_ascii_lowercase()) {
+            Some(c) if c == 'b' || c == 's' => (Some(c), &part[1..]),
+            _ => (None, part)
+        }
+    }
+
+    // Return conditions for a string of neighbour count digits up to max
+    fn digits(text: &str, digits: &str, max: u32) -> Result<[bool; 9], LifeError> {
+        let counts = digits.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(count) if count <= max => Ok(count as u8),
+                _ => Err(LifeError::InvalidRule(format!("'{}' contains '{}', expected neighbour counts 0 to {}", text, c, max)))
+            })
+            .collect::<Result<Vec<u8>, LifeError>>()?;
+        Rule::counts(&counts)
+    }
+
+    // Return conditions for a list of neighbour counts
+    fn counts(counts: &[u8]) -> Result<[bool; 9], LifeError> {
+        let mut conditions = [false; 9];
+        for &count in counts {
+            match conditions.get_mut(count as usize) {
+                Some(condition) => *condition = true,
+                None => return Err(LifeError::InvalidRule(format!("neighbour count {} is out of range 0 to 8", count)))
+            }
+        }
+        Ok(conditions)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::CONWAY
+    }
+}
+
+impl FromStr for Rule {
+    type Err = LifeError;
+
+    fn from_str(text: &str) -> Result<Rule, LifeError> {
+        Rule::parse(text)
+    }
+}
+
+// Format in canonical B/S notation
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = |conditions: &[bool; 9]| conditions.iter().enumerate()
+            .filter(|(_, &condition)| condition)
+            .map(|(count, _)| (b'0' + count as u8) as char)
+            .collect::<String>();
+
+        let suffix = match self.neighbourhood.shape {
+            Shape::VonNeumann => "V",
+            Shape::Hexagonal  => "H",
+            _                 => ""
+        };
+        write!(f, "B{}/S{}{}", digits(&self.birth), digits(&self.survival), suffix)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_should_accept_b_s_notation() {
+        assert_eq!(Rule::parse("B3/S23").unwrap(),         Rule::CONWAY);
+        assert_eq!(Rule::parse("b36/s23").unwrap(),        Rule::HIGHLIFE);
+        assert_eq!(Rule::parse("B2/S").unwrap(),           Rule::SEEDS);
+        assert_eq!(Rule::parse(" B3678/S34678 ").unwrap(), Rule::DAY_AND_NIGHT);
+    }
+
+    #[test]
+    fn parse_should_accept_s_b_notation() {
+        assert_eq!(Rule::parse("23/3").unwrap(),      Rule::CONWAY);
+        assert_eq!(Rule::parse("S23/B36").unwrap(),   Rule::HIGHLIFE);
+        assert_eq!(Rule::parse("/2").unwrap(),        Rule::SEEDS);
+    }
+
+    #[test]
+    fn parse_should_accept_duplicate_digits() {
+        assert_eq!(Rule::parse("B33/S2323").unwrap(), Rule::CONWAY);
+    }
+
+    #[test]
+    fn parse_should_reject_invalid_rules() {
+        assert_eq!(Rule::parse("B39/S23").err(),
+            Some(LifeError::InvalidRule("'B39/S23' contains '9', expected neighbour counts 0 to 8".to_string())));
+        assert_eq!(Rule::parse("B3S23").is_err(),    true);
+        assert_eq!(Rule::parse("B3/S2/3").is_err(),  true);
+        assert_eq!(Rule::parse("B3/B23").is_err(),   true);
+        assert_eq!(Rule::parse("B3/23").is_err(),    true);
+        assert_eq!(Rule::parse("B3x/S23").is_err(),  true);
+        assert_eq!(Rule::parse("").is_err(),         true);
+    }
+
+    #[test]
+    fn fmt_should_round_trip_canonical_b_s_notation() {
+        for text in &["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B/S012345678", "B2/S34V", "B2/S34H"] {
+            assert_eq!(&Rule::parse(text).unwrap().to_string(), text);
+        }
+        assert_eq!(Rule::parse("32/63").unwrap().to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn parse_should_accept_neighbourhood_suffix() {
+        assert_eq!(Rule::parse("B2/S34V").unwrap().neighbourhood(), Neighbourhood::VON_NEUMANN);
+        assert_eq!(Rule::parse("b2/s34h").unwrap().neighbourhood(), Neighbourhood::HEXAGONAL);
+        assert_eq!(Rule::parse("34/2H").unwrap(), Rule::new(&[2], &[3, 4]).unwrap().with_neighbourhood(Neighbourhood::HEXAGONAL).unwrap());
+        assert_eq!(Rule::parse("B3/S23").unwrap().neighbourhood(), Neighbourhood::MOORE);
+    }
+
+    #[test]
+    fn parse_should_reject_counts_larger_than_neighbourhood() {
+        assert_eq!(Rule::parse("B5/S34V").err(),
+            Some(LifeError::InvalidRule("'B5/S34V' contains '5', expected neighbour counts 0 to 4".to_string())));
+        assert_eq!(Rule::parse("B2/S7H").is_err(), true);
+        assert_eq!(Rule::parse("B2/S6H").is_ok(),  true);
+    }
+
+    #[test]
+    fn with_neighbourhood_should_reject_unsupported_neighbourhoods() {
+        assert_eq!(Rule::CONWAY.with_neighbourhood(Neighbourhood::VON_NEUMANN).is_ok(), true);
+        assert_eq!(Rule::DAY_AND_NIGHT.with_neighbourhood(Neighbourhood::VON_NEUMANN).is_err(), true);
+        assert_eq!(Rule::CONWAY.with_neighbourhood(Neighbourhood::new(2, Shape::Moore)).is_err(), true);
+        assert_eq!(Rule::CONWAY.with_neighbourhood(Neighbourhood::new(1, Shape::Circular)).is_err(), true);
+    }
+
+    #[test]
+    fn new_should_build_rule_from_counts() {
+        assert_eq!(Rule::new(&[3, 6], &[2, 3]).unwrap(), Rule::HIGHLIFE);
+        assert_eq!(Rule::new(&[9], &[]).is_err(),        true);
+    }
+
+    #[test]
+    fn apply_should_match_cell_next_given_conway() {
+        for neighbours in 0..=8 {
+            assert_eq!(Rule::CONWAY.apply(&Cell::alive(), neighbours), Cell::alive().next(neighbours));
+            assert_eq!(Rule::CONWAY.apply(&Cell::dead(), neighbours),  Cell::dead().next(neighbours));
+        }
+    }
+
+    #[test]
+    fn apply_neighbourhood_should_count_mask_bits() {
+        assert_eq!(Rule::CONWAY.apply_neighbourhood(0b1010_0001, false), true);
+        assert_eq!(Rule::CONWAY.apply_neighbourhood(0b0001_1000, true),  true);
+        assert_eq!(Rule::CONWAY.apply_neighbourhood(0b0001_1000, false), false);
+        assert_eq!(Rule::CONWAY.apply_neighbourhood(0b1111_0000, true),  false);
+    }
+
+    #[test]
+    fn apply_should_birth_on_six_neighbours_given_highlife() {
+        assert_eq!(Rule::HIGHLIFE.apply(&Cell::dead(), 6),  Cell::alive());
+        assert_eq!(Rule::HIGHLIFE.apply(&Cell::alive(), 6), Cell::dead());
+        assert_eq!(Rule::CONWAY.apply(&Cell::dead(), 6),    Cell::dead());
+    }
+}
+
+/*****************************************************************************/
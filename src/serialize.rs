@@ -0,0 +1,246 @@
+/* serialize.rs - serde support, enabled by the serde feature
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::convert::TryFrom;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+
+use crate::{Cell, Grid, LifeError, Rule};
+
+/*****************************************************************************/
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Return standard padded base64 encoding of bytes
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            text.push(if i <= chunk.len() { BASE64[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char } else { '=' });
+        }
+    }
+    text
+}
+
+// Return bytes decoded from standard padded base64, or None if invalid
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes() {
+        bits = (bits << 6) | BASE64.iter().position(|&b| b == c)? as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/*****************************************************************************/
+
+// Grid as serialized, with rows as text of '.' and 'O' or as a base64 bitmap
+// of rows packed as in binary snapshots
+#[derive(Deserialize, Serialize)]
+struct GridRepr {
+    width:  usize,
+    height: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rows:   Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bitmap: Option<String>
+}
+
+impl TryFrom<GridRepr> for Grid {
+    type Error = LifeError;
+
+    fn try_from(repr: GridRepr) -> Result<Grid, LifeError> {
+        let (width, height) = (repr.width, repr.height);
+        let rows: Vec<Vec<bool>> = match (repr.rows, repr.bitmap) {
+            (Some(rows), None) => rows.iter()
+                .map(|row| row.chars().map(|c| c == 'O').collect())
+                .collect(),
+            (None, Some(bitmap)) => {
+                let bytes = decode_base64(&bitmap)
+                    .ok_or_else(|| LifeError::ParseError { line: 1, msg: "bitmap is not valid base64".to_string() })?;
+                let expected = width.div_ceil(8).checked_mul(height).ok_or_else(|| LifeError::ParseError {
+                    line:   1,
+                    msg:    format!("bitmap for {}x{} is too large", width, height)
+                })?;
+                if bytes.len() != expected {
+                    return Err(LifeError::ParseError {
+                        line:   1,
+                        msg:    format!("bitmap has {} bytes, expected {} for {}x{}", bytes.len(), expected, width, height)
+                    });
+                }
+                return Grid::from_packed_rows(width, height, &bytes);
+            },
+            _ => return Err(LifeError::ParseError { line: 1, msg: "expected exactly one of rows or bitmap".to_string() })
+        };
+
+        let found = (rows.first().map_or(0, Vec::len), rows.len());
+        if found != (width, height) {
+            return Err(LifeError::DimensionMismatch { expected: (width, height), found });
+        }
+        Grid::try_from(rows)
+    }
+}
+
+impl Serialize for Grid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GridRepr {
+            width:  self.width(),
+            height: self.height(),
+            rows:   Some(self.rows().map(|row| row.iter().map(|cell| if cell.alive { 'O' } else { '.' }).collect()).collect()),
+            bitmap: None
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Grid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Grid, D::Error> {
+        Grid::try_from(GridRepr::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+impl Grid {
+    // Return base64 bitmap of packed rows, as accepted in place of rows when deserializing
+    pub fn to_base64_bitmap(&self) -> String {
+        encode_base64(&self.packed_rows())
+    }
+}
+
+/*****************************************************************************/
+
+// Cells serialize as bools
+impl Serialize for Cell {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(self.alive)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cell {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Cell, D::Error> {
+        bool::deserialize(deserializer).map(Cell::new)
+    }
+}
+
+// Rules serialize as rulestrings
+impl Serialize for Rule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Rule, D::Error> {
+        Rule::parse(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::{Boundary, Simulation};
+
+    #[test]
+    fn base64_should_round_trip_bytes() {
+        for bytes in &[&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(decode_base64(&encode_base64(bytes)).unwrap(), bytes.to_vec());
+        }
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(decode_base64("Zm9v!mFy"), None);
+    }
+
+    #[test]
+    fn serialize_should_write_grid_as_rows_of_text() {
+        // given
+        let grid = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(serde_json::to_string(&grid).unwrap(), r#"{"width":3,"height":3,"rows":[".O.","..O","OOO"]}"#);
+    }
+
+    #[test]
+    fn deserialize_should_round_trip_grid_through_json() {
+        // given
+        let grid = Grid::random_seeded(13, 19, 11);
+
+        // when
+        let json = serde_json::to_string(&grid).unwrap();
+
+        // then
+        assert_eq!(serde_json::from_str::<Grid>(&json).unwrap(), grid);
+    }
+
+    #[test]
+    fn deserialize_should_accept_base64_bitmap() {
+        for &width in &[1, 7, 8, 9, 17] {
+            // given
+            let grid = Grid::random_seeded(width as u64, width, 5);
+            let json = format!(r#"{{"width":{},"height":5,"bitmap":"{}"}}"#, width, grid.to_base64_bitmap());
+
+            // then
+            assert_eq!(serde_json::from_str::<Grid>(&json).unwrap(), grid);
+        }
+    }
+
+    #[test]
+    fn deserialize_should_reject_mismatched_dimensions() {
+        assert_eq!(serde_json::from_str::<Grid>(r#"{"width":4,"height":2,"rows":["...","OOO"]}"#).is_err(), true);
+        assert_eq!(serde_json::from_str::<Grid>(r#"{"width":3,"height":3,"rows":["...","OOO"]}"#).is_err(), true);
+        assert_eq!(serde_json::from_str::<Grid>(r#"{"width":3,"height":2,"rows":["...","OO"]}"#).is_err(), true);
+        assert_eq!(serde_json::from_str::<Grid>(r#"{"width":8,"height":2,"bitmap":"AA=="}"#).is_err(), true);
+        assert_eq!(serde_json::from_str::<Grid>(r#"{"width":3,"height":1}"#).is_err(), true);
+        assert_eq!(serde_json::from_str::<Grid>(r#"{"width":0,"height":0,"rows":[]}"#).is_err(), true);
+    }
+
+    #[test]
+    fn deserialize_should_reject_bitmap_dimensions_overflowing() {
+        // given
+        let json = format!(r#"{{"width":{0},"height":{0},"bitmap":"AA=="}}"#, usize::MAX);
+
+        // when
+        let result = serde_json::from_str::<Grid>(&json);
+
+        // then
+        assert_eq!(result.unwrap_err().to_string(),
+            format!("line 1: bitmap for {0}x{0} is too large", usize::MAX));
+    }
+
+    #[test]
+    fn serialize_should_write_rule_and_cell_compactly() {
+        assert_eq!(serde_json::to_string(&Rule::HIGHLIFE).unwrap(), r#""B36/S23""#);
+        assert_eq!(serde_json::from_str::<Rule>(r#""B2/S34H""#).unwrap(), Rule::parse("B2/S34H").unwrap());
+        assert_eq!(serde_json::from_str::<Rule>(r#""B9/S""#).is_err(), true);
+        assert_eq!(serde_json::to_string(&Cell::alive()).unwrap(), "true");
+        assert_eq!(serde_json::from_str::<Cell>("false").unwrap(), Cell::dead());
+    }
+
+    #[test]
+    fn deserialize_should_round_trip_simulation_through_json() {
+        // given
+        let mut simulation = Simulation::new(Grid::random_seeded(1, 8, 8), Rule::HIGHLIFE).with_boundary(Boundary::Klein);
+        simulation.step();
+
+        // when
+        let json = serde_json::to_string(&simulation).unwrap();
+        let mut restored: Simulation = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(restored.grid(), simulation.grid());
+        assert_eq!(restored.generation(), 1);
+        restored.step();
+        simulation.step();
+        assert_eq!(restored.grid(), simulation.grid());
+    }
+}
+
+/*****************************************************************************/
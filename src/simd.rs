@@ -0,0 +1,118 @@
+/* simd.rs - Vectorised neighbour counting, enabled by the simd feature
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::convert::TryInto;
+use crate::{Cell, Grid, MaybeSync, Parallelism};
+
+/*****************************************************************************/
+
+// Number of cells counted at once, each lane being a u8 so that every step of
+// the loop is a single 128 bit vector operation
+const LANES: usize = 16;
+
+impl Grid {
+    // Write next Grid state into target of the same size, counting neighbours of
+    // LANES cells per iteration from column sums of the three rows around each row.
+    // The wrapping first and last columns of each row are counted separately
+    pub(crate) fn simd_step_into<F: Fn(&Cell, u8)->Cell + MaybeSync>(&self, target: &mut Grid, cell_func: F, parallelism: &Parallelism) {
+        let (width, height) = (self.width, self.height);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        parallelism.for_each_band(&mut target.cells, width, |top, band| {
+            let (mut columns, mut counts) = (vec![0u8; width], vec![0u8; width]);
+            for (dy, row) in band.chunks_mut(width).enumerate() {
+                self.simd_step_row(top + dy, row, &mut columns, &mut counts, &cell_func);
+            }
+        });
+    }
+
+    // Write next state of row y into row, using columns and counts as scratch space
+    fn simd_step_row<F: Fn(&Cell, u8)->Cell>(&self, y: usize, row: &mut [Cell], columns: &mut [u8], counts: &mut [u8], cell_func: &F) {
+        let (width, height) = (self.width, self.height);
+        let top    = &self.cells[(if y > 0 { y - 1 } else { height - 1 }) * width..][..width];
+        let middle = &self.cells[y * width..][..width];
+        let bottom = &self.cells[(if y < height - 1 { y + 1 } else { 0 }) * width..][..width];
+
+        // Live cells in each column of the three rows
+        for (((column, top), middle), bottom) in columns.chunks_mut(LANES).zip(top.chunks(LANES)).zip(middle.chunks(LANES)).zip(bottom.chunks(LANES)) {
+            for (((column, top), middle), bottom) in column.iter_mut().zip(top).zip(middle).zip(bottom) {
+                *column = top.alive as u8 + middle.alive as u8 + bottom.alive as u8;
+            }
+        }
+
+        // Columns either side of each inner cell, less the cell itself
+        if width > 2 {
+            let inner = counts[1..width - 1].chunks_exact_mut(LANES)
+                .zip(columns.chunks_exact(LANES))
+                .zip(columns[1..].chunks_exact(LANES))
+                .zip(columns[2..].chunks_exact(LANES))
+                .zip(middle[1..].chunks_exact(LANES));
+            for ((((count, left), centre), right), cell) in inner {
+                sum_lanes(count.try_into().unwrap(), left.try_into().unwrap(), centre.try_into().unwrap(),
+                    right.try_into().unwrap(), cell);
+            }
+        }
+
+        // Wrapping columns and any cells left over from whole lanes
+        let remainder = 1 + (width.saturating_sub(2) / LANES) * LANES;
+        for x in (remainder..width).chain(0..1) {
+            let left  = if x > 0 { x - 1 } else { width - 1 };
+            let right = if x < width - 1 { x + 1 } else { 0 };
+            counts[x] = columns[left] + columns[x] + columns[right] - middle[x].alive as u8;
+        }
+
+        for ((cell, next), &count) in middle.iter().zip(row.iter_mut()).zip(counts.iter()) {
+            *next = cell_func(cell, count);
+        }
+    }
+}
+
+// Set each lane of count to the sum of its left, centre and right columns less the live cell
+fn sum_lanes(count: &mut [u8; LANES], left: &[u8; LANES], centre: &[u8; LANES], right: &[u8; LANES], cells: &[Cell]) {
+    for ((((count, left), centre), right), cell) in count.iter_mut().zip(left).zip(centre).zip(right).zip(cells) {
+        *count = left + centre + right - cell.alive as u8;
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rule;
+
+    #[test]
+    fn next_should_match_scalar_given_widths_not_multiples_of_lanes() {
+        for &(width, height) in &[(1, 1), (2, 5), (3, 3), (15, 7), (17, 9), (18, 2), (33, 16), (100, 40)] {
+            // given
+            let grid = Grid::random_seeded(width as u64 * 31 + height as u64, width, height);
+
+            // when
+            let next = grid.next(Cell::next);
+
+            // then
+            assert_eq!(next, grid.next_scalar(Cell::next), "{}x{}", width, height);
+        }
+    }
+
+    #[test]
+    fn next_should_match_scalar_for_100_generations_of_soup() {
+        // given
+        let mut simd = Grid::random_seeded(11, 70, 45);
+        let mut scalar = simd.clone();
+
+        for generation in 0..100 {
+            // when
+            simd = simd.next(|cell, neighbours| Rule::HIGHLIFE.apply(cell, neighbours));
+            scalar = scalar.next_scalar(|cell, neighbours| Rule::HIGHLIFE.apply(cell, neighbours));
+
+            // then
+            assert_eq!(simd, scalar, "generation {}", generation);
+        }
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,1031 @@
+/* simulation.rs - Grid evolution under a Rule
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::{iter, mem};
+use core::borrow::BorrowMut;
+use alloc::collections::{BTreeMap, VecDeque};
+use core::hash::{Hash, Hasher};
+use alloc::vec::Vec;
+
+use crate::{AgedGrid, Boundary, Cell, EdgeMode, Fnv1a, Grid, Heatmap, HistoryError, Neighbourhood, Parallelism, Rule};
+use crate::history::{History, Step};
+
+/*****************************************************************************/
+
+// Number of periods a population must repeat for to be stable ignoring escaping patterns
+const STABLE_REPEATS: usize = 8;
+
+// Period of a repeating Grid state and the generation it was first reached
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CycleInfo {
+    pub period:             usize,
+    pub start_generation:   u64
+}
+
+// Population of a generation and the cells born and died stepping to it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: usize,
+    pub births:     usize,
+    pub deaths:     usize
+}
+
+// Criteria for Simulation::run_until_stable. A Simulation is stable once its Grid
+// repeats with a period of at most max_period, or if ignoring escaping patterns
+// once its population has repeated with such a period for STABLE_REPEATS periods,
+// as gliders flying off keep the population but not the Grid repeating
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StabilityOptions {
+    pub max_generations:    usize,
+    pub max_period:         usize,
+    pub ignore_escaping:    bool
+}
+
+impl Default for StabilityOptions {
+    fn default() -> StabilityOptions {
+        StabilityOptions { max_generations: 50_000, max_period: 30, ignore_escaping: false }
+    }
+}
+
+// Outcome of Simulation::run_until_stable, the generation being that at which the
+// repeating states start if stabilized and otherwise the last stepped to. Ignoring
+// escaping patterns the period is that of the population, which may divide the Grid's
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StabilityReport {
+    pub stabilized: bool,
+    pub generation: u64,
+    pub period:     Option<usize>,
+    pub population: usize
+}
+
+// Grid evolving under a Rule, counting generations.
+// Rules with B0 turn the dead background beyond the edges of the Grid alive, so
+// the background state is tracked and while it is alive each step is computed on
+// the inverted Grid with the inverted rule, keeping cells beyond the edges dead.
+// The cells changed by each step are tracked, and unless disabled the next step
+// only re-evaluates them and their neighbours, falling back to a full scan when
+// many cells changed, for B0 rules and for boundaries other than wrapped or dead edges.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Simulation {
+    grid:        Grid,
+    rule:        Rule,
+    boundary:    Boundary,
+    generation:  u64,
+    background:  bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    full_scan:   bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    parallelism: Parallelism,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stats:       Option<Vec<GenerationStats>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    heatmap:     Option<Heatmap>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    aged:        Option<AgedGrid>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history:     Option<History>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    back:        Option<Grid>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    changed:     Vec<(usize, usize)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    tracked:     bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    marks:       Vec<u64>
+}
+
+impl Simulation {
+    // Return a Simulation of given Grid at generation 0
+    pub fn new(grid: Grid, rule: Rule) -> Simulation {
+        Simulation {
+            grid,
+            rule,
+            boundary:    Boundary::default(),
+            generation:  0,
+            background:  false,
+            full_scan:   false,
+            parallelism: Parallelism::Auto,
+            stats:       None,
+            heatmap:     None,
+            aged:        None,
+            history:     None,
+            back:        None,
+            changed:     Vec::new(),
+            tracked:     false,
+            marks:       Vec::new()
+        }
+    }
+
+    // Return Simulation using given boundary
+    pub fn with_boundary(mut self, boundary: Boundary) -> Simulation {
+        self.boundary = boundary;
+        self
+    }
+
+    // Return Simulation only re-evaluating cells near those changed by the last
+    // step if incremental, otherwise scanning every cell each step
+    pub fn with_incremental(mut self, incremental: bool) -> Simulation {
+        self.full_scan = !incremental;
+        self
+    }
+
+    // Return Simulation spreading full scans across threads as given
+    pub fn with_parallelism(mut self, parallelism: Parallelism) -> Simulation {
+        self.parallelism = parallelism;
+        self
+    }
+
+    // Return Simulation recording GenerationStats of each generation from the
+    // current one if enabled, counted from the cells changed by each step
+    pub fn with_stats(mut self, enabled: bool) -> Simulation {
+        self.stats = if enabled { Some(Vec::new()) } else { None };
+        self.reset_stats();
+        self
+    }
+
+    // Return Simulation counting the generations each cell is alive from the next step
+    pub fn with_heatmap(mut self) -> Simulation {
+        self.heatmap = Some(Heatmap::new(self.grid.width(), self.grid.height()));
+        self
+    }
+
+    // Return counts of the generations each cell has been alive, empty if not enabled
+    pub fn heatmap(&self) -> &Heatmap {
+        static EMPTY: Heatmap = Heatmap::EMPTY;
+        self.heatmap.as_ref().unwrap_or(&EMPTY)
+    }
+
+    // Return Simulation counting the age of each live cell as AgedGrid does, the
+    // current ones being newborn. Cells set through grid_mut are newborn from the
+    // next step
+    pub fn with_ages(mut self) -> Simulation {
+        self.aged = Some(AgedGrid::new(self.grid.clone()));
+        self
+    }
+
+    // Return age of the cell at given position, or None if dead, out of bounds or
+    // ages are not enabled
+    pub fn age(&self, x: usize, y: usize) -> Option<u32> {
+        self.aged.as_ref().and_then(|aged| aged.age(x, y))
+    }
+
+    // Return Simulation recording up to capacity steps so that it can be rewound.
+    // Each step is stored as the cells it changed rather than a whole Grid
+    pub fn with_history(mut self, capacity: usize) -> Simulation {
+        self.history = Some(History::new(capacity));
+        self
+    }
+
+    // Return number of steps recorded which can be rewound
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, History::len)
+    }
+
+    // Undo the last n steps, discarding their stats. Stepping afterwards records new
+    // steps in place of those undone. Ages, if counted, start again from newborn
+    pub fn rewind(&mut self, n: usize) -> Result<(), HistoryError> {
+        let history = self.history.as_mut().ok_or(HistoryError::Disabled)?;
+        if n > history.len() {
+            return Err(HistoryError::Exhausted { requested: n, available: history.len() });
+        }
+
+        for _ in 0..n {
+            if let Some(step) = history.pop() {
+                if let Some(heatmap) = &mut self.heatmap {
+                    heatmap.forget(&self.grid);
+                }
+                for &(x, y) in &step.changed {
+                    let cell = &mut self.grid[(x, y)];
+                    cell.alive = !cell.alive;
+                }
+                self.background = step.background;
+                self.generation -= 1;
+            }
+        }
+
+        if let Some(aged) = &mut self.aged {
+            *aged = AgedGrid::new(self.grid.clone());
+        }
+
+        // The step to the current generation gives the cells to re-evaluate next
+        match history.last() {
+            Some(step) => self.changed.clone_from(&step.changed),
+            None => {
+                self.changed.clear();
+                self.tracked = false;
+            }
+        }
+        if let Some(stats) = &mut self.stats {
+            let generation = self.generation;
+            stats.retain(|stats| stats.generation <= generation);
+        }
+        Ok(())
+    }
+
+    // Return GenerationStats recorded since enabled or last reset, empty if not enabled
+    pub fn stats(&self) -> &[GenerationStats] {
+        self.stats.as_deref().unwrap_or(&[])
+    }
+
+    // Clear recorded GenerationStats, recording from the current generation again
+    pub fn reset_stats(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            stats.clear();
+            stats.push(GenerationStats { generation: self.generation, population: self.grid.population(), births: 0, deaths: 0 });
+        }
+    }
+
+    // Return current Grid state
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    // Return current Grid state to modify, the next step re-evaluates every cell
+    // and the steps recorded so far can no longer be rewound
+    pub fn grid_mut(&mut self) -> &mut Grid {
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+        self.tracked = false;
+        &mut self.grid
+    }
+
+    // Return Rule
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    // Replace the Rule later steps are under, keeping the Grid and generation. The
+    // next step re-evaluates every cell, the rule changing which cells may change
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.tracked = false;
+    }
+
+    // Replace the boundary later steps are under, keeping the Grid and generation.
+    // The next step re-evaluates every cell, as set_rule does
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.tracked = false;
+    }
+
+    // Return boundary
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    // Return number of live neighbours of each cell of the Grid indexed [y][x], in
+    // the rule's Neighbourhood and the boundary stepped with
+    pub fn neighbour_counts(&self) -> Vec<Vec<u8>> {
+        self.grid.neighbour_counts_with(self.rule.neighbourhood(), self.boundary)
+    }
+
+    // Return number of generations stepped
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Return positions of cells changed by the last step, in row major order
+    pub fn changed_cells(&self) -> &[(usize, usize)] {
+        &self.changed
+    }
+
+    // Advance one generation
+    pub fn step(&mut self) {
+        let background = self.background;
+        if self.is_incremental() {
+            self.step_changed();
+        }
+        else {
+            self.step_all();
+        }
+
+        let rule = self.rule;
+        let size = rule.neighbourhood().size() as u8;
+        self.background = if self.background { rule.survival(size) } else { rule.birth(0) };
+        self.generation += 1;
+
+        if let Some(history) = &mut self.history {
+            history.push(Step { changed: self.changed.clone(), background });
+        }
+
+        if let Some(heatmap) = &mut self.heatmap {
+            heatmap.record(&self.grid);
+        }
+        if let Some(aged) = &mut self.aged {
+            aged.record(&self.grid);
+        }
+
+        let grid = &self.grid;
+        if let Some(stats) = &mut self.stats {
+            let births = self.changed.iter().filter(|&&(x, y)| grid[(x, y)].alive).count();
+            let deaths = self.changed.len() - births;
+            // Population is counted again if the Grid may have been modified since the last step
+            let population = match stats.last() {
+                Some(last) if self.tracked => last.population + births - deaths,
+                _ => grid.population()
+            };
+            stats.push(GenerationStats { generation: self.generation, population, births, deaths });
+        }
+        self.tracked = true;
+    }
+
+    // Return an iterator over (generation, Grid) from the current generation on,
+    // stepping this Simulation as it goes
+    pub fn iter(&mut self) -> Generations<&mut Simulation> {
+        Generations { simulation: self, started: false }
+    }
+
+    // Advance n generations
+    pub fn step_n(&mut self, n: u64) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    // Step until the Grid and background repeat a state seen since the current
+    // generation, for at most max_generations, returning the cycle or None if no
+    // state repeated. States are looked up by hash, a hit being confirmed by
+    // comparing the Grids
+    pub fn detect_cycle(&mut self, max_generations: usize) -> Option<CycleInfo> {
+        let mut seen: BTreeMap<u64, Vec<(u64, Grid, bool)>> = BTreeMap::new();
+        for stepped in 0..=max_generations {
+            if stepped > 0 {
+                self.step();
+            }
+
+            let states = seen.entry(self.state_hash()).or_default();
+            let repeat = states.iter().find(|(_, grid, background)| grid == &self.grid && *background == self.background);
+            if let Some(&(start_generation, ..)) = repeat {
+                return Some(CycleInfo { period: (self.generation - start_generation) as usize, start_generation });
+            }
+            states.push((self.generation, self.grid.clone(), self.background));
+        }
+        None
+    }
+
+    // Step until stable by given criteria or for at most max_generations, returning
+    // how it stabilized. Only the last max_period states are kept to compare against
+    pub fn run_until_stable(&mut self, options: StabilityOptions) -> StabilityReport {
+        let max_period = options.max_period.max(1);
+        let mut population = self.grid.population();
+        let mut grids: VecDeque<(u64, Grid, bool)> = VecDeque::with_capacity(max_period + 1);
+        let mut populations: VecDeque<usize> = VecDeque::with_capacity(max_period * (STABLE_REPEATS + 1) + 1);
+
+        for stepped in 0..=options.max_generations {
+            // Population is counted from the cells each step changed rather than recounted
+            if stepped > 0 {
+                self.step();
+                let births = self.changed.iter().filter(|&&(x, y)| self.grid[(x, y)].alive).count();
+                population = population + births - (self.changed.len() - births);
+            }
+
+            // Period and generation the repeating states start, if repeating
+            let period = if options.ignore_escaping {
+                populations.push_back(population);
+                if populations.len() > max_period * (STABLE_REPEATS + 1) {
+                    populations.pop_front();
+                }
+                (1..=max_period).find(|&period| period * (STABLE_REPEATS + 1) <= populations.len()
+                    && populations.iter().rev().zip(populations.iter().rev().skip(period)).take(period * STABLE_REPEATS)
+                        .all(|(now, before)| now == before))
+                    .map(|period| (period, self.generation + 1 - (period * (STABLE_REPEATS + 1)) as u64))
+            }
+            else {
+                let hash = self.state_hash();
+                let period = grids.iter().rev()
+                    .position(|(seen, grid, background)| *seen == hash && grid == &self.grid && *background == self.background)
+                    .map(|position| (position + 1, self.generation - position as u64 - 1));
+                grids.push_back((hash, self.grid.clone(), self.background));
+                if grids.len() > max_period {
+                    grids.pop_front();
+                }
+                period
+            };
+
+            if let Some((period, generation)) = period {
+                return StabilityReport { stabilized: true, generation, period: Some(period), population };
+            }
+        }
+        StabilityReport { stabilized: false, generation: self.generation, period: None, population }
+    }
+
+    // Return hash of the Grid and background, which together give the next states
+    fn state_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        self.grid.hash(&mut hasher);
+        self.background.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Return true if the next step need only re-evaluate cells near those last changed
+    fn is_incremental(&self) -> bool {
+        let (x, y) = self.boundary.edges();
+        let local = |mode| mode == EdgeMode::Wrap || mode == EdgeMode::Dead;
+        let dirty = self.changed.len() * (self.rule.neighbourhood().size() as usize + 1);
+
+        !self.full_scan && self.tracked && !self.rule.birth(0) && local(x) && local(y)
+            && dirty <= self.grid.width() * self.grid.height() / 4
+    }
+
+    // Advance one generation re-evaluating every cell, noting those that change.
+    // The next state is written into the previous Grid kept from the last full scan
+    fn step_all(&mut self) {
+        let rule = self.rule;
+        let neighbourhood = rule.neighbourhood();
+        let size = neighbourhood.size() as u8;
+
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let mut next = match self.back.take() {
+            Some(back) if back.width() == width && back.height() == height => back,
+            _ => self.grid.clone()
+        };
+
+        if self.background {
+            self.grid.invert().step_with_into(&mut next, |cell, neighbours|
+                Cell::new(if cell.is_alive() { rule.birth(size - neighbours) } else { rule.survival(size - neighbours) }),
+                neighbourhood, self.boundary, &self.parallelism);
+        }
+        else {
+            self.grid.step_with_into(&mut next, |cell, neighbours| rule.apply(cell, neighbours), neighbourhood, self.boundary, &self.parallelism);
+        }
+
+        self.changed = next.cells.iter().zip(&self.grid.cells).enumerate()
+            .filter(|(_, (next, cell))| next != cell)
+            .map(|(i, _)| (i % width, i / width))
+            .collect();
+        self.back = Some(mem::replace(&mut self.grid, next));
+    }
+
+    // Advance one generation re-evaluating only cells changed by the last step and
+    // those with them as neighbours, the only cells that can change
+    fn step_changed(&mut self) {
+        let (rule, boundary) = (self.rule, self.boundary);
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let offsets = rule.neighbourhood().offsets();
+        let moore_torus = rule.neighbourhood() == Neighbourhood::MOORE && boundary == Boundary::Torus;
+
+        // Each cell to re-evaluate is marked with the generation to skip duplicates
+        let stamp = self.generation + 1;
+        self.marks.resize(width * height, 0);
+        let mut dirty = Vec::with_capacity(self.changed.len() * (offsets.len() + 1));
+        for &(x, y) in &self.changed {
+            for &(dx, dy) in iter::once(&(0, 0)).chain(&offsets) {
+                if let Some((nx, ny)) = boundary.resolve(x as isize - dx, y as isize - dy, width, height) {
+                    let mark = &mut self.marks[ny * width + nx];
+                    if *mark != stamp {
+                        *mark = stamp;
+                        dirty.push((nx, ny));
+                    }
+                }
+            }
+        }
+
+        let grid = &self.grid;
+        self.changed = dirty.into_iter()
+            .filter(|&(x, y)| {
+                let neighbours = if moore_torus { grid.neighbours(x, y) } else { grid.neighbours_with(x, y, &offsets, boundary) };
+                rule.apply(&grid[(x, y)], neighbours) != grid[(x, y)]
+            })
+            .collect();
+        self.changed.sort_unstable_by_key(|&(x, y)| (y, x));
+        for &(x, y) in &self.changed {
+            let cell = &mut self.grid[(x, y)];
+            cell.alive = !cell.alive;
+        }
+    }
+}
+
+// Iterator over (generation, Grid) of a Simulation, owned or borrowed, starting
+// with the current generation. A Simulation only holds its current Grid, so each
+// Grid yielded by next is a clone made when it is asked for. Generations passed
+// over by nth, and so by skip and step_by, are stepped without being cloned
+pub struct Generations<S> {
+    simulation: S,
+    started:    bool
+}
+
+impl<S: BorrowMut<Simulation>> Iterator for Generations<S> {
+    type Item = (u64, Grid);
+
+    fn next(&mut self) -> Option<(u64, Grid)> {
+        self.nth(0)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<(u64, Grid)> {
+        let simulation = self.simulation.borrow_mut();
+        simulation.step_n(n as u64 + self.started as u64);
+        self.started = true;
+        Some((simulation.generation(), simulation.grid().clone()))
+    }
+}
+
+impl IntoIterator for Simulation {
+    type Item = (u64, Grid);
+    type IntoIter = Generations<Simulation>;
+
+    fn into_iter(self) -> Generations<Simulation> {
+        Generations { simulation: self, started: false }
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_should_advance_grid_and_generation() {
+        // given
+        let grid = Grid::from_text(".....\n..O..\n..O..\n..O..\n.....", 'O').unwrap();
+        let mut simulation = Simulation::new(grid.clone(), Rule::CONWAY);
+
+        // when
+        simulation.step();
+
+        // then
+        assert_eq!(simulation.generation(), 1);
+        assert_eq!(simulation.grid(), &grid.next(Cell::next));
+    }
+
+    #[test]
+    fn neighbour_counts_should_use_boundary_and_neighbourhood_of_rule() {
+        // given
+        let grid = Grid::from_coords(3, 3, vec![(0, 0)]).unwrap();
+        let rule = Rule::parse("B2/S34V").unwrap();
+
+        // then
+        assert_eq!(Simulation::new(grid.clone(), Rule::CONWAY).neighbour_counts(), grid.neighbour_counts());
+        assert_eq!(Simulation::new(grid, rule).with_boundary(Boundary::Dead).neighbour_counts(), vec![
+            vec![0, 1, 0],
+            vec![1, 0, 0],
+            vec![0, 0, 0]
+        ]);
+    }
+
+    #[test]
+    fn step_n_should_move_glider_one_cell_diagonally_every_4_generations() {
+        // given
+        let glider = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+        let mut grid = Grid::new(12, 12).unwrap();
+        grid.insert(&glider, 0, 0, crate::InsertMode::Replace, crate::Overflow::Clip);
+        let mut simulation = Simulation::new(grid, Rule::CONWAY).with_boundary(Boundary::Dead);
+
+        // when
+        simulation.step_n(20);
+
+        // then
+        let mut expected = Grid::new(12, 12).unwrap();
+        expected.insert(&glider, 5, 5, crate::InsertMode::Replace, crate::Overflow::Clip);
+        assert_eq!(simulation.generation(), 20);
+        assert_eq!(simulation.grid(), &expected);
+        simulation.step();
+        assert_eq!(simulation.generation(), 21);
+    }
+
+    #[test]
+    fn iter_should_yield_identical_grids_given_step_by_2_over_blinker() {
+        // given
+        let blinker = Grid::from_text(".....\n..O..\n..O..\n..O..\n.....", 'O').unwrap();
+        let mut simulation = Simulation::new(blinker.clone(), Rule::CONWAY);
+
+        // when
+        let generations: Vec<(u64, Grid)> = simulation.iter().step_by(2).take(4).collect();
+
+        // then
+        assert_eq!(generations.iter().map(|(generation, _)| *generation).collect::<Vec<u64>>(), vec![0, 2, 4, 6]);
+        assert!(generations.iter().all(|(_, grid)| grid == &blinker));
+        assert_eq!(simulation.generation(), 6);
+        assert_eq!(simulation.iter().next(), Some((6, blinker)));
+    }
+
+    #[test]
+    fn into_iter_should_compose_with_iterator_adapters() {
+        // given
+        let grid = Grid::from_text("......\n.O....\n..O...\n...O..\n......\n......", 'O').unwrap();
+        let simulation = Simulation::new(grid, Rule::CONWAY);
+
+        // when
+        let living: Vec<u64> = simulation.into_iter().take_while(|(_, grid)| grid.population() > 0).map(|(generation, _)| generation).collect();
+
+        // then
+        assert_eq!(living, vec![0, 1]);
+        assert_eq!(Simulation::new(Grid::new(3, 3).unwrap(), Rule::CONWAY).into_iter().nth(10).map(|(generation, _)| generation), Some(10));
+    }
+
+    // Return a Simulation with dead edges of a pattern centred in a Grid of given size
+    fn centred(pattern: &str, width: usize, height: usize) -> Simulation {
+        let mut grid = Grid::new(width, height).unwrap();
+        grid.insert(&Grid::from_text(pattern, 'O').unwrap(), 0, 0, crate::InsertMode::Replace, crate::Overflow::Clip);
+        let grid = grid.translate((width as isize - pattern.lines().next().unwrap().len() as isize) / 2,
+            (height as isize - pattern.lines().count() as isize) / 2);
+        Simulation::new(grid, Rule::CONWAY).with_boundary(Boundary::Dead)
+    }
+
+    #[test]
+    fn detect_cycle_should_report_period_of_still_lifes_and_oscillators() {
+        let pulsar = "..OOO...OOO..\n.............\nO....O.O....O\nO....O.O....O\nO....O.O....O\n..OOO...OOO..\n.............\n\
+                      ..OOO...OOO..\nO....O.O....O\nO....O.O....O\nO....O.O....O\n.............\n..OOO...OOO..";
+        let pentadecathlon = "..O....O..\nOO.OOOO.OO\n..O....O..";
+
+        for &(pattern, width, height, period) in &[("OO\nOO", 4, 4, 1), ("OOO", 5, 5, 2), (pulsar, 17, 17, 3), (pentadecathlon, 18, 11, 15)] {
+            // given
+            let mut simulation = centred(pattern, width, height);
+
+            // when
+            let cycle = simulation.detect_cycle(100);
+
+            // then
+            assert_eq!(cycle, Some(CycleInfo { period, start_generation: 0 }), "{}", pattern);
+            assert_eq!(simulation.generation(), period as u64);
+        }
+    }
+
+    #[test]
+    fn detect_cycle_should_report_generation_cycle_starts() {
+        // given
+        let mut simulation = centred("O..\n.O.\n..O", 6, 6);
+        simulation.step();
+
+        // when
+        let cycle = simulation.detect_cycle(10);
+
+        // then
+        assert_eq!(cycle, Some(CycleInfo { period: 1, start_generation: 2 }));
+    }
+
+    #[test]
+    fn detect_cycle_should_tell_background_phases_apart_given_b0_rule() {
+        // given
+        // The Grid repeats from generation 1 to 2, but with the background strobing
+        let rule = Rule::parse("B0/S").unwrap();
+        let mut simulation = Simulation::new(Grid::from_text(".O", 'O').unwrap(), rule).with_boundary(Boundary::Dead);
+
+        // when
+        let cycle = simulation.detect_cycle(10);
+
+        // then
+        assert_eq!(cycle, Some(CycleInfo { period: 2, start_generation: 2 }));
+        let mut stable = Simulation::new(Grid::from_text(".O", 'O').unwrap(), rule).with_boundary(Boundary::Dead);
+        assert_eq!(stable.run_until_stable(StabilityOptions::default()).period, Some(2));
+    }
+
+    #[test]
+    fn detect_cycle_should_return_none_given_budget_exhausted() {
+        // given
+        let mut grid = Grid::new(20, 20).unwrap();
+        grid.insert(&Grid::from_text(".O.\n..O\nOOO", 'O').unwrap(), 0, 0, crate::InsertMode::Replace, crate::Overflow::Clip);
+        let mut simulation = Simulation::new(grid.clone(), Rule::CONWAY);
+
+        // then
+        assert_eq!(simulation.detect_cycle(79), None);
+        assert_eq!(simulation.generation(), 79);
+        assert_eq!(Simulation::new(grid, Rule::CONWAY).detect_cycle(80), Some(CycleInfo { period: 80, start_generation: 0 }));
+    }
+
+    #[test]
+    fn stats_should_record_population_of_r_pentomino() {
+        // given
+        let mut simulation = centred(".OO\nOO.\n.O.", 600, 600).with_stats(true);
+
+        // when
+        simulation.step_n(1103);
+
+        // then
+        let stats = simulation.stats();
+        assert_eq!(stats.len(), 1104);
+        assert_eq!(stats[0], GenerationStats { generation: 0, population: 5, births: 0, deaths: 0 });
+        assert_eq!(stats[1103].generation, 1103);
+        assert_eq!(stats[1103].population, 116);
+        assert_eq!(stats[1103].population, simulation.grid().population());
+        assert!(stats.windows(2).all(|pair| pair[1].population == pair[0].population + pair[1].births - pair[1].deaths));
+    }
+
+    #[test]
+    fn stats_should_record_no_births_or_deaths_given_still_life() {
+        // given
+        let mut simulation = centred("OO\nOO", 6, 6).with_stats(true);
+
+        // when
+        simulation.step_n(5);
+
+        // then
+        assert_eq!(simulation.stats().len(), 6);
+        assert!(simulation.stats().iter().all(|stats| stats.population == 4 && stats.births == 0 && stats.deaths == 0));
+        simulation.reset_stats();
+        assert_eq!(simulation.stats(), &[GenerationStats { generation: 5, population: 4, births: 0, deaths: 0 }]);
+        assert_eq!(Simulation::new(Grid::new(2, 2).unwrap(), Rule::CONWAY).stats(), &[]);
+    }
+
+    #[test]
+    fn rewind_should_restore_earlier_generation_and_step_forward_again() {
+        // given
+        let mut simulation = Simulation::new(Grid::random_seeded(6, 30, 20), Rule::CONWAY).with_history(256).with_stats(true);
+        let mut snapshots = vec![simulation.grid().clone()];
+        for _ in 0..10 {
+            simulation.step();
+            snapshots.push(simulation.grid().clone());
+        }
+
+        // when
+        simulation.rewind(3).unwrap();
+
+        // then
+        assert_eq!(simulation.generation(), 7);
+        assert_eq!(simulation.grid(), &snapshots[7]);
+        assert_eq!(simulation.history_len(), 7);
+        assert_eq!(simulation.stats().last().map(|stats| stats.generation), Some(7));
+        for snapshot in &snapshots[8..] {
+            simulation.step();
+            assert_eq!(simulation.grid(), snapshot, "generation {}", simulation.generation());
+        }
+        assert_eq!(simulation.history_len(), 10);
+        simulation.rewind(10).unwrap();
+        assert_eq!(simulation.grid(), &snapshots[0]);
+    }
+
+    #[test]
+    fn rewind_should_restore_background_given_b0_rule() {
+        // given
+        let rule = Rule::parse("B0123478/S01234678").unwrap();
+        let mut simulation = Simulation::new(Grid::random_seeded(2, 12, 10), rule).with_boundary(Boundary::Dead).with_history(4);
+        let mut snapshots = vec![simulation.grid().clone()];
+        for _ in 0..3 {
+            simulation.step();
+            snapshots.push(simulation.grid().clone());
+        }
+
+        // when
+        simulation.rewind(2).unwrap();
+
+        // then
+        assert_eq!(simulation.grid(), &snapshots[1]);
+        simulation.step_n(2);
+        assert_eq!(simulation.grid(), &snapshots[3]);
+        simulation.rewind(3).unwrap();
+        simulation.step_n(3);
+        assert_eq!(simulation.grid(), &snapshots[3]);
+    }
+
+    #[test]
+    fn rewind_should_return_error_given_steps_not_recorded() {
+        // given
+        let mut simulation = Simulation::new(Grid::random_seeded(6, 10, 10), Rule::CONWAY).with_history(4);
+        simulation.step_n(10);
+
+        // then
+        assert_eq!(simulation.history_len(), 4);
+        assert_eq!(simulation.rewind(5), Err(HistoryError::Exhausted { requested: 5, available: 4 }));
+        assert_eq!(simulation.generation(), 10);
+        assert_eq!(Simulation::new(Grid::new(2, 2).unwrap(), Rule::CONWAY).with_history(4).rewind(1),
+            Err(HistoryError::Exhausted { requested: 1, available: 0 }));
+        assert_eq!(Simulation::new(Grid::new(2, 2).unwrap(), Rule::CONWAY).rewind(1), Err(HistoryError::Disabled));
+        simulation.grid_mut().set(0, 0, true).unwrap();
+        assert_eq!(simulation.history_len(), 0);
+    }
+
+    #[test]
+    fn run_until_stable_should_report_period_2_given_blinker() {
+        // given
+        let mut simulation = centred("OOO", 7, 7);
+
+        // when
+        let report = simulation.run_until_stable(StabilityOptions::default());
+
+        // then
+        assert_eq!(report, StabilityReport { stabilized: true, generation: 0, period: Some(2), population: 3 });
+        assert_eq!(simulation.generation(), 2);
+    }
+
+    #[test]
+    fn run_until_stable_should_stabilize_at_5206_given_acorn_ignoring_escaping_gliders() {
+        // given
+        let mut simulation = centred(".O.....\n...O...\nOO..OOO", 2600, 2600);
+        let options = StabilityOptions { max_generations: 6000, ignore_escaping: true, ..StabilityOptions::default() };
+
+        // when
+        let report = simulation.run_until_stable(options);
+
+        // then
+        assert!(report.stabilized);
+        assert_eq!(report.generation, 5206);
+        assert_eq!(report.population, 633);
+    }
+
+    #[test]
+    fn run_until_stable_should_not_stabilize_given_exhausted_budget() {
+        // given
+        let glider = Grid::from_coords(20, 20, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]).unwrap();
+        let mut simulation = Simulation::new(glider, Rule::CONWAY);
+        let options = StabilityOptions { max_generations: 100, ..StabilityOptions::default() };
+
+        // when
+        let report = simulation.run_until_stable(options);
+
+        // then
+        assert_eq!(report, StabilityReport { stabilized: false, generation: 100, period: None, population: 5 });
+    }
+
+    #[test]
+    fn heatmap_should_count_generations_alive_given_block() {
+        // given
+        let mut simulation = centred("OO\nOO", 6, 6).with_heatmap();
+
+        // when
+        simulation.step_n(100);
+
+        // then
+        let heatmap = simulation.heatmap();
+        for y in 0..6 {
+            for x in 0..6 {
+                let count = if (2..4).contains(&x) && (2..4).contains(&y) { 100 } else { 0 };
+                assert_eq!(heatmap.get(x, y), Some(count), "({}, {})", x, y);
+            }
+        }
+        assert_eq!(Simulation::new(Grid::new(2, 2).unwrap(), Rule::CONWAY).heatmap(), &Heatmap::default());
+    }
+
+    #[test]
+    fn age_should_count_generations_survived_given_blinker() {
+        // given
+        let mut simulation = centred("OOO", 5, 5).with_ages().with_history(4);
+
+        // when
+        simulation.step_n(3);
+
+        // then
+        assert_eq!(simulation.age(2, 2), Some(3));
+        assert_eq!((simulation.age(2, 1), simulation.age(1, 2)), (Some(0), None));
+        simulation.rewind(1).unwrap();
+        assert_eq!((simulation.age(2, 2), simulation.age(1, 2)), (Some(0), Some(0)));
+        assert_eq!(Simulation::new(Grid::new(2, 2).unwrap(), Rule::CONWAY).age(0, 0), None);
+    }
+
+    #[test]
+    fn heatmap_should_show_diagonal_stripe_given_glider() {
+        // given
+        let glider = Grid::from_coords(30, 30, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]).unwrap();
+        let mut simulation = Simulation::new(glider, Rule::CONWAY).with_heatmap().with_history(8);
+
+        // when
+        simulation.step_n(80);
+
+        // then
+        let heatmap = simulation.heatmap();
+        for y in 0..30 {
+            for x in 0..30 {
+                let count = heatmap.get(x, y).unwrap();
+                if (x as isize - y as isize).abs() > 2 || x > 23 || y > 23 {
+                    assert_eq!(count, 0, "({}, {})", x, y);
+                }
+            }
+        }
+        for k in 2..21 {
+            assert!(heatmap.get(k, k).unwrap() > 0, "({}, {})", k, k);
+        }
+
+        // Rewinding takes back the counts of the generations undone
+        let before = heatmap.clone();
+        simulation.step_n(4);
+        simulation.rewind(4).unwrap();
+        assert_eq!(simulation.heatmap(), &before);
+    }
+
+    #[test]
+    fn grid_mut_should_change_grid_for_next_step() {
+        // given
+        let mut simulation = Simulation::new(Grid::new(6, 6).unwrap(), Rule::CONWAY);
+        simulation.step_n(3);
+
+        // when
+        for &(x, y) in &[(2, 1), (2, 2), (2, 3)] {
+            simulation.grid_mut().set(x, y, true).unwrap();
+        }
+        simulation.step();
+
+        // then
+        assert_eq!(simulation.grid(), &Grid::from_text("......\n......\n.OOO..\n......\n......\n......", 'O').unwrap());
+        assert_eq!(simulation.changed_cells(), &[(2, 1), (1, 2), (3, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn step_should_match_direct_rule_given_torus_and_b0_rule() {
+        // given
+        let rule = Rule::parse("B0123478/S01234678").unwrap();
+        let mut grid = Grid::random_seeded(8, 16, 12);
+        let mut simulation = Simulation::new(grid.clone(), rule);
+
+        for _ in 0..10 {
+            // when
+            simulation.step();
+            grid = grid.next_with_rule(&rule);
+
+            // then
+            assert_eq!(simulation.grid(), &grid);
+        }
+    }
+
+    #[test]
+    fn set_rule_should_step_same_grid_under_new_rule() {
+        // given
+        let mut simulation = Simulation::new(Grid::random_seeded(4, 16, 12), Rule::CONWAY);
+        simulation.step_n(20);
+        let grid = simulation.grid().clone();
+
+        // when
+        simulation.set_rule(Rule::HIGHLIFE);
+        simulation.step_n(3);
+
+        // then
+        assert_eq!((simulation.rule(), simulation.generation()), (&Rule::HIGHLIFE, 23));
+        assert_eq!(simulation.grid(), &grid.next_with_rule(&Rule::HIGHLIFE).next_with_rule(&Rule::HIGHLIFE).next_with_rule(&Rule::HIGHLIFE));
+    }
+
+    #[test]
+    fn set_boundary_should_step_same_grid_under_new_boundary() {
+        // given
+        let mut simulation = Simulation::new(Grid::random_seeded(5, 16, 12), Rule::CONWAY);
+        simulation.step_n(10);
+        let grid = simulation.grid().clone();
+
+        // when
+        simulation.set_boundary(Boundary::Mirror);
+        simulation.step();
+
+        // then
+        assert_eq!((simulation.boundary(), simulation.generation()), (Boundary::Mirror, 11));
+        assert_eq!(simulation.grid(), &grid.next_with(|cell, n| Rule::CONWAY.apply(cell, n), Neighbourhood::MOORE, Boundary::Mirror));
+    }
+
+    #[test]
+    fn step_should_evolve_antilife_as_complement_of_conway_given_dead_boundary() {
+        // given
+        let antilife = Rule::parse("B0123478/S01234678").unwrap();
+        let grid = Grid::random_seeded(9, 16, 12);
+        let mut simulation = Simulation::new(grid, antilife).with_boundary(Boundary::Dead);
+        simulation.step();
+
+        for _ in 0..10 {
+            // when
+            let conway = simulation.grid().invert().next_with(|cell, n| Rule::CONWAY.apply(cell, n), Neighbourhood::MOORE, Boundary::Dead);
+            simulation.step();
+
+            // then
+            assert_eq!(simulation.grid(), &conway.invert());
+        }
+    }
+
+    #[test]
+    fn step_should_strobe_background_given_b0_rule_without_s8() {
+        // given
+        let rule = Rule::parse("B0/S").unwrap();
+        let mut simulation = Simulation::new(Grid::new(4, 4).unwrap(), rule).with_boundary(Boundary::Dead);
+
+        // then
+        simulation.step();
+        assert_eq!(simulation.grid().population(), 16);
+        simulation.step();
+        assert_eq!(simulation.grid().population(), 0);
+        simulation.step();
+        assert_eq!(simulation.grid().population(), 16);
+    }
+
+    #[test]
+    fn step_should_match_full_scans_for_500_generations_given_incremental() {
+        for &(rule, boundary) in &[(Rule::CONWAY, Boundary::Torus), (Rule::HIGHLIFE, Boundary::Dead), (Rule::CONWAY, Boundary::Klein)] {
+            // given
+            let grid = Grid::random_seeded(5, 48, 32);
+            let mut full = Simulation::new(grid.clone(), rule).with_boundary(boundary).with_incremental(false);
+            let mut incremental = Simulation::new(grid, rule).with_boundary(boundary);
+
+            for generation in 0..500 {
+                // when
+                full.step();
+                incremental.step();
+
+                // then
+                assert_eq!(incremental.grid(), full.grid(), "{:?} generation {}", boundary, generation);
+                assert_eq!(incremental.changed_cells(), full.changed_cells(), "{:?} generation {}", boundary, generation);
+            }
+        }
+    }
+
+    #[test]
+    fn changed_cells_should_list_cells_changed_by_last_step() {
+        // given
+        let grid = Grid::from_text(".....\n..O..\n..O..\n..O..\n.....", 'O').unwrap();
+        let mut simulation = Simulation::new(grid, Rule::CONWAY);
+
+        // then
+        assert_eq!(simulation.changed_cells(), &[]);
+        simulation.step();
+        assert_eq!(simulation.changed_cells(), &[(2, 1), (1, 2), (3, 2), (2, 3)]);
+        simulation.step();
+        assert_eq!(simulation.changed_cells(), &[(2, 1), (1, 2), (3, 2), (2, 3)]);
+    }
+}
+
+/*****************************************************************************/
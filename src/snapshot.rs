@@ -0,0 +1,178 @@
+/* snapshot.rs - Compact binary Grid snapshots
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::{error, fmt};
+use core::convert::TryInto;
+use alloc::vec::Vec;
+
+use crate::{Cell, Grid, LifeError};
+
+/*****************************************************************************/
+
+// Snapshot layout: magic bytes, format version, width and height as u32 little
+// endian, then rows of cells packed most significant bit first, each padded to
+// a whole byte
+const MAGIC:        &[u8; 4] = b"RSLF";
+const VERSION:      u8 = 1;
+const HEADER_LEN:   usize = 13;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum SnapshotError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    Truncated { expected: usize, found: usize },
+    TrailingBytes { expected: usize, found: usize },
+    InvalidGrid(LifeError)
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::BadMagic => write!(f, "data is not a grid snapshot"),
+            SnapshotError::UnsupportedVersion(version) =>
+                write!(f, "snapshot format version {} is not supported, expected {}", version, VERSION),
+            SnapshotError::Truncated { expected, found } =>
+                write!(f, "snapshot is truncated, {} bytes found, expected {}", found, expected),
+            SnapshotError::TrailingBytes { expected, found } =>
+                write!(f, "snapshot has trailing bytes, {} bytes found, expected {}", found, expected),
+            SnapshotError::InvalidGrid(err) => write!(f, "{}", err)
+        }
+    }
+}
+
+impl error::Error for SnapshotError {}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return binary snapshot of the Grid
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.width().div_ceil(8) * self.height());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(self.width() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.height() as u32).to_le_bytes());
+        bytes.extend(self.packed_rows());
+        bytes
+    }
+
+    // Return a Grid read from a binary snapshot
+    pub fn from_bytes(bytes: &[u8]) -> Result<Grid, SnapshotError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(SnapshotError::Truncated { expected: HEADER_LEN, found: bytes.len() });
+        }
+        if bytes[4] != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(bytes[4]));
+        }
+
+        let read_u32 = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap_or_default()) as usize;
+        let (width, height) = (read_u32(5), read_u32(9));
+        let expected = width.div_ceil(8).checked_mul(height).and_then(|len| len.checked_add(HEADER_LEN)).unwrap_or(usize::MAX);
+        if bytes.len() < expected {
+            return Err(SnapshotError::Truncated { expected, found: bytes.len() });
+        }
+        if bytes.len() > expected {
+            return Err(SnapshotError::TrailingBytes { expected, found: bytes.len() });
+        }
+
+        Grid::from_packed_rows(width, height, &bytes[HEADER_LEN..]).map_err(SnapshotError::InvalidGrid)
+    }
+
+    // Return rows of cells packed most significant bit first, each padded to a whole byte
+    pub(crate) fn packed_rows(&self) -> Vec<u8> {
+        self.rows()
+            .flat_map(|row| row.chunks(8)
+                .map(|chunk| chunk.iter().enumerate().fold(0u8, |byte, (i, cell)| byte | ((cell.alive as u8) << (7 - i)))))
+            .collect()
+    }
+
+    // Return a Grid of given size from rows packed as by packed_rows, which must be of the right length
+    pub(crate) fn from_packed_rows(width: usize, height: usize, bytes: &[u8]) -> Result<Grid, LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        for (row, packed) in grid.cells.chunks_mut(width).zip(bytes.chunks(width.div_ceil(8))) {
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = Cell::new(packed[x / 8] & (0x80 >> (x % 8)) != 0);
+            }
+        }
+        Ok(grid)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_bytes_should_write_header_and_packed_rows() {
+        // given
+        let grid = Grid::from_text("O.......O\n.O.......", 'O').unwrap();
+
+        // then
+        assert_eq!(grid.to_bytes(), vec![b'R', b'S', b'L', b'F', 1, 9, 0, 0, 0, 2, 0, 0, 0, 0x80, 0x80, 0x40, 0x00]);
+    }
+
+    #[test]
+    fn from_bytes_should_round_trip_awkward_widths() {
+        for &width in &[1, 7, 8, 9, 63, 64, 65] {
+            // given
+            let grid = Grid::random_seeded(width as u64, width, 11);
+
+            // when
+            let bytes = grid.to_bytes();
+
+            // then
+            assert_eq!(bytes.len(), HEADER_LEN + width.div_ceil(8) * 11);
+            assert_eq!(Grid::from_bytes(&bytes).unwrap(), grid);
+        }
+    }
+
+    #[test]
+    fn from_bytes_should_reject_other_data_and_versions() {
+        // given
+        let mut bytes = Grid::random_seeded(1, 10, 10).to_bytes();
+
+        // then
+        assert_eq!(Grid::from_bytes(b"GIF89a"), Err(SnapshotError::BadMagic));
+        assert_eq!(Grid::from_bytes(b""),       Err(SnapshotError::BadMagic));
+        bytes[4] = 2;
+        assert_eq!(Grid::from_bytes(&bytes), Err(SnapshotError::UnsupportedVersion(2)));
+        assert_eq!(Grid::from_bytes(&bytes).unwrap_err().to_string(), "snapshot format version 2 is not supported, expected 1");
+    }
+
+    #[test]
+    fn from_bytes_should_return_error_given_truncated_input() {
+        // given
+        let bytes = Grid::random_seeded(1, 10, 10).to_bytes();
+
+        // then
+        for len in 4..bytes.len() {
+            assert!(matches!(Grid::from_bytes(&bytes[..len]), Err(SnapshotError::Truncated { .. })), "length {}", len);
+        }
+        assert_eq!(Grid::from_bytes(&bytes[..20]), Err(SnapshotError::Truncated { expected: 33, found: 20 }));
+    }
+
+    #[test]
+    fn from_bytes_should_return_error_given_invalid_dimensions() {
+        // given
+        let mut huge = b"RSLF\x01".to_vec();
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        let mut empty = b"RSLF\x01".to_vec();
+        empty.extend_from_slice(&[0; 8]);
+
+        // then
+        assert!(matches!(Grid::from_bytes(&huge), Err(SnapshotError::Truncated { .. })));
+        assert_eq!(Grid::from_bytes(&empty), Err(SnapshotError::InvalidGrid(LifeError::ZeroDimension { width: 0, height: 0 })));
+        let mut trailing = Grid::random_seeded(1, 3, 3).to_bytes();
+        trailing.push(0);
+        assert_eq!(Grid::from_bytes(&trailing), Err(SnapshotError::TrailingBytes { expected: 16, found: 17 }));
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,279 @@
+/* sparse.rs - Sparse storage of live cells on an unbounded plane
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{Cell, Grid, LifeError, Rule};
+
+/*****************************************************************************/
+
+// Period of a pattern which repeats translated and its displacement each period
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SpaceshipInfo {
+    pub period: usize,
+    pub dx:     i64,
+    pub dy:     i64
+}
+
+// Live cells at any (x, y) position on an unbounded plane, nothing wraps
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SparseGrid {
+    cells:  HashSet<(i64, i64)>
+}
+
+impl SparseGrid {
+    // Return a SparseGrid with no live cells
+    pub fn new() -> SparseGrid {
+        SparseGrid::default()
+    }
+
+    // Return number of live cells
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    // Return true if there are no live cells
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Return (min_x, min_y, max_x, max_y) of live cells, or None if there are none
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.cells.iter().fold(None, |bounds, &(x, y)| match bounds {
+            None => Some((x, y, x, y)),
+            Some((x0, y0, x1, y1)) => Some((x0.min(x), y0.min(y), x1.max(x), y1.max(y)))
+        })
+    }
+
+    // Return true if the cell at given position is alive
+    pub fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.cells.contains(&(x, y))
+    }
+
+    // Set state of cell at given position
+    pub fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if alive { self.cells.insert((x, y)); } else { self.cells.remove(&(x, y)); }
+    }
+
+    // Return positions of live cells, in no particular order
+    pub fn live_cells(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.cells.iter().copied()
+    }
+
+    // Return a copy with cells shifted by given offsets
+    pub fn translate(&self, dx: i64, dy: i64) -> SparseGrid {
+        SparseGrid { cells: self.cells.iter().map(|&(x, y)| (x + dx, y + dy)).collect() }
+    }
+
+    // Return a copy shifted so that the bounding box of live cells has its top left
+    // at the origin, and the position it was shifted from
+    pub fn crop(&self) -> (SparseGrid, (i64, i64)) {
+        match self.bounding_box() {
+            Some((min_x, min_y, _, _)) => (self.translate(-min_x, -min_y), (min_x, min_y)),
+            None => (self.clone(), (0, 0))
+        }
+    }
+
+    // Step a copy using given rule until the pattern repeats a cropped state seen
+    // before, for at most max_generations, returning the period and displacement
+    // or None if the pattern dies or never repeats. Still lifes and oscillators
+    // are reported with no displacement
+    pub fn detect_spaceship(&self, rule: &Rule, max_generations: usize) -> Option<SpaceshipInfo> {
+        let mut seen = HashMap::new();
+        let mut pattern = self.clone();
+        for generation in 0..=max_generations {
+            if generation > 0 {
+                pattern = pattern.next_with_rule(rule);
+            }
+            if pattern.is_empty() {
+                return None;
+            }
+
+            let (cropped, (x, y)) = pattern.crop();
+            let mut cells: Vec<(i64, i64)> = cropped.live_cells().collect();
+            cells.sort_unstable();
+            if let Some(&(start, (start_x, start_y))) = seen.get(&cells) {
+                return Some(SpaceshipInfo { period: generation - start, dx: x - start_x, dy: y - start_y });
+            }
+            seen.insert(cells, (generation, (x, y)));
+        }
+        None
+    }
+
+    // Return next state under Conway's Life
+    pub fn next(&self) -> SparseGrid {
+        self.next_with_rule(&Rule::CONWAY)
+    }
+
+    // Return next state using given birth/survival rule, counting neighbours in
+    // its Neighbourhood. Only live cells and their neighbours are visited, so
+    // births on 0 neighbours (B0 rules) never happen on the unbounded plane
+    pub fn next_with_rule(&self, rule: &Rule) -> SparseGrid {
+        let offsets = rule.neighbourhood().offsets();
+
+        // Neighbour counts of every cell next to a live cell
+        let mut counts: HashMap<(i64, i64), u8> = HashMap::with_capacity(self.cells.len() * offsets.len());
+        for &(x, y) in &self.cells {
+            for &(dx, dy) in &offsets {
+                *counts.entry((x + dx as i64, y + dy as i64)).or_insert(0) += 1;
+            }
+        }
+
+        let mut cells: HashSet<(i64, i64)> = counts.into_iter()
+            .filter(|&(position, neighbours)| rule.apply(&Cell::new(self.cells.contains(&position)), neighbours).is_alive())
+            .map(|(position, _)| position)
+            .collect();
+        if rule.survival(0) {
+            cells.extend(self.cells.iter().filter(|&&(x, y)| offsets.iter().all(|&(dx, dy)| !self.is_alive(x - dx as i64, y - dy as i64))));
+        }
+        SparseGrid { cells }
+    }
+
+    // Return a Grid of given size showing the cells with top left at (x, y)
+    pub fn to_dense(&self, x: i64, y: i64, width: usize, height: usize) -> Result<Grid, LifeError> {
+        let mut grid = Grid::new(width, height)?;
+        for &(cx, cy) in &self.cells {
+            let (gx, gy) = (cx - x, cy - y);
+            if gx >= 0 && gy >= 0 {
+                grid.set(gx as usize, gy as usize, true).ok();
+            }
+        }
+        Ok(grid)
+    }
+}
+
+// Live cells of a Grid at the same positions, the Grid's edges no longer wrap
+impl From<&Grid> for SparseGrid {
+    fn from(grid: &Grid) -> SparseGrid {
+        SparseGrid { cells: grid.live_cells().map(|(x, y)| (x as i64, y as i64)).collect() }
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_should_move_glider_250_cells_diagonally_in_1000_generations() {
+        // given
+        let glider = SparseGrid::from(&Grid::from_text(".O.\n..O\nOOO", 'O').unwrap());
+        let mut sparse = glider.clone();
+
+        // when
+        for _ in 0..1000 {
+            sparse = sparse.next();
+        }
+
+        // then
+        assert_eq!(sparse.population(), 5);
+        assert_eq!(sparse.bounding_box(), Some((250, 250, 252, 252)));
+        assert_eq!(sparse.to_dense(250, 250, 3, 3).unwrap(), glider.to_dense(0, 0, 3, 3).unwrap());
+    }
+
+    #[test]
+    fn next_should_keep_block_in_place() {
+        // given
+        let mut sparse = SparseGrid::new();
+        for &(x, y) in &[(-1, -1), (0, -1), (-1, 0), (0, 0)] {
+            sparse.set(x, y, true);
+        }
+
+        // when
+        let next = sparse.next();
+
+        // then
+        assert_eq!(next, sparse);
+        assert_eq!(next.bounding_box(), Some((-1, -1, 0, 0)));
+    }
+
+    #[test]
+    fn next_should_match_grid_away_from_edges() {
+        // given
+        let mut grid = Grid::new(40, 40).unwrap();
+        grid.insert(&Grid::random_seeded(2, 10, 10), 15, 15, crate::InsertMode::Replace, crate::Overflow::Clip);
+        let mut sparse = SparseGrid::from(&grid);
+
+        for _ in 0..10 {
+            // when
+            grid = grid.next_with_rule(&Rule::HIGHLIFE);
+            sparse = sparse.next_with_rule(&Rule::HIGHLIFE);
+
+            // then
+            assert_eq!(sparse.to_dense(0, 0, 40, 40).unwrap(), grid);
+        }
+    }
+
+    #[test]
+    fn next_with_rule_should_keep_isolated_cells_given_survival_on_0() {
+        // given
+        let mut sparse = SparseGrid::new();
+        sparse.set(5, -5, true);
+
+        // then
+        assert_eq!(sparse.next_with_rule(&Rule::parse("B3/S0").unwrap()), sparse);
+        assert_eq!(sparse.next().is_empty(), true);
+    }
+
+    #[test]
+    fn detect_spaceship_should_report_period_and_displacement() {
+        for &(pattern, period, dx, dy) in &[(".O.\n..O\nOOO", 4, 1, 1), ("O..O.\n....O\nO...O\n.OOOO", 4, 2, 0), ("OOO", 2, 0, 0), ("OO\nOO", 1, 0, 0)] {
+            // given
+            let sparse = SparseGrid::from(&Grid::from_text(pattern, 'O').unwrap()).translate(-7, 3);
+
+            // when
+            let spaceship = sparse.detect_spaceship(&Rule::CONWAY, 10);
+
+            // then
+            assert_eq!(spaceship, Some(SpaceshipInfo { period, dx, dy }), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn detect_spaceship_should_return_none_given_pattern_dies_or_budget_exhausted() {
+        // given
+        let glider = SparseGrid::from(&Grid::from_text(".O.\n..O\nOOO", 'O').unwrap());
+        let dying = SparseGrid::from(&Grid::from_text("O..\n.O.\n..O", 'O').unwrap());
+
+        // then
+        assert_eq!(glider.detect_spaceship(&Rule::CONWAY, 3), None);
+        assert_eq!(dying.detect_spaceship(&Rule::CONWAY, 10), None);
+    }
+
+    #[test]
+    fn crop_should_move_bounding_box_to_origin() {
+        // given
+        let mut sparse = SparseGrid::new();
+        sparse.set(-4, 9, true);
+        sparse.set(-2, 7, true);
+
+        // when
+        let (cropped, origin) = sparse.crop();
+
+        // then
+        assert_eq!(origin, (-4, 7));
+        assert_eq!(cropped.bounding_box(), Some((0, 0, 2, 2)));
+        assert_eq!(cropped.translate(-4, 7), sparse);
+    }
+
+    #[test]
+    fn to_dense_should_clip_cells_outside_viewport() {
+        // given
+        let mut sparse = SparseGrid::new();
+        sparse.set(-3, 2, true);
+        sparse.set(1, 1, true);
+        sparse.set(9, 1, true);
+
+        // when
+        let grid = sparse.to_dense(-3, 0, 5, 3).unwrap();
+
+        // then
+        assert_eq!(grid, Grid::from_text(".....\n....O\nO....", 'O').unwrap());
+        assert_eq!(sparse.to_dense(0, 0, 0, 3), Err(LifeError::ZeroDimension { width: 0, height: 3 }));
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,61 @@
+/* stochastic.rs - Rules whose transitions fire with given probabilities
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use crate::{LifeError, Rule};
+
+/*****************************************************************************/
+
+// Birth/survival Rule where each birth happens with probability birth_prob and
+// each survival with probability survival_prob, cells otherwise die or stay dead
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StochasticRule {
+    rule:           Rule,
+    birth_prob:     f64,
+    survival_prob:  f64
+}
+
+impl StochasticRule {
+    // Return a StochasticRule of given Rule and probabilities between 0 and 1
+    pub fn new(rule: Rule, birth_prob: f64, survival_prob: f64) -> Result<StochasticRule, LifeError> {
+        for &prob in &[birth_prob, survival_prob] {
+            if !(0.0..=1.0).contains(&prob) {
+                return Err(LifeError::InvalidRule(format!("probability {} is out of range 0 to 1", prob)));
+            }
+        }
+        Ok(StochasticRule { rule, birth_prob, survival_prob })
+    }
+
+    // Return deterministic birth/survival Rule
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    // Return probability of a birth the Rule allows happening
+    pub fn birth_prob(&self) -> f64 {
+        self.birth_prob
+    }
+
+    // Return probability of a survival the Rule allows happening
+    pub fn survival_prob(&self) -> f64 {
+        self.survival_prob
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_should_reject_probabilities_out_of_range() {
+        assert_eq!(StochasticRule::new(Rule::CONWAY, 0.0, 1.0).is_ok(),   true);
+        assert_eq!(StochasticRule::new(Rule::CONWAY, -0.1, 1.0).is_err(), true);
+        assert_eq!(StochasticRule::new(Rule::CONWAY, 0.5, 1.1).is_err(),  true);
+        assert_eq!(StochasticRule::new(Rule::CONWAY, f64::NAN, 0.5).is_err(), true);
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,128 @@
+/* stream.rs - Simulations as asynchronous Streams of generations
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+// Each generation is stepped when the next item is polled for, never ahead of
+// the consumer, on tokio's blocking pool so that stepping a large Grid does not
+// stall the executor. The streams must be polled within a tokio runtime
+
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream};
+use tokio::task;
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+use crate::{Grid, Simulation};
+
+/*****************************************************************************/
+
+impl Simulation {
+    // Return a Stream of the Grid of each generation from the current one on, at
+    // most one per interval or, given a zero interval, as fast as it is consumed.
+    // A consumer slower than that delays later items rather than some being
+    // yielded at once to catch up
+    pub fn into_stream(self, interval: Duration) -> impl Stream<Item = Grid> + Send {
+        self.stream(Some(interval).filter(|interval| !interval.is_zero()))
+    }
+
+    // Return a Stream of the Grid of each generation from the current one on, as
+    // fast as it is consumed
+    pub fn stream_generations(self) -> impl Stream<Item = Grid> + Send {
+        self.stream(None)
+    }
+
+    // Return a Stream of the Grid of each generation, waiting for each tick of an
+    // Interval of given period if any. The Interval is made on the first poll, as
+    // it needs a runtime. The Stream ends if a step panics
+    fn stream(self, period: Option<Duration>) -> impl Stream<Item = Grid> + Send {
+        stream::unfold((self, None::<Interval>, false), move |(mut simulation, mut ticker, started)| async move {
+            if !started {
+                ticker = period.map(|period| {
+                    let mut ticker = time::interval(period);
+                    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                    ticker
+                });
+            }
+            if let Some(ticker) = ticker.as_mut() {
+                ticker.tick().await;
+            }
+            if started {
+                simulation = task::spawn_blocking(move || {
+                    simulation.step();
+                    simulation
+                }).await.ok()?;
+            }
+            Some((simulation.grid().clone(), (simulation, ticker, true)))
+        })
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use tokio::time::Instant;
+    use crate::Rule;
+
+    // Return Grids of the first n generations of a Simulation stepped in turn
+    fn stepped_grids(mut simulation: Simulation, n: usize) -> Vec<Grid> {
+        let mut grids = vec![simulation.grid().clone()];
+        for _ in 1..n {
+            simulation.step();
+            grids.push(simulation.grid().clone());
+        }
+        grids
+    }
+
+    fn simulation() -> Simulation {
+        Simulation::new(Grid::random_seeded(3, 24, 16), Rule::CONWAY)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn into_stream_should_yield_stepped_grids_once_per_interval() {
+        // given
+        let start = Instant::now();
+
+        // when
+        let grids: Vec<Grid> = simulation().into_stream(Duration::from_millis(100)).take(5).collect().await;
+
+        // then
+        assert_eq!(grids, stepped_grids(simulation(), 5));
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn into_stream_should_yield_stepped_grids_unpaced_given_zero_interval() {
+        // when
+        let grids: Vec<Grid> = simulation().into_stream(Duration::ZERO).take(5).collect().await;
+
+        // then
+        assert_eq!(grids, stepped_grids(simulation(), 5));
+    }
+
+    #[test]
+    fn into_stream_should_only_need_runtime_when_polled() {
+        // given
+        let stream = simulation().into_stream(Duration::from_millis(1));
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+
+        // when
+        let grids: Vec<Grid> = runtime.block_on(stream.take(3).collect());
+
+        // then
+        assert_eq!(grids, stepped_grids(simulation(), 3));
+    }
+
+    #[tokio::test]
+    async fn stream_generations_should_yield_stepped_grids() {
+        // when
+        let grids: Vec<Grid> = simulation().stream_generations().take(5).collect().await;
+
+        // then
+        assert_eq!(grids, stepped_grids(simulation(), 5));
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,144 @@
+/* svg.rs - Rendering Grids as SVG images
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt::Write;
+use alloc::string::{String, ToString};
+
+use crate::Grid;
+
+/*****************************************************************************/
+
+// Options for Grid::to_svg. Colours are any SVG paint such as "#00ff00" or "green"
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SvgOptions {
+    // Width and height in user units of each cell
+    pub cell_size:  u32,
+    // Paint of live cells
+    pub fill:       String,
+    // Paint of the background, none if not given
+    pub background: Option<String>,
+    // Paint of lines between cells, none if not given
+    pub grid_lines: Option<String>
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions { cell_size: 10, fill: "#000000".to_string(), background: Some("#ffffff".to_string()), grid_lines: None }
+    }
+}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return the cells as an SVG document with a rect for each run of live cells
+    // in a row, merging horizontal neighbours to keep the file small
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = options.cell_size as usize;
+        let (width, height) = (self.width * size, self.height * size);
+        let mut svg = String::new();
+
+        // Writing to a String cannot fail
+        let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height);
+        if let Some(background) = &options.background {
+            let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="{}"/>"#, width, height, escape(background));
+        }
+
+        let _ = writeln!(svg, r#"<g fill="{}">"#, escape(&options.fill));
+        for (y, row) in self.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if !row[x].alive {
+                    x += 1;
+                    continue;
+                }
+                let run = row[x..].iter().take_while(|cell| cell.alive).count();
+                let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#, x * size, y * size, run * size, size);
+                x += run;
+            }
+        }
+        let _ = writeln!(svg, "</g>");
+
+        if let Some(grid_lines) = &options.grid_lines {
+            let _ = write!(svg, r#"<path stroke="{}" stroke-width="1" fill="none" d=""#, escape(grid_lines));
+            for x in 0..=self.width {
+                let _ = write!(svg, "M{} 0V{}", x * size, height);
+            }
+            for y in 0..=self.height {
+                let _ = write!(svg, "M0 {}H{}", y * size, width);
+            }
+            let _ = writeln!(svg, r#""/>"#);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+// Return text with the chars that would end or break an XML attribute value replaced by entities
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('"', "&quot;")
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rects(svg: &str) -> Vec<&str> {
+        svg.lines().filter(|line| line.starts_with("<rect x=")).collect()
+    }
+
+    #[test]
+    fn to_svg_should_merge_runs_of_live_cells() {
+        // given
+        let grid = Grid::from_text(".OOOOO.\nO.O...O", 'O').unwrap();
+
+        // when
+        let svg = grid.to_svg(&SvgOptions { cell_size: 4, ..SvgOptions::default() });
+
+        // then
+        assert_eq!(rects(&svg), vec![
+            r#"<rect x="4" y="0" width="20" height="4"/>"#,
+            r#"<rect x="0" y="4" width="4" height="4"/>"#,
+            r#"<rect x="8" y="4" width="4" height="4"/>"#,
+            r#"<rect x="24" y="4" width="4" height="4"/>"#
+        ]);
+    }
+
+    #[test]
+    fn to_svg_should_write_document_with_options() {
+        // given
+        let grid = Grid::from_text("O.\n.O", 'O').unwrap();
+        let options = SvgOptions {
+            cell_size: 5, fill: "green".to_string(), background: None, grid_lines: Some("a\"<&b".to_string())
+        };
+
+        // then
+        assert_eq!(grid.to_svg(&options), concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\" viewBox=\"0 0 10 10\">\n",
+            "<g fill=\"green\">\n",
+            "<rect x=\"0\" y=\"0\" width=\"5\" height=\"5\"/>\n",
+            "<rect x=\"5\" y=\"5\" width=\"5\" height=\"5\"/>\n",
+            "</g>\n",
+            "<path stroke=\"a&quot;&lt;&amp;b\" stroke-width=\"1\" fill=\"none\" d=\"M0 0V10M5 0V10M10 0V10M0 0H10M0 5H10M0 10H10\"/>\n",
+            "</svg>\n"));
+    }
+
+    #[test]
+    fn to_svg_should_draw_background_given_default_options() {
+        // given
+        let svg = Grid::new(3, 2).unwrap().to_svg(&SvgOptions::default());
+
+        // then
+        assert!(svg.contains(r##"<rect width="30" height="20" fill="#ffffff"/>"##));
+        assert!(rects(&svg).is_empty());
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
+
+/*****************************************************************************/
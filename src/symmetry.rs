@@ -0,0 +1,212 @@
+/* symmetry.rs - Dihedral symmetries and canonical forms of Grid patterns
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::hash::{Hash, Hasher};
+
+use crate::{Fnv1a, Grid};
+
+/*****************************************************************************/
+
+// A transformation a pattern may be unchanged by, other than the identity
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    // Mirrored left to right
+    MirrorHorizontal,
+    // Mirrored top to bottom
+    MirrorVertical,
+    // Mirrored about the diagonal from the top left corner
+    MirrorDiagonal,
+    // Mirrored about the diagonal from the top right corner
+    MirrorAntiDiagonal,
+    // Rotated 90 degrees either way
+    Rotate90,
+    // Rotated 180 degrees
+    Rotate180
+}
+
+impl Symmetry {
+    const ALL: [Symmetry; 6] = [Symmetry::MirrorHorizontal, Symmetry::MirrorVertical, Symmetry::MirrorDiagonal,
+        Symmetry::MirrorAntiDiagonal, Symmetry::Rotate90, Symmetry::Rotate180];
+
+    // Return the pattern transformed by this symmetry
+    fn apply(self, pattern: &Grid) -> Grid {
+        match self {
+            Symmetry::MirrorHorizontal   => pattern.flip_horizontal(),
+            Symmetry::MirrorVertical     => pattern.flip_vertical(),
+            Symmetry::MirrorDiagonal     => pattern.rotate_cw().flip_horizontal(),
+            Symmetry::MirrorAntiDiagonal => pattern.rotate_ccw().flip_horizontal(),
+            Symmetry::Rotate90           => pattern.rotate_cw(),
+            Symmetry::Rotate180          => pattern.rotate_cw().rotate_cw()
+        }
+    }
+
+    // Return bit of SymmetrySet for this symmetry
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+// Set of the symmetries a pattern has
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SymmetrySet {
+    bits: u8
+}
+
+impl SymmetrySet {
+    // Set of every symmetry, as had by an empty pattern or a single cell
+    pub const ALL: SymmetrySet = SymmetrySet { bits: (1 << Symmetry::ALL.len()) - 1 };
+
+    // Return true if given symmetry is in the set
+    pub fn contains(&self, symmetry: Symmetry) -> bool {
+        self.bits & symmetry.bit() != 0
+    }
+
+    // Return true if the set has no symmetries
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    // Return an iterator over the symmetries in the set
+    pub fn iter(&self) -> impl Iterator<Item = Symmetry> + '_ {
+        Symmetry::ALL.iter().copied().filter(move |&symmetry| self.contains(symmetry))
+    }
+}
+
+/*****************************************************************************/
+
+impl Grid {
+    // Return the symmetries of the bounding box of the live cells, all given no
+    // live cells. Diagonal mirrors and 90 degree rotations need a square box
+    pub fn symmetries(&self) -> SymmetrySet {
+        let pattern = match self.crop() {
+            Some(pattern) => pattern,
+            None => return SymmetrySet::ALL
+        };
+
+        SymmetrySet {
+            bits: Symmetry::ALL.iter()
+                .filter(|symmetry| symmetry.apply(&pattern) == pattern)
+                .fold(0, |bits, symmetry| bits | symmetry.bit())
+        }
+    }
+
+    // Return the bounding box of the live cells in whichever orientation is least,
+    // ordered by width, height then cells in row major order with dead before live.
+    // The same object anywhere in any orientation has the same canonical form, a
+    // Grid with no live cells being a single dead cell
+    pub fn canonical(&self) -> Grid {
+        let pattern = match self.crop() {
+            Some(pattern) => pattern,
+            None => return Grid::new(1, 1).unwrap_or_else(|_| unreachable!("dimensions are non-zero"))
+        };
+
+        pattern.orientations().into_iter()
+            .min_by(|a, b| (a.width, a.height).cmp(&(b.width, b.height))
+                .then_with(|| a.cells.iter().map(|cell| cell.alive).cmp(b.cells.iter().map(|cell| cell.alive))))
+            .unwrap_or(pattern)
+    }
+
+    // Return hash of the canonical form, equal for the same object anywhere in any orientation
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        self.canonical().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symmetries_of(text: &str) -> Vec<Symmetry> {
+        Grid::from_text(text, 'O').unwrap().symmetries().iter().collect()
+    }
+
+    #[test]
+    fn symmetries_should_be_all_given_pulsar() {
+        // given
+        let pulsar = Grid::from_text(concat!(
+            "..OOO...OOO..\n", ".............\n", "O....O.O....O\n", "O....O.O....O\n",
+            "O....O.O....O\n", "..OOO...OOO..\n", ".............\n", "..OOO...OOO..\n",
+            "O....O.O....O\n", "O....O.O....O\n", "O....O.O....O\n", ".............\n",
+            "..OOO...OOO.."), 'O').unwrap();
+
+        // then
+        assert_eq!(pulsar.symmetries(), SymmetrySet::ALL);
+    }
+
+    #[test]
+    fn symmetries_should_be_empty_given_glider() {
+        assert!(Grid::from_text(".O.\n..O\nOOO", 'O').unwrap().symmetries().is_empty());
+    }
+
+    #[test]
+    fn symmetries_should_be_all_given_empty_grid_or_single_cell() {
+        assert_eq!(Grid::new(5, 5).unwrap().symmetries(), SymmetrySet::ALL);
+        assert_eq!(Grid::from_text("...\n.O.\n...", 'O').unwrap().symmetries(), SymmetrySet::ALL);
+    }
+
+    #[test]
+    fn symmetries_should_exclude_diagonals_given_non_square_bounding_box() {
+        assert_eq!(symmetries_of("OOO"), vec![Symmetry::MirrorHorizontal, Symmetry::MirrorVertical, Symmetry::Rotate180]);
+        assert_eq!(symmetries_of(".OO\nOO.\n"), vec![Symmetry::Rotate180]);
+    }
+
+    #[test]
+    fn symmetries_should_distinguish_diagonals() {
+        assert_eq!(symmetries_of("OO\nO."), vec![Symmetry::MirrorDiagonal]);
+        assert_eq!(symmetries_of("OO\n.O"), vec![Symmetry::MirrorAntiDiagonal]);
+        assert_eq!(symmetries_of("O..\n.O.\n..O"),
+            vec![Symmetry::MirrorDiagonal, Symmetry::MirrorAntiDiagonal, Symmetry::Rotate180]);
+    }
+
+    #[test]
+    fn canonical_hash_should_match_given_glider_phases_anywhere() {
+        // given
+        let mut grid = Grid::new(20, 20).unwrap();
+        grid.insert(&Grid::from_text(".O.\n..O\nOOO", 'O').unwrap(), 2, 3, crate::InsertMode::Replace, crate::Overflow::Clip);
+        let mut hashes = Vec::new();
+
+        for generation in 0..4 {
+            // when
+            let phase = grid.translate(generation * 3, 5 - generation);
+            hashes.extend(phase.orientations().iter().map(Grid::canonical_hash));
+            grid = grid.next_with_rule(&crate::Rule::CONWAY);
+        }
+
+        // then
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), 2);
+    }
+
+    #[test]
+    fn canonical_hash_should_give_same_value_on_every_target() {
+        // given
+        let glider = Grid::from_text(".O.\n..O\nOOO", 'O').unwrap();
+
+        // then
+        assert_eq!(glider.canonical_hash(), 0xee80_0247_ee8b_c162);
+    }
+
+    #[test]
+    fn canonical_should_match_given_rotated_eaters() {
+        // given
+        let eater = Grid::from_text("OO..\nO.O.\n..O.\n..OO", 'O').unwrap();
+        let mut grid = Grid::new(9, 7).unwrap();
+        grid.insert(&eater, 1, 2, crate::InsertMode::Replace, crate::Overflow::Clip);
+
+        // then
+        for orientation in grid.orientations() {
+            assert_eq!(orientation.canonical(), grid.canonical());
+            assert_eq!(orientation.canonical_hash(), eater.canonical_hash());
+        }
+        assert_ne!(eater.canonical_hash(), Grid::from_text("OO\nOO", 'O').unwrap().canonical_hash());
+        assert_eq!(Grid::new(3, 3).unwrap().canonical(), Grid::new(1, 1).unwrap());
+    }
+}
+
+/*****************************************************************************/
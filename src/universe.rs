@@ -0,0 +1,132 @@
+/* universe.rs - Operations shared by Grid storage types
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use core::fmt;
+use rand::Rng;
+
+use crate::{BitGrid, Grid, LifeError, Rule};
+
+/*****************************************************************************/
+
+// A toroidal universe of cells, implemented by each storage type so that
+// callers can switch between them
+pub trait Universe: Clone + fmt::Display + Sized {
+    // Return a universe of randomised cell states
+    fn random<R: Rng>(rng: R, width: usize, height: usize) -> Self;
+
+    // Return number of cells in a row
+    fn width(&self) -> usize;
+
+    // Return number of rows
+    fn height(&self) -> usize;
+
+    // Return true if the cell at given position is alive, false if out of bounds
+    fn is_alive(&self, x: usize, y: usize) -> bool;
+
+    // Set state of cell at given position
+    fn set(&mut self, x: usize, y: usize, alive: bool) -> Result<(), LifeError>;
+
+    // Return number of live cells
+    fn population(&self) -> usize;
+
+    // Return next state using given birth/survival rule
+    fn next_with_rule(&self, rule: &Rule) -> Self;
+}
+
+impl Universe for Grid {
+    fn random<R: Rng>(rng: R, width: usize, height: usize) -> Grid {
+        Grid::random(rng, width, height)
+    }
+
+    fn width(&self) -> usize {
+        Grid::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Grid::height(self)
+    }
+
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.get(x, y).is_some_and(|cell| cell.is_alive())
+    }
+
+    fn set(&mut self, x: usize, y: usize, alive: bool) -> Result<(), LifeError> {
+        Grid::set(self, x, y, alive)
+    }
+
+    fn population(&self) -> usize {
+        Grid::population(self)
+    }
+
+    fn next_with_rule(&self, rule: &Rule) -> Grid {
+        Grid::next_with_rule(self, rule)
+    }
+}
+
+impl Universe for BitGrid {
+    fn random<R: Rng>(rng: R, width: usize, height: usize) -> BitGrid {
+        BitGrid::random(rng, width, height)
+    }
+
+    fn width(&self) -> usize {
+        BitGrid::width(self)
+    }
+
+    fn height(&self) -> usize {
+        BitGrid::height(self)
+    }
+
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.get(x, y).is_some_and(|cell| cell.is_alive())
+    }
+
+    fn set(&mut self, x: usize, y: usize, alive: bool) -> Result<(), LifeError> {
+        BitGrid::set(self, x, y, alive)
+    }
+
+    fn population(&self) -> usize {
+        BitGrid::population(self)
+    }
+
+    fn next_with_rule(&self, rule: &Rule) -> BitGrid {
+        BitGrid::next_with_rule(self, rule)
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    // Return population after evolving a seeded soup, whatever the storage type
+    fn evolve<U: Universe>(mut universe: U, generations: usize) -> usize {
+        for _ in 0..generations {
+            universe = universe.next_with_rule(&Rule::CONWAY);
+        }
+        universe.population()
+    }
+
+    #[test]
+    fn universe_should_behave_the_same_for_each_storage_type() {
+        // given
+        let rng = StdRng::seed_from_u64(7);
+        let mut grid = <Grid as Universe>::random(rng.clone(), 40, 30);
+        let mut bits = <BitGrid as Universe>::random(rng, 40, 30);
+
+        // when
+        Universe::set(&mut grid, 39, 29, true).unwrap();
+        Universe::set(&mut bits, 39, 29, true).unwrap();
+
+        // then
+        assert!(grid.is_alive(39, 29) && bits.is_alive(39, 29));
+        assert!(!grid.is_alive(40, 0) && !bits.is_alive(40, 0));
+        assert_eq!(evolve(grid.clone(), 50), evolve(bits.clone(), 50));
+        assert_eq!(grid.to_string(), bits.to_string());
+    }
+}
+
+/*****************************************************************************/
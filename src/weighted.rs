@@ -0,0 +1,58 @@
+/* weighted.rs - Rules on weighted sums of neighbours
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use alloc::vec::Vec;
+
+use crate::Cell;
+
+/*****************************************************************************/
+
+// Birth and survival conditions on the sum of the weights of live cells in the
+// 3x3 block around a cell. Weights are in row major order NW, N, NE, W, centre,
+// E, SW, S, SE so that Conway's Life is all weights 1, births on 3 and survival
+// on 3 or 4 as a live centre counts itself
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct WeightedRule {
+    weights:    [i32; 9],
+    birth:      Vec<i32>,
+    survival:   Vec<i32>
+}
+
+impl WeightedRule {
+    // Return a WeightedRule given cell weights and the weighted sums causing birth and survival
+    pub fn new(weights: [i32; 9], birth: Vec<i32>, survival: Vec<i32>) -> WeightedRule {
+        WeightedRule { weights, birth, survival }
+    }
+
+    // Return weights of the 3x3 block in row major order
+    pub fn weights(&self) -> &[i32; 9] {
+        &self.weights
+    }
+
+    // Return next cell state given the weighted sum of live cells around it
+    pub fn apply(&self, cell: &Cell, sum: i32) -> Cell {
+        Cell::new(if cell.is_alive() { self.survival.contains(&sum) } else { self.birth.contains(&sum) })
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_should_check_sum_against_birth_or_survival() {
+        // given
+        let rule = WeightedRule::new([1; 9], vec![3], vec![3, 4]);
+
+        // then
+        assert_eq!(rule.apply(&Cell::dead(), 3),  Cell::alive());
+        assert_eq!(rule.apply(&Cell::dead(), 4),  Cell::dead());
+        assert_eq!(rule.apply(&Cell::alive(), 4), Cell::alive());
+        assert_eq!(rule.apply(&Cell::alive(), 2), Cell::dead());
+    }
+}
+
+/*****************************************************************************/
@@ -0,0 +1,81 @@
+/* parallelism.rs - Integration tests of stepping with each Parallelism
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use rs_life::{Boundary, Cell, Grid, Parallelism, Rule, Simulation};
+
+/*****************************************************************************/
+
+// Return each Parallelism, including a dedicated pool of two threads
+#[cfg(feature = "parallel")]
+fn parallelisms() -> Vec<Parallelism> {
+    vec![Parallelism::Auto, Parallelism::Serial, Parallelism::Parallel, Parallelism::threads(2).unwrap()]
+}
+
+// Return each Parallelism, all stepping on the calling thread without the parallel feature
+#[cfg(not(feature = "parallel"))]
+fn parallelisms() -> Vec<Parallelism> {
+    vec![Parallelism::Auto, Parallelism::Serial, Parallelism::Parallel]
+}
+
+#[test]
+fn next_with_parallelism_should_give_same_grid_for_each_parallelism() {
+    for &(width, height) in &[(1, 1), (80, 24), (200, 60), (300, 200)] {
+        // given
+        let grid = Grid::random_seeded(3, width, height);
+        let expected = grid.next_serial(Cell::next);
+
+        for parallelism in parallelisms() {
+            // when
+            let next = grid.next_with_parallelism(Cell::next, &parallelism);
+
+            // then
+            assert_eq!(next, expected, "{}x{} {:?}", width, height, parallelism);
+        }
+    }
+}
+
+#[test]
+fn simulation_should_give_same_grids_for_each_parallelism() {
+    for &boundary in &[Boundary::Torus, Boundary::Klein] {
+        // given
+        let grid = Grid::random_seeded(4, 250, 150);
+        let mut expected = Simulation::new(grid.clone(), Rule::HIGHLIFE).with_boundary(boundary)
+            .with_parallelism(Parallelism::Serial).with_incremental(false);
+        let mut simulations: Vec<Simulation> = parallelisms().into_iter()
+            .map(|parallelism| Simulation::new(grid.clone(), Rule::HIGHLIFE).with_boundary(boundary)
+                .with_parallelism(parallelism).with_incremental(false))
+            .collect();
+
+        for generation in 0..20 {
+            // when
+            expected.step();
+            for simulation in simulations.iter_mut() {
+                simulation.step();
+            }
+
+            // then
+            for simulation in &simulations {
+                assert_eq!(simulation.grid(), expected.grid(), "{:?} generation {}", boundary, generation);
+            }
+        }
+    }
+}
+
+#[test]
+fn seeded_soup_should_give_same_grid_with_or_without_parallel_feature() {
+    // given
+    let grid = Grid::random_seeded(42, 300, 200);
+    let checksum = |grid: &Grid| grid.live_cells()
+        .fold(0u64, |sum, (x, y)| sum.wrapping_mul(31).wrapping_add((y * grid.width() + x) as u64));
+
+    // when
+    let next = grid.step_n(100, Cell::next);
+
+    // then
+    // Both builds are pinned to the same result, so they cannot diverge
+    assert_eq!((next.population(), checksum(&next)), (5519, 1504724415785951612));
+    assert_eq!(next.diff(&grid).map(|diff| diff.births.len() + diff.deaths.len() > 0), Ok(true));
+}
+
+/*****************************************************************************/
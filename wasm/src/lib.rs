@@ -0,0 +1,168 @@
+/* lib.rs - WebAssembly bindings for driving rs-life from JavaScript
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+use wasm_bindgen::prelude::*;
+
+use rs_life::{Grid, Pattern, Rule, Simulation};
+
+/*****************************************************************************/
+
+// Toroidal universe of cells stepped under Conway's Life, or the rule of the last
+// RLE pattern loaded.
+// cells_ptr() points at width() * height() bytes in wasm memory, one per cell in
+// row major order from the top left, 1 for a live cell and 0 for a dead one. The
+// bytes are rewritten in place by each call changing cells and stay at the same
+// address for the life of the universe, but views of wasm memory are detached if
+// it grows, so JavaScript should make a new view from memory.buffer if so
+#[wasm_bindgen]
+pub struct Universe {
+    simulation: Simulation,
+    cells:      Vec<u8>
+}
+
+#[wasm_bindgen]
+impl Universe {
+    // Return a universe of given size with every cell dead
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize) -> Result<Universe, JsError> {
+        let mut universe = Universe {
+            simulation: Simulation::new(Grid::new(width, height)?, Rule::CONWAY),
+            cells:      vec![0; width * height]
+        };
+        universe.update_cells();
+        Ok(universe)
+    }
+
+    // Replace every cell with a random state, a fraction density of them alive,
+    // the same seed giving the same cells
+    pub fn randomize(&mut self, seed: u32, density: f64) -> Result<(), JsError> {
+        if !(0.0..=1.0).contains(&density) {
+            return Err(JsError::new(&format!("density {} is outside of the range 0.0 to 1.0", density)));
+        }
+        let grid = Grid::random_seeded_with_density(seed as u64, self.width(), self.height(), density);
+        self.simulation = Simulation::new(grid, *self.simulation.rule());
+        self.update_cells();
+        Ok(())
+    }
+
+    // Advance to the next generation
+    pub fn tick(&mut self) {
+        self.simulation.step();
+        self.update_cells();
+    }
+
+    // Return number of cells in a row
+    pub fn width(&self) -> usize {
+        self.simulation.grid().width()
+    }
+
+    // Return number of rows
+    pub fn height(&self) -> usize {
+        self.simulation.grid().height()
+    }
+
+    // Return address in wasm memory of the byte per cell
+    pub fn cells_ptr(&self) -> *const u8 {
+        self.cells.as_ptr()
+    }
+
+    // Set state of cell at given position
+    pub fn set_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), JsError> {
+        self.simulation.grid_mut().set(x, y, alive)?;
+        let width = self.width();
+        self.cells[y * width + x] = alive as u8;
+        Ok(())
+    }
+
+    // Replace every cell with the pattern of given RLE text centred in the universe,
+    // clipped at the edges, stepping under its rule from then on if it has one
+    pub fn load_rle(&mut self, text: &str) -> Result<(), JsError> {
+        let pattern = Pattern::from_rle(text)?;
+        let rule = pattern.rule.unwrap_or(*self.simulation.rule());
+        let x = self.width().saturating_sub(pattern.width()) / 2;
+        let y = self.height().saturating_sub(pattern.height()) / 2;
+        self.simulation = Simulation::new(pattern.to_grid(self.width(), self.height(), x, y)?, rule);
+        self.update_cells();
+        Ok(())
+    }
+}
+
+impl Universe {
+    // Rewrite the byte per cell from the grid
+    fn update_cells(&mut self) {
+        let grid = self.simulation.grid();
+        for (index, byte) in self.cells.iter_mut().enumerate() {
+            *byte = grid.get(index % grid.width(), index / grid.width()).is_some_and(|cell| cell.is_alive()) as u8;
+        }
+    }
+
+    // Return the byte per cell the memory at cells_ptr() holds
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+}
+
+/*****************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_should_hold_a_byte_per_cell_in_row_major_order() {
+        // given
+        let mut universe = Universe::new(5, 4).unwrap();
+
+        // when
+        universe.set_cell(1, 2, true).unwrap();
+        universe.set_cell(4, 3, true).unwrap();
+
+        // then
+        assert_eq!(universe.cells(), &[
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+            0, 1, 0, 0, 0,
+            0, 0, 0, 0, 1
+        ]);
+        assert_eq!(universe.cells_ptr(), universe.cells().as_ptr());
+    }
+
+    #[test]
+    fn tick_should_update_cells_in_place() {
+        // given
+        let mut universe = Universe::new(5, 5).unwrap();
+        universe.load_rle("x = 3, y = 1, rule = B3/S23\n3o!").unwrap();
+        let ptr = universe.cells_ptr();
+
+        // when
+        universe.tick();
+
+        // then
+        assert_eq!(universe.cells_ptr(), ptr);
+        assert_eq!(universe.cells(), &[
+            0, 0, 0, 0, 0,
+            0, 0, 1, 0, 0,
+            0, 0, 1, 0, 0,
+            0, 0, 1, 0, 0,
+            0, 0, 0, 0, 0
+        ]);
+    }
+
+    #[test]
+    fn randomize_should_give_same_cells_given_same_seed() {
+        // given
+        let mut a = Universe::new(32, 16).unwrap();
+        let mut b = Universe::new(32, 16).unwrap();
+
+        // when
+        a.randomize(7, 0.5).unwrap();
+        b.randomize(7, 0.5).unwrap();
+
+        // then
+        assert_eq!(a.cells(), b.cells());
+        assert!(a.cells().contains(&1));
+    }
+}
+
+/*****************************************************************************/
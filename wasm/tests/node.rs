@@ -0,0 +1,57 @@
+/* node.rs - Tests of the bindings run under Node.js by wasm-pack test --node
+*  (c)2020 James Wright, see LICENSE file.
+*/
+
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+use rs_life_wasm::Universe;
+
+/*****************************************************************************/
+
+#[wasm_bindgen_test]
+fn glider_should_move_through_cells_buffer() {
+    // given
+    let mut universe = Universe::new(6, 6).unwrap();
+    universe.load_rle("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+
+    // when
+    for _ in 0..4 {
+        universe.tick();
+    }
+
+    // then
+    let cells = unsafe { std::slice::from_raw_parts(universe.cells_ptr(), universe.width() * universe.height()) };
+    assert_eq!(cells, &[
+        0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0,
+        0, 0, 0, 1, 0, 0,
+        0, 0, 0, 0, 1, 0,
+        0, 0, 1, 1, 1, 0,
+        0, 0, 0, 0, 0, 0
+    ]);
+}
+
+#[wasm_bindgen_test]
+fn errors_should_be_returned_to_javascript() {
+    assert!(Universe::new(0, 4).is_err());
+    assert!(Universe::new(4, 4).unwrap().set_cell(4, 0, true).is_err());
+    assert!(Universe::new(4, 4).unwrap().randomize(1, 1.5).is_err());
+    assert!(Universe::new(4, 4).unwrap().load_rle("x = 1, y = 1\nq!").is_err());
+}
+
+#[wasm_bindgen_test]
+fn randomize_should_populate_given_density() {
+    // given
+    let mut universe = Universe::new(100, 100).unwrap();
+
+    // when
+    universe.randomize(42, 0.25).unwrap();
+
+    // then
+    let population = universe.cells().iter().filter(|&&cell| cell == 1).count();
+    assert!((2000..3000).contains(&population), "{}", population);
+}
+
+/*****************************************************************************/